@@ -3,10 +3,20 @@
 //! Attribute macros to control how [cargo-mutants](https://crates.io/crates/cargo-mutants) mutates code.
 //!
 //! For example, a function that is difficult to test, or has disruptive effects when mutated, can
-//! be marked with [macro@skip].
+//! be marked with [macro@skip]. [macro@skip_ops] turns off only some mutation operators,
+//! [macro@timeout] allows the tests more time when a function is mutated, and
+//! [macro@replace_with] gives the values a function should be mutated to return.
 //!
 //! # Changelog
 //!
+//! ## 0.0.5
+//!
+//! * New attribute `replace_with`.
+//!
+//! ## 0.0.4
+//!
+//! * New attributes `skip_ops` and `timeout`.
+//!
 //! ## 0.0.3
 //!
 //! * Reset edition to 2018 for broader compatibility.
@@ -29,3 +39,64 @@ use proc_macro::TokenStream;
 pub fn skip(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
+
+/// `cargo mutants` should not generate mutants from the named mutation operators in this
+/// function.
+///
+/// This can be applied to functions, methods, `impl` blocks, and inline modules, and applies to
+/// all the functions inside them. The operator names are those listed in the cargo-mutants
+/// documentation, or the names of plugins.
+///
+/// ```
+/// #[mutants::skip_ops(unit)]
+/// pub fn log_progress(message: &str) {
+///     // ...
+/// }
+/// ```
+///
+/// This is a no-op during compilation, but is seen by cargo-mutants as it processes the source.
+#[proc_macro_attribute]
+pub fn skip_ops(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// `cargo mutants` should stop the tests for mutants of this function after this many seconds,
+/// rather than the timeout for the whole run.
+///
+/// This can be applied to functions, methods, `impl` blocks, and inline modules, and applies to
+/// all the functions inside them.
+///
+/// ```
+/// #[mutants::timeout(120)]
+/// pub fn converge() -> bool {
+///     // ...
+///     # true
+/// }
+/// ```
+///
+/// This is a no-op during compilation, but is seen by cargo-mutants as it processes the source.
+#[proc_macro_attribute]
+pub fn timeout(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// `cargo mutants` should replace the body of this function with each of these expressions,
+/// rather than the values chosen from its return type.
+///
+/// This applies only to the function it's attached to. Each expression is given as a string,
+/// and makes one mutant.
+///
+/// ```
+/// pub struct Port(u16);
+///
+/// #[mutants::replace_with("Port(1)", "Port(u16::MAX)")]
+/// pub fn default_port() -> Port {
+///     Port(8080)
+/// }
+/// ```
+///
+/// This is a no-op during compilation, but is seen by cargo-mutants as it processes the source.
+#[proc_macro_attribute]
+pub fn replace_with(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
@@ -0,0 +1,350 @@
+// Copyright 2022 Martin Pool
+
+//! Read Cargo's own configuration (`.cargo/config.toml`) for settings that affect how the
+//! scratch tree is built.
+//!
+//! The scratch tree is copied out of its original location, so Cargo config files in
+//! directories above the source tree don't apply to it, and a relative `target-dir`
+//! configured in the tree is resolved differently. The settings that matter are collected
+//! here and passed to Cargo in the scratch tree through environment variables, or, for
+//! `[patch]` tables, written into the scratch tree's own config file.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+
+/// Settings read from Cargo config files and environment variables.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CargoConfig {
+    /// The absolute path of the build directory, if it's configured.
+    pub target_dir: Option<PathBuf>,
+
+    /// The target triple to build for, from `build.target`.
+    pub build_target: Option<String>,
+
+    /// Extra flags to pass to rustc, from `build.rustflags`.
+    pub rustflags: Option<String>,
+
+    /// `[patch]` tables, by registry and then by crate name, with any `path` made absolute.
+    pub patch: toml::value::Table,
+}
+
+impl CargoConfig {
+    /// Read the Cargo config that applies to a source tree, from the tree and all its
+    /// parent directories.
+    ///
+    /// As in Cargo, settings in directories closer to the tree take precedence, and the
+    /// `CARGO_TARGET_DIR` and `CARGO_BUILD_TARGET_DIR` environment variables, looked up with
+    /// `env_var`, normally [env::var_os], override the configured target directory.
+    pub fn read<F>(source_root: &Path, env_var: F) -> Result<CargoConfig>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        let source_root = fs::canonicalize(source_root)
+            .with_context(|| format!("canonicalize {}", source_root.to_slash_lossy()))?;
+        let mut config = CargoConfig::default();
+        for dir in source_root.ancestors() {
+            if let Some(path) = config_file_in(dir) {
+                let text = fs::read_to_string(&path)
+                    .with_context(|| format!("read cargo config {}", path.to_slash_lossy()))?;
+                let toml: toml::Value = text
+                    .parse()
+                    .with_context(|| format!("parse cargo config {}", path.to_slash_lossy()))?;
+                config.merge_from(&toml, dir);
+            }
+        }
+        for var in ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"] {
+            if let Some(target_dir) = env_var(var) {
+                config.target_dir = Some(env::current_dir()?.join(target_dir));
+                break;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Fill in any settings that aren't already set from a parsed config file that applies
+    /// to `dir`.
+    fn merge_from(&mut self, toml: &toml::Value, dir: &Path) {
+        if let Some(patches) = toml.get("patch").and_then(|p| p.as_table()) {
+            for (registry, patch) in patches {
+                let merged = self
+                    .patch
+                    .entry(registry.clone())
+                    .or_insert_with(|| toml::Value::Table(Default::default()));
+                if let (Some(merged), Some(patch)) = (merged.as_table_mut(), patch.as_table()) {
+                    for (name, spec) in patch {
+                        if !merged.contains_key(name) {
+                            let mut spec = spec.clone();
+                            if let Some(toml::Value::String(path)) = spec.get_mut("path") {
+                                *path = dir.join(&path).to_slash_lossy();
+                            }
+                            merged.insert(name.clone(), spec);
+                        }
+                    }
+                }
+            }
+        }
+        let build = match toml.get("build") {
+            Some(build) => build,
+            None => return,
+        };
+        if self.target_dir.is_none() {
+            self.target_dir = build
+                .get("target-dir")
+                .and_then(|v| v.as_str())
+                .map(|target_dir| dir.join(target_dir));
+        }
+        if self.build_target.is_none() {
+            match build.get("target") {
+                Some(toml::Value::String(target)) => self.build_target = Some(target.clone()),
+                Some(_) => eprintln!(
+                    "warning: build.target in cargo config in {} is not a single string, and is not passed to the scratch tree",
+                    dir.to_slash_lossy()
+                ),
+                None => (),
+            }
+        }
+        if self.rustflags.is_none() {
+            self.rustflags = match build.get("rustflags") {
+                Some(toml::Value::String(flags)) => Some(flags.clone()),
+                Some(toml::Value::Array(flags)) => Some(
+                    flags
+                        .iter()
+                        .filter_map(|flag| flag.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                _ => None,
+            };
+        }
+    }
+
+    /// Return the tree-relative path of the build directory: either the configured target
+    /// directory if it's inside the tree, or otherwise `target`.
+    pub fn tree_target_dir(&self, source_root: &Path) -> PathBuf {
+        let source_root = fs::canonicalize(source_root).unwrap_or_else(|_| source_root.to_owned());
+        self.target_dir
+            .as_ref()
+            .and_then(|target_dir| target_dir.strip_prefix(&source_root).ok())
+            .and_then(crate::source::normalize_relative_path)
+            .filter(|path| !path.as_os_str().is_empty())
+            .unwrap_or_else(|| PathBuf::from("target"))
+    }
+
+    /// Return environment variables to pass to Cargo when building a scratch copy of the tree
+    /// in `scratch_dir`.
+    ///
+    /// The target directory is always set to the copy of the tree's own build directory
+    /// within the scratch tree, so that builds of mutants can never write into the real
+    /// target directory, wherever it's configured to be.
+    pub fn scratch_env(&self, source_root: &Path, scratch_dir: &Path) -> Vec<(String, String)> {
+        let mut env = vec![(
+            "CARGO_TARGET_DIR".to_owned(),
+            scratch_dir
+                .join(self.tree_target_dir(source_root))
+                .to_string_lossy()
+                .into_owned(),
+        )];
+        if let Some(build_target) = &self.build_target {
+            env.push(("CARGO_BUILD_TARGET".to_owned(), build_target.clone()));
+        }
+        if let Some(rustflags) = &self.rustflags {
+            env.push(("CARGO_BUILD_RUSTFLAGS".to_owned(), rustflags.clone()));
+        }
+        env
+    }
+
+    /// Return the `[patch]` tables to use in a scratch copy of the tree in `scratch_dir`, or None
+    /// if there are none.
+    ///
+    /// Patches that point inside the source tree are redirected to the copy in the scratch
+    /// directory; others keep pointing to their original absolute location.
+    pub fn scratch_patch(&self, source_root: &Path, scratch_dir: &Path) -> Option<toml::Value> {
+        if self.patch.is_empty() {
+            return None;
+        }
+        let source_root = fs::canonicalize(source_root).unwrap_or_else(|_| source_root.to_owned());
+        let mut patch = self.patch.clone();
+        for (_registry, crates) in patch.iter_mut() {
+            if let Some(crates) = crates.as_table_mut() {
+                for (_name, spec) in crates.iter_mut() {
+                    if let Some(toml::Value::String(path)) = spec.get_mut("path") {
+                        if let Ok(relative) = Path::new(path.as_str()).strip_prefix(&source_root) {
+                            *path = scratch_dir.join(relative).to_slash_lossy();
+                        }
+                    }
+                }
+            }
+        }
+        Some(toml::Value::Table(patch))
+    }
+
+    /// Write any `[patch]` tables that apply to the source tree into the Cargo config of the
+    /// scratch copy in `scratch_dir`.
+    ///
+    /// Cargo config files above the source tree don't apply to the scratch copy, and relative
+    /// paths in those within the tree may point outside it, so without this dependencies could
+    /// be resolved differently in the scratch copy.
+    pub fn write_scratch_patch(&self, source_root: &Path, scratch_dir: &Path) -> Result<()> {
+        let patch = match self.scratch_patch(source_root, scratch_dir) {
+            Some(patch) => patch,
+            None => return Ok(()),
+        };
+        let path = config_file_in(scratch_dir)
+            .unwrap_or_else(|| scratch_dir.join(".cargo").join("config.toml"));
+        let mut config = if path.is_file() {
+            fs::read_to_string(&path)
+                .with_context(|| format!("read cargo config {}", path.to_slash_lossy()))?
+                .parse()
+                .with_context(|| format!("parse cargo config {}", path.to_slash_lossy()))?
+        } else {
+            fs::create_dir_all(path.parent().expect("config file has a parent"))?;
+            toml::Value::Table(Default::default())
+        };
+        if let Some(config) = config.as_table_mut() {
+            config.insert("patch".to_owned(), patch);
+        }
+        let toml = toml::to_string_pretty(&config).context("serialize cargo config")?;
+        fs::write(&path, toml)
+            .with_context(|| format!("write cargo config {}", path.to_slash_lossy()))
+    }
+}
+
+/// Return the path of the Cargo config file in a directory, if there is one.
+///
+/// Like Cargo, this prefers `.cargo/config` if both it and `.cargo/config.toml` exist.
+fn config_file_in(dir: &Path) -> Option<PathBuf> {
+    ["config", "config.toml"]
+        .iter()
+        .map(|name| dir.join(".cargo").join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn nearer_config_takes_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outer = fs::canonicalize(tmp.path()).unwrap();
+        let tree = outer.join("tree");
+        fs::create_dir_all(outer.join(".cargo")).unwrap();
+        fs::create_dir_all(tree.join(".cargo")).unwrap();
+        fs::write(
+            outer.join(".cargo/config.toml"),
+            "[build]\ntarget = 'wasm32-unknown-unknown'\nrustflags = ['-C', 'debuginfo=0']\ntarget-dir = 'shared-target'\n",
+        )
+        .unwrap();
+        fs::write(
+            tree.join(".cargo/config.toml"),
+            "[build]\ntarget-dir = 'out'\n",
+        )
+        .unwrap();
+
+        let config = CargoConfig::read(&tree, |_| None).unwrap();
+        assert_eq!(
+            config.build_target.as_deref(),
+            Some("wasm32-unknown-unknown")
+        );
+        assert_eq!(config.rustflags.as_deref(), Some("-C debuginfo=0"));
+        assert_eq!(config.target_dir, Some(tree.join("out")));
+        assert_eq!(config.tree_target_dir(&tree), PathBuf::from("out"));
+        assert_eq!(
+            config.scratch_env(&tree, Path::new("/scratch")),
+            [
+                (
+                    "CARGO_TARGET_DIR".to_owned(),
+                    Path::new("/scratch")
+                        .join("out")
+                        .to_string_lossy()
+                        .into_owned()
+                ),
+                (
+                    "CARGO_BUILD_TARGET".to_owned(),
+                    "wasm32-unknown-unknown".to_owned()
+                ),
+                (
+                    "CARGO_BUILD_RUSTFLAGS".to_owned(),
+                    "-C debuginfo=0".to_owned()
+                ),
+            ]
+        );
+
+        // The environment overrides both files.
+        let target_dir = outer.join("env-target");
+        for var in ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"] {
+            let config = CargoConfig::read(&tree, |name| {
+                (name == var).then(|| target_dir.clone().into_os_string())
+            })
+            .unwrap();
+            assert_eq!(config.target_dir.as_ref(), Some(&target_dir));
+            assert_eq!(config.tree_target_dir(&tree), PathBuf::from("target"));
+        }
+    }
+
+    #[test]
+    fn patches_are_merged_and_redirected_to_scratch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outer = fs::canonicalize(tmp.path()).unwrap();
+        let tree = outer.join("tree");
+        let scratch = outer.join("scratch");
+        for dir in [&outer, &tree] {
+            fs::create_dir_all(dir.join(".cargo")).unwrap();
+        }
+        fs::create_dir_all(&scratch).unwrap();
+        fs::write(
+            outer.join(".cargo/config.toml"),
+            "[patch.crates-io]\nfoo = { path = 'vendor/foo' }\nbar = { path = 'vendor/bar' }\n",
+        )
+        .unwrap();
+        fs::write(
+            tree.join(".cargo/config.toml"),
+            "[patch.crates-io]\nbar = { path = 'patched/bar' }\n",
+        )
+        .unwrap();
+
+        let mut config = CargoConfig::default();
+        for dir in tree.ancestors().take(2) {
+            let path = config_file_in(dir).unwrap();
+            config.merge_from(&fs::read_to_string(path).unwrap().parse().unwrap(), dir);
+        }
+        let patch = config.scratch_patch(&tree, &scratch).unwrap();
+        assert_eq!(
+            patch["crates-io"]["foo"]["path"].as_str().unwrap(),
+            outer.join("vendor/foo").to_slash_lossy()
+        );
+        assert_eq!(
+            patch["crates-io"]["bar"]["path"].as_str().unwrap(),
+            scratch.join("patched/bar").to_slash_lossy()
+        );
+
+        config.write_scratch_patch(&tree, &scratch).unwrap();
+        let written: toml::Value = fs::read_to_string(scratch.join(".cargo/config.toml"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(written["patch"], patch);
+        assert_eq!(CargoConfig::default().scratch_patch(&tree, &scratch), None);
+    }
+
+    #[test]
+    fn target_dir_outside_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = CargoConfig {
+            target_dir: Some(PathBuf::from("/elsewhere/target")),
+            ..CargoConfig::default()
+        };
+        assert_eq!(config.tree_target_dir(tmp.path()), PathBuf::from("target"));
+        assert_eq!(
+            CargoConfig::default().tree_target_dir(tmp.path()),
+            PathBuf::from("target")
+        );
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright 2022 Martin Pool
+
+//! `cargo mutants clean`: remove output and scratch directories left behind by earlier runs.
+//!
+//! Scratch directories are normally deleted when cargo-mutants exits, but they can leak if
+//! it's interrupted or crashes. To recognize them later, each scratch directory is created
+//! with a distinctive prefix and holds a marker file naming the source tree it was copied from.
+//!
+//! On Windows, the build products in a scratch directory can be nested more deeply than the
+//! traditional 260-character path limit, and files can stay locked for a moment after a test
+//! process exits, or while antivirus software scans them. So scratch directories are copied
+//! into and removed through extended-length paths, and removal is retried before giving up.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+use walkdir::WalkDir;
+
+use crate::output::{OUTDIR_NAME, ROTATED_NAME};
+
+/// Prefix of the names of scratch directories in the temporary directory.
+const SCRATCH_PREFIX: &str = "cargo-mutants-";
+
+/// Name of the file, within a scratch directory, that holds the path of the source tree.
+const SOURCE_MARKER_NAME: &str = "source-tree";
+
+/// How long to wait before each retry of a filesystem operation that failed.
+const RETRY_DELAYS: [Duration; 5] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+];
+
+/// A scratch directory, which is removed when it's dropped.
+#[derive(Debug)]
+pub struct ScratchDir {
+    /// The path of the directory, or empty if it's been kept by [ScratchDir::into_path].
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keep the directory rather than removing it, and return its path.
+    #[cfg(test)]
+    fn into_path(mut self) -> PathBuf {
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if self.path.as_os_str().is_empty() {
+            return;
+        }
+        if let Err(err) = remove_dir_all(&self.path) {
+            // Failing to clean up shouldn't fail the run, which is otherwise finished.
+            eprintln!(
+                "warning: failed to remove scratch directory {}: {}; `cargo mutants clean` can remove it later",
+                self.path.to_slash_lossy(),
+                err
+            );
+        }
+    }
+}
+
+/// A directory removed by [clean], and the number of bytes it held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removed {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Create a scratch directory for mutants of the given source tree, marked so that it can
+/// be found by [clean] if it's leaked.
+pub fn create_scratch_dir(source_root: &Path) -> Result<ScratchDir> {
+    create_scratch_dir_in(source_root, &env::temp_dir())
+}
+
+fn create_scratch_dir_in(source_root: &Path, temp_root: &Path) -> Result<ScratchDir> {
+    let tmp_dir = ScratchDir {
+        path: tempfile::Builder::new()
+            .prefix(SCRATCH_PREFIX)
+            .tempdir_in(temp_root)
+            .context("create scratch directory")?
+            .into_path(),
+    };
+    let marker = tmp_dir.path().join(SOURCE_MARKER_NAME);
+    fs::write(
+        &marker,
+        canonical_source_root(source_root)?.to_slash_lossy(),
+    )
+    .with_context(|| format!("write {}", marker.to_slash_lossy()))?;
+    Ok(tmp_dir)
+}
+
+/// Remove `mutants.out`, `mutants.out.old`, and any leaked scratch directories for this tree.
+///
+/// Returns the directories that were removed, and how big they were.
+pub fn clean(source_root: &Path) -> Result<Vec<Removed>> {
+    clean_in(source_root, &env::temp_dir())
+}
+
+fn clean_in(source_root: &Path, temp_root: &Path) -> Result<Vec<Removed>> {
+    let mut paths = vec![
+        source_root.join(OUTDIR_NAME),
+        source_root.join(ROTATED_NAME),
+    ];
+    paths.extend(leaked_scratch_dirs(source_root, temp_root)?);
+    let mut removed = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            continue;
+        }
+        let bytes = dir_size(&path);
+        remove_dir_all(&path).with_context(|| format!("remove {}", path.to_slash_lossy()))?;
+        removed.push(Removed { path, bytes });
+    }
+    Ok(removed)
+}
+
+/// Find scratch directories in `temp_root` whose marker names this source tree.
+fn leaked_scratch_dirs(source_root: &Path, temp_root: &Path) -> Result<Vec<PathBuf>> {
+    let source_root = canonical_source_root(source_root)?.to_slash_lossy();
+    let mut dirs = Vec::new();
+    let entries = match fs::read_dir(temp_root) {
+        Ok(entries) => entries,
+        // If the temp directory can't be read there's nothing we can clean there.
+        Err(_) => return Ok(dirs),
+    };
+    for entry in entries.flatten() {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(SCRATCH_PREFIX)
+        {
+            continue;
+        }
+        let marker = entry.path().join(SOURCE_MARKER_NAME);
+        if let Ok(marked_source) = fs::read_to_string(&marker) {
+            if marked_source == source_root {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn canonical_source_root(source_root: &Path) -> Result<PathBuf> {
+    source_root
+        .canonicalize()
+        .with_context(|| format!("canonicalize {}", source_root.to_slash_lossy()))
+}
+
+/// Return a path to the same file that isn't subject to the Windows 260-character limit on
+/// the length of paths, if possible.
+///
+/// Extended-length paths are passed to Windows without being normalized, so this only
+/// changes absolute paths with no `.` or `..` components. On other platforms, the path is
+/// returned unchanged.
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::ffi::OsString;
+        use std::path::{Component, Prefix};
+
+        let mut components = path.components();
+        let verbatim_prefix: OsString = match components.next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(_) => r"\\?\".into(),
+                Prefix::UNC(..) => r"\\?\UNC".into(),
+                _ => return path.to_owned(),
+            },
+            _ => return path.to_owned(),
+        };
+        if !components.all(|c| matches!(c, Component::RootDir | Component::Normal(_))) {
+            return path.to_owned();
+        }
+        let mut long_path = verbatim_prefix;
+        // A UNC path `\\server\share\...` becomes `\\?\UNC\server\share\...`.
+        let os_path = path.as_os_str().to_string_lossy();
+        long_path.push(os_path.strip_prefix('\\').unwrap_or(&os_path));
+        PathBuf::from(long_path)
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_owned()
+    }
+}
+
+/// Remove a directory and everything in it, retrying if that fails, perhaps because a file is
+/// briefly locked. It's not an error if the directory doesn't exist.
+fn remove_dir_all(path: &Path) -> io::Result<()> {
+    let long_path = extended_length_path(path);
+    retry_io(|| match fs::remove_dir_all(&long_path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        r => r,
+    })
+}
+
+/// Run a filesystem operation, retrying with increasing delays if it fails with any error
+/// other than a file not being found.
+fn retry_io<T, F>(mut op: F) -> io::Result<T>
+where
+    F: FnMut() -> io::Result<T>,
+{
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        match op() {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => match delays.next() {
+                Some(delay) => thread::sleep(*delay),
+                None => return Err(err),
+            },
+            r => return r,
+        }
+    }
+}
+
+/// Return the total size of all the files in a directory.
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clean_removes_output_and_leaked_scratch_dirs() {
+        let tree = tempfile::tempdir().unwrap();
+        let temp_root = tempfile::tempdir().unwrap();
+        fs::create_dir(tree.path().join(OUTDIR_NAME)).unwrap();
+        fs::write(tree.path().join(OUTDIR_NAME).join("outcomes.json"), "{}").unwrap();
+        fs::create_dir(tree.path().join(ROTATED_NAME)).unwrap();
+
+        let leaked = create_scratch_dir_in(tree.path(), temp_root.path())
+            .unwrap()
+            .into_path();
+        fs::write(leaked.join("big"), vec![0u8; 1000]).unwrap();
+        let other_tree = tempfile::tempdir().unwrap();
+        let other = create_scratch_dir_in(other_tree.path(), temp_root.path()).unwrap();
+        let unrelated = temp_root.path().join("unrelated");
+        fs::create_dir(&unrelated).unwrap();
+
+        let removed = clean_in(tree.path(), temp_root.path()).unwrap();
+        assert_eq!(
+            removed.iter().map(|r| r.path.clone()).collect::<Vec<_>>(),
+            [
+                tree.path().join(OUTDIR_NAME),
+                tree.path().join(ROTATED_NAME),
+                leaked.clone()
+            ]
+        );
+        assert_eq!(removed[0].bytes, 2);
+        assert!(removed[2].bytes > 1000);
+        assert!(!leaked.exists());
+        assert!(other.path().exists());
+        assert!(unrelated.exists());
+
+        // Cleaning again finds nothing to do.
+        assert_eq!(clean_in(tree.path(), temp_root.path()).unwrap(), []);
+    }
+
+    #[test]
+    fn scratch_dir_is_removed_when_dropped() {
+        let tree = tempfile::tempdir().unwrap();
+        let temp_root = tempfile::tempdir().unwrap();
+        let scratch = create_scratch_dir_in(tree.path(), temp_root.path()).unwrap();
+        let path = scratch.path().to_owned();
+        fs::create_dir_all(path.join("build/target/debug")).unwrap();
+        fs::write(path.join("build/target/debug/output"), "hello").unwrap();
+        drop(scratch);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn failed_operations_are_retried() {
+        let mut attempts = 0;
+        let result = retry_io(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(io::Error::other("locked"))
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts = 0;
+        let result: io::Result<()> = retry_io(|| {
+            attempts += 1;
+            Err(io::ErrorKind::NotFound.into())
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(attempts, 1);
+
+        let tmp = tempfile::tempdir().unwrap();
+        remove_dir_all(&tmp.path().join("nonexistent")).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn extended_length_paths() {
+        assert_eq!(
+            extended_length_path(Path::new(r"C:\Users\a\Temp\cargo-mutants-x")),
+            Path::new(r"\\?\C:\Users\a\Temp\cargo-mutants-x")
+        );
+        assert_eq!(
+            extended_length_path(Path::new(r"\\server\share\tmp")),
+            Path::new(r"\\?\UNC\server\share\tmp")
+        );
+        for unchanged in [r"\\?\C:\tmp", r"C:\tmp\..\x", r"tmp\x"] {
+            assert_eq!(
+                extended_length_path(Path::new(unchanged)),
+                Path::new(unchanged)
+            );
+        }
+    }
+}
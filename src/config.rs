@@ -0,0 +1,164 @@
+// Copyright 2022 Martin Pool
+
+//! `.cargo/mutants.toml` configuration file.
+//!
+//! The config file is read from the source tree, so that settings about what to mutate can be
+//! committed alongside the code, rather than repeated on every command line.
+//!
+//! Command-line options generally add to, or override, values from the config file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+use serde::Deserialize;
+
+/// The path of the config file, relative to the source tree root.
+const CONFIG_PATH: &str = ".cargo/mutants.toml";
+
+/// Configuration read from a `.cargo/mutants.toml` file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Also mutate code in `examples/`.
+    pub include_examples: bool,
+    /// Also mutate code in `benches/`.
+    pub include_benches: bool,
+    /// Also mutate non-test helper code in integration tests under `tests/`.
+    pub include_test_helpers: bool,
+    /// Also mutate `unsafe fn`s.
+    pub mutate_unsafe: bool,
+    /// Don't mutate trivial accessors, whose body only reads or assigns a field.
+    pub skip_trivial: bool,
+    /// Don't mutate functions containing `unsafe` blocks.
+    pub skip_unsafe_blocks: bool,
+    /// Mutate functions returning references to return a leaked default value.
+    pub leak_references: bool,
+    /// Copy the targets of symlinks into the scratch directory, rather than the symlinks.
+    pub follow_symlinks: bool,
+    /// Copy version control directories into the scratch directory.
+    pub copy_vcs: bool,
+    /// Gitignore-style patterns for source files that should never be mutated.
+    pub exclude_globs: Vec<String>,
+    /// Cfg predicates that are false when the tests run, so that code only compiled when
+    /// they're true is never mutated.
+    pub exclude_cfgs: Vec<String>,
+    /// Sets of cargo arguments selecting features to test each mutant under.
+    pub feature_sets: Vec<Vec<String>>,
+    /// Names of mutation operators that should not generate mutants.
+    pub skip_operators: Vec<String>,
+    /// Paths of plugin programs, relative to the tree root, that suggest more mutants.
+    pub plugins: Vec<String>,
+    /// Expressions to return from functions returning `impl Trait`, keyed by the path of the
+    /// trait.
+    pub impl_trait_replacements: BTreeMap<String, Vec<String>>,
+    /// Expressions to return from functions returning types that have no `Default`, keyed by
+    /// the path of the type.
+    pub constructors: BTreeMap<String, Vec<String>>,
+    /// Names of type aliases for `Result`, such as `Fallible`, whose functions are mutated like
+    /// those returning a `Result`.
+    pub result_aliases: Vec<String>,
+    /// A command and its arguments, run in the tree root after each mutant is tested, whose
+    /// exit code can reclassify the outcome.
+    pub verdict_command: Option<Vec<String>>,
+    /// Test at most this many mutants of each function.
+    pub max_mutants_per_function: Option<usize>,
+    /// A command and its arguments that formats diffs shown on a terminal, reading the diff on
+    /// stdin.
+    pub diff_command: Option<Vec<String>>,
+    /// Draw progress bars using only ASCII characters.
+    pub ascii: bool,
+    /// The frames of the spinner, followed by the character shown when it's finished.
+    pub spinner_chars: Option<String>,
+    /// The characters for the filled, partly-filled, and empty parts of progress bars.
+    pub progress_chars: Option<String>,
+}
+
+impl Config {
+    /// Read the config file from a source tree, or return the default config if there is none.
+    pub fn read_tree_config(tree_root: &Path) -> Result<Config> {
+        let path = tree_root.join(CONFIG_PATH);
+        if path.is_file() {
+            Config::read_file(&path)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    /// Read a config file from a given path.
+    pub fn read_file(path: &Path) -> Result<Config> {
+        let toml = fs::read_to_string(path)
+            .with_context(|| format!("read config {}", path.to_slash_lossy()))?;
+        toml::from_str(&toml).with_context(|| format!("parse config {}", path.to_slash_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn missing_config_is_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            Config::read_tree_config(tmp.path()).unwrap(),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn read_config_from_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join(".cargo")).unwrap();
+        fs::write(
+            tmp.path().join(".cargo/mutants.toml"),
+            "include_examples = true\n",
+        )
+        .unwrap();
+        let config = Config::read_tree_config(tmp.path()).unwrap();
+        assert!(config.include_examples);
+        assert!(!config.include_benches);
+    }
+
+    #[test]
+    fn impl_trait_replacements_are_keyed_by_trait_path() {
+        let config: Config = toml::from_str(
+            r#"
+            [impl_trait_replacements]
+            "fmt::Display" = ["\"\"", "\"xyzzy\""]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.impl_trait_replacements["fmt::Display"],
+            ["\"\"", "\"xyzzy\""]
+        );
+    }
+
+    #[test]
+    fn constructors_are_keyed_by_type_path() {
+        let config: Config = toml::from_str(
+            r#"
+            [constructors]
+            "chrono::NaiveDate" = ["chrono::NaiveDate::MIN"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.constructors["chrono::NaiveDate"],
+            ["chrono::NaiveDate::MIN"]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("mutants.toml");
+        fs::write(&path, "wibble = 1\n").unwrap();
+        assert!(Config::read_file(&path).is_err());
+    }
+}
@@ -2,20 +2,97 @@
 
 //! Print messages and progress bars on the terminal.
 
-use std::time::Instant;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use console::{style, StyledObject};
+use anyhow::{anyhow, Context, Result};
+
+use console::{style, StyledObject, Term};
 use indicatif::{ProgressBar, ProgressStyle};
+use path_slash::PathExt;
 
-use crate::lab::{Outcome, Status};
-use crate::mutate::Mutation;
+use crate::clean::Removed;
+use crate::ignore_list::IGNORE_LIST_NAME;
+use crate::lab::{LabOutcome, Outcome, Status, LOG_MARKER};
+use crate::mutate::{one_line, Mutation};
+use crate::observer::{Observer, Phase, Scenario};
+use crate::output::LogFile;
+use crate::source::{DiscoveredFiles, Skipped};
 
 /// Top-level UI object that manages the state of an interactive console: mostly progress bars and
 /// messages.
+///
+/// The console observes the lab, and shows a progress bar for the current scenario, followed by
+/// its outcome.
+///
+/// Mutants that were caught, which need no action, aren't listed unless the console is verbose;
+/// they're counted in a summary at the end of the run.
+///
+/// On an interactive terminal, the progress bar also shows how many mutants have been tested,
+/// caught, and missed, and an estimate of the time remaining; only the mutants that need
+/// attention, such as missed mutants and timeouts, are listed. The status line also shows the
+/// latest line of output from cargo, so that long builds visibly make progress. Otherwise, each
+/// listed outcome is printed on its own line, with a line of running totals every so often.
+///
+/// On a terminal, the location of each mutant in its result line links to the source file, and
+/// the outcome links to the log, using OSC 8 hyperlinks. Terminals that don't support them just
+/// show the text.
+///
+/// Results can also be grouped by source file, with a subtotal for each file, and printed once
+/// the run is finished, rather than listed as they happen.
+///
+/// In quiet mode, only problems are printed: mutants that were missed, timed out, or failed to
+/// build, and then a summary of the whole run.
 pub struct Console {
     show_all_logs: bool,
     show_times: bool,
+
+    /// True to print only problems and a summary.
+    quiet: bool,
+
+    /// True to list every mutant, including those that were caught.
+    verbose: bool,
+
+    /// The results for each source file, by its path, if results are grouped by file.
+    groups: Option<BTreeMap<String, FileGroup>>,
+
+    /// How many lines from the end of the log of each missed mutant to print.
+    missed_log_lines: usize,
+
+    /// True to show overall progress, rather than listing every outcome.
+    interactive: bool,
+
+    /// True to link result lines to the source and the log.
+    hyperlinks: bool,
+
+    /// The root of the source tree, to link to source files, if known.
+    source_root: Option<PathBuf>,
+
+    /// Characters used to draw progress bars.
+    theme: Theme,
+
+    /// Ids of the mutants that no tests could catch.
+    uncatchable: HashSet<String>,
+
+    /// Counts of the mutants tested so far, once they've been discovered.
+    progress: Option<MutantProgress>,
+
+    /// When running totals were last printed, if not interactive.
+    last_tally: Option<Instant>,
+
+    /// How long the baseline test took, once it's finished.
+    baseline_duration: Option<Duration>,
+
+    /// The scenario currently running, if any.
+    activity: Option<Activity>,
+
+    /// Copying the tree, if that's in progress.
+    copy_activity: Option<CopyActivity>,
 }
 
 impl Console {
@@ -24,6 +101,20 @@ impl Console {
         Console {
             show_all_logs: false,
             show_times: true,
+            quiet: false,
+            verbose: false,
+            groups: None,
+            missed_log_lines: 0,
+            interactive: Term::stdout().is_term(),
+            hyperlinks: Term::stdout().is_term(),
+            source_root: None,
+            theme: Theme::default(),
+            uncatchable: HashSet::new(),
+            progress: None,
+            last_tally: None,
+            baseline_duration: None,
+            activity: None,
+            copy_activity: None,
         }
     }
 
@@ -38,88 +129,548 @@ impl Console {
         Console { show_times, ..self }
     }
 
-    /// Create an Activity for a new mutation.
-    pub fn start_mutation(&self, mutation: &Mutation) -> Activity {
-        self.start_activity(&style_mutation(mutation))
+    /// Print only mutants that were missed, timed out, or failed to build, and a summary at the
+    /// end, rather than every outcome.
+    pub fn quiet(self, quiet: bool) -> Console {
+        Console { quiet, ..self }
     }
 
-    /// Start a general-purpose activity.
-    pub fn start_activity(&self, task: &str) -> Activity {
-        let progress_bar = ProgressBar::new(0)
-            .with_message(task.to_owned())
-            .with_style(
-                ProgressStyle::default_spinner()
-                    .template("{msg} ... {elapsed:.cyan} {spinner:.cyan}"),
-            );
-        progress_bar.set_draw_rate(5); // updates per second
-        Activity {
-            task: task.to_owned(),
-            progress_bar,
-            start_time: Instant::now(),
-            console: self,
+    /// Link the location and outcome of each mutant to the source file and log. By default,
+    /// this is on if stdout is a terminal.
+    pub fn hyperlinks(self, hyperlinks: bool) -> Console {
+        Console { hyperlinks, ..self }
+    }
+
+    /// Draw progress bars with the characters from this theme.
+    pub fn theme(self, theme: Theme) -> Console {
+        Console { theme, ..self }
+    }
+
+    /// Set the root of the source tree, so that result lines can link to source files.
+    pub fn source_root(self, source_root: &Path) -> Console {
+        Console {
+            source_root: Some(source_root.to_owned()),
+            ..self
+        }
+    }
+
+    /// List every mutant, including those that were caught, rather than just those that need
+    /// attention.
+    pub fn verbose(self, verbose: bool) -> Console {
+        Console { verbose, ..self }
+    }
+
+    /// Group the results by source file, with a subtotal for each file, and print them when the
+    /// run is finished.
+    pub fn group_by_file(self, group_by_file: bool) -> Console {
+        Console {
+            groups: if group_by_file {
+                Some(BTreeMap::new())
+            } else {
+                None
+            },
+            ..self
+        }
+    }
+
+    /// Print the last few lines of the log of each missed mutant, so that it's easy to see that
+    /// the tests really passed.
+    pub fn missed_log_lines(self, missed_log_lines: usize) -> Console {
+        Console {
+            missed_log_lines,
+            ..self
+        }
+    }
+
+    /// Show overall progress, rather than listing every outcome. By default, this is on if
+    /// stdout is a terminal.
+    pub fn interactive(self, interactive: bool) -> Console {
+        Console {
+            interactive,
+            ..self
+        }
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console::new()
+    }
+}
+
+impl Observer for Console {
+    fn copy_started(&mut self) {
+        self.copy_activity = Some(CopyActivity::new(
+            "copy source and build products to scratch directory",
+        ));
+    }
+
+    fn copy_progress(&mut self, bytes_copied: u64) {
+        if let Some(copy_activity) = &mut self.copy_activity {
+            copy_activity.bytes_copied(bytes_copied);
+        }
+    }
+
+    fn copy_finished(&mut self, bytes_copied: Option<u64>) {
+        if let Some(copy_activity) = self.copy_activity.take() {
+            match bytes_copied {
+                Some(_) if self.quiet => copy_activity.clear(),
+                Some(bytes_copied) => copy_activity.succeed(bytes_copied, self.show_times),
+                None => copy_activity.fail(),
+            }
+        }
+    }
+
+    fn mutants_discovered(&mut self, mutations: &[Mutation]) {
+        if let Some(line) = self.estimate_line(mutations.len()) {
+            println!("{}", line);
+        }
+        self.progress = Some(MutantProgress {
+            total: mutations.len(),
+            ..Default::default()
+        });
+        self.last_tally = Some(Instant::now());
+    }
+
+    fn mutants_ignored(&mut self, mutations: &[Mutation]) {
+        if self.quiet {
+            return;
+        }
+        println!(
+            "{} ignored by {}",
+            plural(mutations.len(), "mutant"),
+            IGNORE_LIST_NAME
+        );
+    }
+
+    fn mutants_uncatchable(&mut self, mutations: &[Mutation]) {
+        self.uncatchable
+            .extend(mutations.iter().map(|mutation| mutation.id().to_owned()));
+    }
+
+    fn warning(&mut self, message: &str) {
+        print_warning(message);
+    }
+
+    fn scenario_started(&mut self, scenario: &Scenario, log_file: &LogFile) {
+        let task = describe_scenario(scenario, None);
+        let progress = match scenario {
+            Scenario::Mutant(_) if self.interactive => self.progress.as_ref(),
+            _ => None,
+        };
+        let log_file = if self.interactive {
+            Some(log_file)
+        } else {
+            None
+        };
+        self.activity = Some(Activity::new(&task, progress, log_file, &self.theme));
+    }
+
+    fn phase_started(&mut self, _scenario: &Scenario, phase: Phase) {
+        if let Some(activity) = &mut self.activity {
+            activity.set_phase(phase.name());
+        }
+    }
+
+    fn scenario_finished(&mut self, scenario: &Scenario, outcome: &Outcome) {
+        let mut quiet = false;
+        if let (Scenario::Mutant(_), Some(progress)) = (scenario, &mut self.progress) {
+            progress.record(outcome);
+        }
+        if matches!(scenario, Scenario::Mutant(_)) && !self.verbose && !self.show_all_logs {
+            quiet = if self.interactive {
+                // Outcomes that need attention are still listed above the progress bar.
+                !matches!(
+                    outcome.status,
+                    Status::MutantMissed | Status::Timeout | Status::BadPatch
+                )
+            } else {
+                matches!(
+                    outcome.status,
+                    Status::MutantCaught | Status::CheckPassed | Status::Ignored
+                )
+            };
+        }
+        if self.quiet {
+            quiet = match scenario {
+                Scenario::Mutant(_) => !matches!(
+                    outcome.status,
+                    Status::MutantMissed
+                        | Status::Timeout
+                        | Status::CheckFailed
+                        | Status::BuildFailed
+                        | Status::BadPatch
+                ),
+                _ => outcome.status.passed(),
+            };
+        }
+        let mut status = self.link_to_log(&style_status(outcome.status).to_string(), outcome);
+        if let Scenario::Mutant(mutation) = scenario {
+            if outcome.status == Status::MutantMissed && self.uncatchable.contains(mutation.id()) {
+                status.push_str(&style(" (no tests can catch it)").dim().to_string());
+            }
+        }
+        let mut group = None;
+        if let (Some(groups), Scenario::Mutant(mutation)) = (&mut self.groups, scenario) {
+            let file_group = groups
+                .entry(mutation.source_file.tree_relative_slashes())
+                .or_default();
+            file_group.progress.record(outcome);
+            // Mutants are listed in the group unless they'd otherwise not be printed.
+            if !quiet {
+                let location = format!("line {}", mutation.span().start.line);
+                let mut line = format!(
+                    "    {} ... {}",
+                    style_mutation_at(&location, mutation),
+                    status
+                );
+                if self.show_times {
+                    line.push_str(&format!(" in {:.3}s", outcome.duration.as_secs_f64()));
+                }
+                file_group.lines.push(line);
+                group = Some(file_group);
+            }
+            quiet = true;
+        }
+        if let Some(activity) = self.activity.take() {
+            if quiet {
+                activity.clear();
+            } else {
+                let source_root = if self.hyperlinks {
+                    self.source_root.as_deref()
+                } else {
+                    None
+                };
+                activity.finish(
+                    &describe_scenario(scenario, source_root),
+                    &status,
+                    self.show_times,
+                );
+            }
+        }
+        if outcome.status.should_show_logs() || self.show_all_logs {
+            match outcome.log_file.log_content() {
+                Ok(log_content) => print!("{}", log_content),
+                Err(err) => print_error(&format!("{:#}", err)),
+            }
+        } else if outcome.status == Status::MutantMissed && self.missed_log_lines > 0 {
+            match outcome.log_file.log_content() {
+                Ok(log_content) => {
+                    for line in log_excerpt(&log_content, self.missed_log_lines) {
+                        let line = format!("    {}", style(line).dim());
+                        match &mut group {
+                            Some(group) => group.lines.push(format!("    {}", line)),
+                            None => println!("{}", line),
+                        }
+                    }
+                }
+                Err(err) => print_error(&format!("{:#}", err)),
+            }
+        }
+        // A failed baseline says nothing about how long the mutants will take.
+        if matches!(scenario, Scenario::Baseline) && outcome.status == Status::CleanTestPassed {
+            self.baseline_duration = Some(outcome.duration);
+        }
+        // Without a progress bar, print the running totals now and then so that long runs in
+        // CI show how they're trending. They depend on timing, so aren't shown with --no-times.
+        if let (false, false, true, Some(progress), Some(last_tally)) = (
+            self.interactive,
+            self.quiet,
+            self.show_times,
+            &self.progress,
+            self.last_tally,
+        ) {
+            if last_tally.elapsed() >= TALLY_INTERVAL && progress.done < progress.total {
+                println!("{}", style(progress.describe(Duration::ZERO)).dim());
+                self.last_tally = Some(Instant::now());
+            }
+        }
+        if matches!(scenario, Scenario::Baseline) && !outcome.status.passed() {
+            print_error("tests failed in a clean copy of the tree, so no mutants were tested");
         }
     }
 
-    /// Start an Activity for copying a tree.
-    pub fn start_copy_activity(&self, name: &str) -> CopyActivity {
-        CopyActivity::new(name, self)
+    fn run_finished(&mut self, _lab_outcome: &LabOutcome) {
+        if let Some(groups) = &self.groups {
+            for (path, group) in groups {
+                println!("{} ({})", style(path).bold(), group.progress.summary());
+                for line in &group.lines {
+                    println!("{}", line);
+                }
+            }
+        }
+        if let Some(progress) = &self.progress {
+            println!("{}", progress.summary());
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(activity) = &mut self.activity {
+            if let Some(progress) = &self.progress {
+                activity.update_progress(progress);
+            }
+            activity.show_latest_output();
+            activity.tick();
+        }
     }
 }
 
-pub struct Activity<'c> {
-    pub start_time: Instant,
+impl Console {
+    /// If hyperlinks are on, link some text to the log of an outcome.
+    /// Describe how many mutants will be tested and about how long it will take, or None if
+    /// times aren't shown, or there's no successful baseline to estimate from.
+    fn estimate_line(&self, n_mutants: usize) -> Option<String> {
+        match (self.show_times, self.quiet, self.baseline_duration) {
+            (true, false, Some(baseline_duration)) if n_mutants > 0 => Some(format!(
+                "{} to test, estimated time {}",
+                plural(n_mutants, "mutant"),
+                style(format_duration(estimate_total_time(
+                    baseline_duration,
+                    n_mutants
+                )))
+                .cyan()
+            )),
+            _ => None,
+        }
+    }
+
+    fn link_to_log(&self, text: &str, outcome: &Outcome) -> String {
+        if self.hyperlinks {
+            hyperlink(&file_url(&outcome.log_file.path), text)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+/// The results for one source file, when results are grouped by file.
+#[derive(Default)]
+struct FileGroup {
+    /// Counts of the outcomes of mutants in this file.
+    progress: MutantProgress,
+
+    /// A line describing each mutant's outcome, to print under the file.
+    lines: Vec<String>,
+}
+
+/// How often to print running totals, when there's no progress bar.
+const TALLY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Counts of the mutants tested so far, to show overall progress.
+#[derive(Debug, Default)]
+struct MutantProgress {
+    total: usize,
+    done: usize,
+    caught: usize,
+    missed: usize,
+    unviable: usize,
+    timeouts: usize,
+    /// Mutants that built, when only checking that they build.
+    passed_check: usize,
+    ignored: usize,
+    /// Mutants whose code didn't parse, because of a bug in cargo-mutants or a plugin.
+    bad_patches: usize,
+    /// The total time taken by the mutants that are done.
+    elapsed: Duration,
+}
+
+impl MutantProgress {
+    fn record(&mut self, outcome: &Outcome) {
+        self.done += 1;
+        self.elapsed += outcome.duration;
+        match outcome.status {
+            Status::MutantCaught => self.caught += 1,
+            Status::MutantMissed => self.missed += 1,
+            Status::CheckFailed | Status::BuildFailed => self.unviable += 1,
+            Status::Timeout => self.timeouts += 1,
+            Status::CheckPassed => self.passed_check += 1,
+            Status::Ignored => self.ignored += 1,
+            Status::BadPatch => self.bad_patches += 1,
+            _ => (),
+        }
+    }
+
+    /// Estimate how long the remaining mutants will take, from the average time of those done
+    /// so far, less how long the current mutant has already been running.
+    fn remaining_time(&self, current: Duration) -> Option<Duration> {
+        if self.done == 0 {
+            return None;
+        }
+        let mean = self.elapsed / self.done as u32;
+        Some((mean * (self.total - self.done) as u32).saturating_sub(current))
+    }
+
+    /// Summarize the outcomes of the mutants done, such as "3 mutants tested: 2 caught, 1
+    /// missed".
+    fn summary(&self) -> String {
+        let parts = [
+            (self.caught, "caught"),
+            (self.passed_check, "passed check"),
+            (self.missed, "missed"),
+            (self.timeouts, "timed out"),
+            (self.unviable, "unviable"),
+            (self.ignored, "ignored"),
+            (self.bad_patches, "unparseable"),
+        ];
+        let details = parts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, name)| format!("{} {}", count, name))
+            .collect::<Vec<_>>();
+        if details.is_empty() {
+            format!("{} tested", plural(self.done, "mutant"))
+        } else {
+            format!(
+                "{} tested: {}",
+                plural(self.done, "mutant"),
+                details.join(", ")
+            )
+        }
+    }
+
+    /// Describe the progress, for the second line of the progress bar or the running totals.
+    ///
+    /// Unviable, timed out, and unparseable mutants are only mentioned once there are some.
+    fn describe(&self, current: Duration) -> String {
+        let percent = (self.done * 100).checked_div(self.total).unwrap_or(100);
+        let mut s = format!(
+            "{}/{} mutants tested ({}%), {} caught, {} missed",
+            self.done, self.total, percent, self.caught, self.missed
+        );
+        if self.unviable > 0 {
+            s.push_str(&format!(", {} unviable", self.unviable));
+        }
+        if self.timeouts > 0 {
+            s.push_str(&format!(", {} timed out", self.timeouts));
+        }
+        if self.bad_patches > 0 {
+            s.push_str(&format!(", {} unparseable", self.bad_patches));
+        }
+        if let Some(remaining) = self.remaining_time(current) {
+            s.push_str(&format!(", about {} left", format_duration(remaining)));
+        }
+        s
+    }
+}
+
+/// A progress bar for a scenario that's running.
+struct Activity {
+    start_time: Instant,
     progress_bar: ProgressBar,
     task: String,
-    console: &'c Console,
+
+    /// The phase that's running, if it's started.
+    phase: Option<&'static str>,
+
+    /// The log of the scenario, if the latest line of output should be shown.
+    log_file: Option<LogFile>,
 }
 
-impl<'c> Activity<'c> {
-    pub fn set_phase(&mut self, phase: &'static str) {
+impl Activity {
+    /// Start showing progress for a task, and, if given, the overall progress of testing
+    /// mutants on a second line.
+    ///
+    /// If a log file is given, the status line follows the latest line of output in it.
+    fn new(
+        task: &str,
+        progress: Option<&MutantProgress>,
+        log_file: Option<&LogFile>,
+        theme: &Theme,
+    ) -> Activity {
+        let progress_bar = match progress {
+            Some(progress) => ProgressBar::new(progress.total as u64)
+                .with_position(progress.done as u64)
+                .with_prefix(progress.describe(Duration::ZERO))
+                .with_style(theme.style(ProgressStyle::default_bar().template(
+                    "{msg} ... {elapsed:.cyan} {spinner:.cyan}\n{bar:30.cyan/blue} {prefix}",
+                ))),
+            None => ProgressBar::new(0).with_style(
+                theme.style(
+                    ProgressStyle::default_spinner()
+                        .template("{msg} ... {elapsed:.cyan} {spinner:.cyan}"),
+                ),
+            ),
+        }
+        .with_message(task.to_owned());
+        progress_bar.set_draw_rate(5); // updates per second
+        Activity {
+            task: task.to_owned(),
+            progress_bar,
+            start_time: Instant::now(),
+            phase: None,
+            log_file: log_file.cloned(),
+        }
+    }
+
+    fn update_progress(&mut self, progress: &MutantProgress) {
         self.progress_bar
-            .set_message(format!("{} ({})", self.task, phase));
+            .set_prefix(progress.describe(self.start_time.elapsed()));
     }
 
-    /// Finish the progress bar, and print a concluding message to stdout.
-    fn finish(self, styled_status: StyledObject<&str>) {
+    /// Remove the progress bar, without printing anything.
+    fn clear(self) {
         self.progress_bar.finish_and_clear();
-        print!("{} ... {}", self.task, styled_status,);
-        if self.console.show_times {
-            println!(" in {}", self.format_elapsed());
-        } else {
-            println!();
-        }
     }
 
-    pub fn tick(&mut self) {
-        self.progress_bar.tick();
+    fn set_phase(&mut self, phase: &'static str) {
+        self.phase = Some(phase);
+        self.progress_bar
+            .set_message(format!("{} ({})", self.task, phase));
+    }
+
+    /// Show the latest line of output from cargo after the task and phase, shortened to fit on
+    /// the terminal, like cargo's own progress line.
+    fn show_latest_output(&mut self) {
+        let (log_file, phase) = match (&self.log_file, self.phase) {
+            (Some(log_file), Some(phase)) => (log_file, phase),
+            _ => return,
+        };
+        // If the log can't be read, just keep showing the phase; any real problem will be
+        // reported when the scenario finishes.
+        let tail = log_file.tail(LOG_TAIL_BYTES).unwrap_or_default();
+        let mut message = format!("{} ({})", self.task, phase);
+        if let Some(line) = log_excerpt(&tail, 1).first() {
+            let (_rows, columns) = Term::stdout().size();
+            // Leave space for the task, the elapsed time, and the spinner.
+            let width = (columns as usize)
+                .saturating_sub(console::measure_text_width(&message) + 20)
+                .max(10);
+            message.push_str(&format!(
+                ": {}",
+                style(console::truncate_str(line.trim(), width, "...")).dim()
+            ));
+        }
+        self.progress_bar.set_message(message);
     }
 
-    /// Report the outcome of a scenario.
+    /// Finish the progress bar, and print a concluding message to stdout, describing the task
+    /// and its status.
     ///
-    /// Prints the log content if appropriate.
-    pub fn outcome(self, outcome: &Outcome) -> Result<()> {
-        let show_all_logs = self.console.show_all_logs; // survive consumption by finish
-        self.finish(style_status(outcome.status));
-        if outcome.status.should_show_logs() || show_all_logs {
-            print!("{}", outcome.log_file.log_content()?);
+    /// The task is passed in again, because it may include hyperlinks that aren't shown in the
+    /// progress bar.
+    fn finish(self, task: &str, styled_status: &str, show_times: bool) {
+        self.progress_bar.finish_and_clear();
+        print!("{} ... {}", task, styled_status);
+        if show_times {
+            println!(" in {}", format_elapsed(self.start_time));
+        } else {
+            println!();
         }
-        Ok(())
     }
 
-    fn format_elapsed(&self) -> String {
-        format_elapsed(self.start_time)
+    fn tick(&mut self) {
+        self.progress_bar.tick();
     }
 }
 
-pub struct CopyActivity<'c> {
+struct CopyActivity {
     name: String,
     progress_bar: ProgressBar,
     start_time: Instant,
-    console: &'c Console,
 }
 
-impl<'c> CopyActivity<'c> {
-    fn new(name: &str, console: &'c Console) -> CopyActivity<'c> {
+impl CopyActivity {
+    fn new(name: &str) -> CopyActivity {
         let progress_bar = ProgressBar::new(0)
             .with_message(name.to_owned())
             .with_style(ProgressStyle::default_spinner().template("{msg}"));
@@ -128,11 +679,10 @@ impl<'c> CopyActivity<'c> {
             name: name.to_owned(),
             progress_bar,
             start_time: Instant::now(),
-            console,
         }
     }
 
-    pub fn bytes_copied(&mut self, bytes_copied: u64) {
+    fn bytes_copied(&mut self, bytes_copied: u64) {
         let styled = format!(
             "{} ... {} in {}",
             self.name,
@@ -142,11 +692,11 @@ impl<'c> CopyActivity<'c> {
         self.progress_bar.set_message(styled);
     }
 
-    pub fn succeed(self, bytes_copied: u64) {
+    fn succeed(self, bytes_copied: u64, show_times: bool) {
         self.progress_bar.finish_and_clear();
         // Print to stdout even if progress bars weren't drawn.
         print!("{} ...", self.name);
-        if self.console.show_times {
+        if show_times {
             println!(
                 " {} in {}",
                 style_mb(bytes_copied),
@@ -157,10 +707,73 @@ impl<'c> CopyActivity<'c> {
         }
     }
 
-    pub fn fail(self) {
+    fn fail(self) {
         self.progress_bar.finish_and_clear();
         println!("{} ... {}", self.name, style("failed").bold().red(),);
     }
+
+    /// Remove the progress bar, without printing anything.
+    fn clear(self) {
+        self.progress_bar.finish_and_clear();
+    }
+}
+
+/// The characters used to draw spinners and progress bars.
+///
+/// The default theme uses Unicode block and Braille characters; [Theme::ascii] suits terminals
+/// and CI systems that can't show them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// The frames of the spinner animation, followed by the character shown once it's finished.
+    spinner_chars: String,
+
+    /// The characters for the filled part of a progress bar, optionally any partly-filled steps,
+    /// and the empty part.
+    progress_chars: String,
+}
+
+impl Theme {
+    /// Make a theme from custom characters, which must each have at least two characters.
+    pub fn new(spinner_chars: &str, progress_chars: &str) -> Result<Theme> {
+        for (name, chars) in [
+            ("spinner_chars", spinner_chars),
+            ("progress_chars", progress_chars),
+        ] {
+            if chars.chars().count() < 2 {
+                return Err(anyhow!("{} needs at least two characters", name));
+            }
+        }
+        Ok(Theme {
+            spinner_chars: spinner_chars.to_owned(),
+            progress_chars: progress_chars.to_owned(),
+        })
+    }
+
+    /// A theme using only ASCII characters.
+    pub fn ascii() -> Theme {
+        Theme::new("-\\|/ ", "=> ").unwrap()
+    }
+
+    pub fn spinner_chars(&self) -> &str {
+        &self.spinner_chars
+    }
+
+    pub fn progress_chars(&self) -> &str {
+        &self.progress_chars
+    }
+
+    fn style(&self, style: ProgressStyle) -> ProgressStyle {
+        style
+            .tick_chars(&self.spinner_chars)
+            .progress_chars(&self.progress_chars)
+    }
+}
+
+impl Default for Theme {
+    /// The same characters that indicatif uses by default.
+    fn default() -> Self {
+        Theme::new("⠁⠁⠉⠙⠚⠒⠂⠂⠒⠲⠴⠤⠄⠄⠤⠠⠠⠤⠦⠖⠒⠐⠐⠒⠓⠋⠉⠈⠈ ", "█░").unwrap()
+    }
 }
 
 /// Return a styled string reflecting the moral value of this outcome.
@@ -174,26 +787,175 @@ pub fn style_status(status: Status) -> StyledObject<&'static str> {
         // neutral/inconclusive
         CheckFailed => style("check failed").yellow(),
         BuildFailed => style("build failed").yellow(),
+        Ignored => style("ignored").yellow(),
         // bad statuses
         MutantMissed => style("NOT CAUGHT").red().bold(),
         Timeout => style("TIMEOUT").red().bold(),
         SourceBuildFailed | CleanTestFailed => style("FAILED").red().bold(),
+        BadPatch => style("BAD PATCH").red().bold(),
     }
 }
 
-pub fn list_mutations(mutations: &[Mutation], show_diffs: bool) {
+/// Print a list of mutations, and optionally their diffs.
+///
+/// If a diff command is given and stdout is a terminal, each diff is piped through the command,
+/// such as `delta` or `bat`, to format it.
+pub fn list_mutations(
+    mutations: &[Mutation],
+    show_diffs: bool,
+    diff_command: Option<&[String]>,
+) -> Result<()> {
+    let diff_command = diff_command.filter(|_| Term::stdout().is_term());
     for mutation in mutations {
         println!("{}", style_mutation(mutation));
         if show_diffs {
-            println!("{}", mutation.diff());
+            match diff_command {
+                Some(argv) => print!("{}", format_diff(&mutation.diff(), argv)?),
+                None => println!("{}", mutation.diff()),
+            }
         }
     }
+    Ok(())
+}
+
+/// Format a diff by piping it through a command, and return what the command prints.
+fn format_diff(diff: &str, argv: &[String]) -> Result<String> {
+    let name = argv.join(" ");
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("run diff command {}", name))?;
+    match child
+        .stdin
+        .take()
+        .expect("child has stdin")
+        .write_all(diff.as_bytes())
+    {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => (),
+        r => r.with_context(|| format!("write diff to {}", name))?,
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("wait for diff command {}", name))?;
+    if !output.status.success() {
+        return Err(anyhow!("diff command {} failed: {}", name, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn list_files(discovered: &DiscoveredFiles) {
+    for source_file in &discovered.files {
+        println!("{}", source_file.tree_relative_slashes());
+    }
+    for excluded in &discovered.excluded {
+        println!(
+            "{} {}",
+            excluded.name,
+            style(format!("(excluded: {})", excluded.reason)).yellow()
+        );
+    }
+}
+
+/// Print the functions, items, and files that aren't mutated, and why.
+pub fn list_skipped(skipped: &[Skipped]) {
+    for skipped in skipped {
+        let location = match (&skipped.name, skipped.line) {
+            (Some(name), Some(line)) => format!("{}:{}: {}", skipped.file, line, name),
+            (Some(name), None) => format!("{}: {}", skipped.file, name),
+            (None, _) => skipped.file.clone(),
+        };
+        let reason = match &skipped.detail {
+            Some(detail) => format!("{}: {}", skipped.reason.description(), detail),
+            None => skipped.reason.description().to_owned(),
+        };
+        println!(
+            "{} {}",
+            location,
+            style(format!("(skipped: {})", reason)).yellow()
+        );
+    }
+}
+
+/// Print the directories removed by `cargo mutants clean`, and the total space reclaimed.
+pub fn list_removed(removed: &[Removed], show_sizes: bool) {
+    if removed.is_empty() {
+        println!("Nothing to clean");
+        return;
+    }
+    for r in removed {
+        if show_sizes {
+            println!(
+                "Removed {} ({})",
+                r.path.to_slash_lossy(),
+                style_mb(r.bytes)
+            );
+        } else {
+            println!("Removed {}", r.path.to_slash_lossy());
+        }
+    }
+    if show_sizes {
+        let total: u64 = removed.iter().map(|r| r.bytes).sum();
+        println!("Reclaimed {}", style_mb(total));
+    }
+}
+
+/// Describe a scenario, for its progress bar and result line.
+///
+/// If the root of the source tree is given, the location of a mutant links to its source file.
+fn describe_scenario(scenario: &Scenario, source_root: Option<&Path>) -> String {
+    match scenario {
+        Scenario::SourceTree { check_only: true } => "check source tree".to_owned(),
+        Scenario::SourceTree { check_only: false } => "build source tree".to_owned(),
+        Scenario::Baseline => "baseline test with no mutations".to_owned(),
+        Scenario::Mutant(mutation) => match source_root {
+            Some(source_root) => style_mutation_at(
+                &hyperlink(
+                    &file_url(&source_root.join(mutation.source_file.tree_relative_slashes())),
+                    &mutation.describe_location(),
+                ),
+                mutation,
+            ),
+            None => style_mutation(mutation),
+        },
+    }
 }
 
 fn style_mutation(mutation: &Mutation) -> String {
+    style_mutation_at(&mutation.describe_location(), mutation)
+}
+
+/// Describe a mutation, starting with its location, which may be styled or linked.
+fn style_mutation_at(location: &str, mutation: &Mutation) -> String {
+    if let Some(pattern) = mutation.match_arm_pattern() {
+        return format!(
+            "{}: delete match arm {} in {}",
+            location,
+            style(pattern).magenta(),
+            style(mutation.function_name()).bright().magenta(),
+        );
+    }
+    if let Some(statement) = mutation.deleted_statement() {
+        return format!(
+            "{}: delete {} in {}",
+            location,
+            style(statement).magenta(),
+            style(mutation.function_name()).bright().magenta(),
+        );
+    }
+    if let Some(original) = mutation.original_text() {
+        return format!(
+            "{}: replace {} with {} in {}",
+            location,
+            style(one_line(original)).magenta(),
+            style(one_line(mutation.replacement_text())).yellow(),
+            style(mutation.function_name()).bright().magenta(),
+        );
+    }
     format!(
         "{}: replace {}{}{} with {}",
-        mutation.describe_location(),
+        location,
         style(mutation.function_name()).bright().magenta(),
         if mutation.return_type().is_empty() {
             ""
@@ -205,14 +967,88 @@ fn style_mutation(mutation: &Mutation) -> String {
     )
 }
 
+/// Make text into an OSC 8 hyperlink to a URL.
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Return a `file://` URL for a path, which is made absolute if possible.
+fn file_url(path: &Path) -> String {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    let slashes = path.to_slash_lossy();
+    // Windows canonical paths start with `\\?\`, which isn't wanted in a URL.
+    let slashes = slashes.strip_prefix("//?/").unwrap_or(&slashes);
+    let mut url = "file://".to_owned();
+    if !slashes.starts_with('/') {
+        url.push('/');
+    }
+    for b in slashes.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/:".contains(&b) {
+            url.push(b as char);
+        } else {
+            url.push_str(&format!("%{:02X}", b));
+        }
+    }
+    url
+}
+
 pub fn print_error(msg: &str) {
     println!("{}: {}", style("error").bold().red(), msg);
 }
 
+pub fn print_warning(msg: &str) {
+    println!("{}: {}", style("warning").bold().yellow(), msg);
+}
+
 fn format_elapsed(since: Instant) -> String {
     format!("{:.3}s", since.elapsed().as_secs_f64())
 }
 
+/// How much of the end of the log to read to find the latest line of output.
+const LOG_TAIL_BYTES: u64 = 4096;
+
+/// Return up to the last `n` lines of a log that came from the commands that were run, skipping
+/// blank lines and cargo-mutants' own `***` lines.
+fn log_excerpt(log_content: &str, n: usize) -> Vec<&str> {
+    let mut lines: Vec<&str> = log_content
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with(LOG_MARKER))
+        .take(n)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+/// Estimate how long it will take to test some mutants, before any have been tested.
+///
+/// Each mutant is built and tested much as the baseline was, so this assumes they each take
+/// about as long.
+fn estimate_total_time(baseline_duration: Duration, n_mutants: usize) -> Duration {
+    baseline_duration.saturating_mul(u32::try_from(n_mutants).unwrap_or(u32::MAX))
+}
+
+/// Describe a count of things, such as "1 mutant" or "2 mutants".
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+/// Format a duration roughly, for estimates.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 fn format_mb(bytes: u64) -> String {
     format!("{} MB", bytes / 1_000_000)
 }
@@ -220,3 +1056,196 @@ fn format_mb(bytes: u64) -> String {
 fn style_mb(bytes: u64) -> StyledObject<String> {
     style(format_mb(bytes)).cyan()
 }
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn describe_progress_with_estimate() {
+        let mut progress = MutantProgress {
+            total: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            progress.describe(Duration::from_secs(5)),
+            "0/10 mutants tested (0%), 0 caught, 0 missed"
+        );
+        progress.done = 2;
+        progress.caught = 1;
+        progress.missed = 1;
+        progress.elapsed = Duration::from_secs(40);
+        assert_eq!(
+            progress.describe(Duration::from_secs(5)),
+            "2/10 mutants tested (20%), 1 caught, 1 missed, about 2m35s left"
+        );
+        progress.done = 4;
+        progress.unviable = 1;
+        progress.timeouts = 1;
+        progress.elapsed = Duration::from_secs(80);
+        assert_eq!(
+            progress.describe(Duration::ZERO),
+            "4/10 mutants tested (40%), 1 caught, 1 missed, 1 unviable, 1 timed out, about 2m00s left"
+        );
+        assert_eq!(format_duration(Duration::from_secs(7384)), "2h03m");
+        assert_eq!(
+            format_duration(estimate_total_time(Duration::from_millis(1500), 100)),
+            "2m30s"
+        );
+        assert_eq!(plural(1, "mutant"), "1 mutant");
+        assert_eq!(plural(0, "mutant"), "0 mutants");
+    }
+
+    #[test]
+    fn themes_need_two_characters_of_each_kind() {
+        assert!(Theme::ascii().spinner_chars().is_ascii());
+        assert!(Theme::ascii().progress_chars().is_ascii());
+        assert_eq!(Theme::new("ab", "#-").unwrap().spinner_chars(), "ab");
+        assert_eq!(
+            Theme::new("a", "#-").unwrap_err().to_string(),
+            "spinner_chars needs at least two characters"
+        );
+        assert_eq!(
+            Theme::new("ab", "").unwrap_err().to_string(),
+            "progress_chars needs at least two characters"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_urls_are_absolute_and_escaped() {
+        assert_eq!(
+            file_url(Path::new("/no such/dir/a#b.rs")),
+            "file:///no%20such/dir/a%23b.rs"
+        );
+        let url = file_url(Path::new("src/console.rs"));
+        assert!(url.starts_with("file:///"), "{}", url);
+        assert!(url.ends_with("/src/console.rs"), "{}", url);
+        assert_eq!(
+            hyperlink("file:///a", "text"),
+            "\x1b]8;;file:///a\x1b\\text\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn diff_is_formatted_by_command() {
+        let argv = ["sed".to_owned(), "s/^/> /".to_owned()];
+        assert_eq!(format_diff("-a\n+b\n", &argv).unwrap(), "> -a\n> +b\n");
+        let err = format_diff("", &["false".to_owned()]).unwrap_err();
+        assert!(err.to_string().starts_with("diff command false failed"));
+    }
+
+    #[test]
+    fn log_excerpt_skips_markers_and_blank_lines() {
+        let log = "*** replace main with ()\n\n*** run cargo test\nrunning 1 test\n\
+                   test test_factorial ... ok\n\ntest result: ok. 1 passed\n\n\
+                   *** cargo result: Exited(0) in 0.035s\n";
+        assert_eq!(
+            log_excerpt(log, 2),
+            ["test test_factorial ... ok", "test result: ok. 1 passed"]
+        );
+        assert_eq!(log_excerpt(log, 10).len(), 3);
+        assert!(log_excerpt(log, 0).is_empty());
+    }
+
+    #[test]
+    fn running_totals_count_each_kind_of_outcome() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log_file = LogFile {
+            path: tmp.path().join("log"),
+        };
+        let mut progress = MutantProgress {
+            total: 8,
+            ..Default::default()
+        };
+        for status in [
+            Status::MutantCaught,
+            Status::MutantMissed,
+            Status::CheckFailed,
+            Status::BuildFailed,
+            Status::Timeout,
+            Status::Ignored,
+        ] {
+            progress.record(&Outcome::new(&log_file, &Instant::now(), status));
+        }
+        // The time left isn't predictable, because it depends on how long this took.
+        assert!(progress
+            .describe(Duration::ZERO)
+            .starts_with("6/8 mutants tested (75%), 1 caught, 1 missed, 2 unviable, 1 timed out"));
+        assert_eq!(
+            MutantProgress::default().describe(Duration::ZERO),
+            "0/0 mutants tested (100%), 0 caught, 0 missed"
+        );
+    }
+
+    #[test]
+    fn summary_counts_mutant_outcomes() {
+        let mut progress = MutantProgress::default();
+        assert_eq!(progress.summary(), "0 mutants tested");
+        let tmp = tempfile::tempdir().unwrap();
+        let log_file = LogFile {
+            path: tmp.path().join("log"),
+        };
+        for status in [
+            Status::MutantCaught,
+            Status::MutantCaught,
+            Status::MutantMissed,
+            Status::CheckFailed,
+            Status::BuildFailed,
+            Status::BadPatch,
+        ] {
+            progress.record(&Outcome::new(&log_file, &Instant::now(), status));
+        }
+        assert_eq!(
+            progress.summary(),
+            "6 mutants tested: 2 caught, 1 missed, 2 unviable, 1 unparseable"
+        );
+    }
+
+    #[test]
+    fn estimate_is_only_shown_with_times_after_a_passing_baseline() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log_file = LogFile {
+            path: tmp.path().join("log"),
+        };
+        let baseline = |status| Outcome {
+            duration: Duration::from_secs(3),
+            ..Outcome::new(&log_file, &Instant::now(), status)
+        };
+        let console = || Console::new().interactive(false).hyperlinks(false);
+        let estimate = |console: &Console| {
+            console
+                .estimate_line(40)
+                .map(|line| console::strip_ansi_codes(&line).into_owned())
+        };
+
+        let mut passed = console();
+        passed.scenario_finished(&Scenario::Baseline, &baseline(Status::CleanTestPassed));
+        assert_eq!(
+            estimate(&passed).as_deref(),
+            Some("40 mutants to test, estimated time 2m00s")
+        );
+        assert_eq!(passed.estimate_line(0), None);
+
+        let mut no_times = console().show_times(false);
+        no_times.scenario_finished(&Scenario::Baseline, &baseline(Status::CleanTestPassed));
+        assert_eq!(estimate(&no_times), None);
+
+        let mut failed = console();
+        failed.scenario_finished(&Scenario::Baseline, &baseline(Status::CleanTestFailed));
+        assert_eq!(estimate(&failed), None);
+
+        assert_eq!(estimate(&console()), None);
+    }
+
+    #[test]
+    fn estimate_saturates_for_huge_counts() {
+        assert_eq!(
+            estimate_total_time(Duration::from_secs(1), usize::MAX),
+            Duration::from_secs(u32::MAX.into())
+        );
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright 2022 Martin Pool
+
+//! Copy a source tree into a scratch directory, skipping files that aren't needed to build and
+//! test it.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use path_slash::PathExt;
+
+use crate::source::normalize_relative_path;
+
+/// Top-level directories that are never copied: they're our own output.
+const ALWAYS_EXCLUDED: &[&str] = &["mutants.out", "mutants.out.old"];
+
+/// Top-level version control directories, which are only copied if asked for.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".bzr", ".svn", "_darcs", ".jj", ".pijul"];
+
+/// Options for copying a source tree.
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Skip files excluded by `.gitignore` files in the tree.
+    ///
+    /// The build directory and `Cargo.lock` files are copied anyhow, because they're often
+    /// ignored but copying them makes the scratch build faster and more faithful to the
+    /// source.
+    pub gitignore: bool,
+
+    /// The tree-relative path of the build directory, which is copied even if it's
+    /// gitignored.
+    pub target_dir: PathBuf,
+
+    /// Copy the files and directories that symlinks point to, rather than the symlinks
+    /// themselves.
+    ///
+    /// Symlinks that would make a cycle are skipped with a warning.
+    ///
+    /// When symlinks are preserved, relative symlinks that point outside of the tree are
+    /// rewritten to absolute paths so that they still refer to the same place.
+    pub follow_symlinks: bool,
+
+    /// Copy version control directories such as `.git`, for builds that need to read VCS
+    /// metadata.
+    ///
+    /// They're normally skipped, because they can be large and are rarely needed.
+    pub copy_vcs: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            gitignore: true,
+            target_dir: PathBuf::from("target"),
+            follow_symlinks: false,
+            copy_vcs: false,
+        }
+    }
+}
+
+/// Copy the source tree `from` into a new directory `to`.
+///
+/// `bytes_copied` is called with the running total of bytes copied after each entry.
+///
+/// Returns the total number of bytes of file content copied.
+pub fn copy_tree<F>(from: &Path, to: &Path, options: &CopyOptions, bytes_copied: F) -> Result<u64>
+where
+    F: FnMut(u64),
+{
+    let mut copier = TreeCopier {
+        options,
+        bytes_copied,
+        total_bytes: 0,
+        active_dirs: Vec::new(),
+    };
+    copier
+        .copy_dir(from, to, true)
+        .context("copy source tree to scratch directory")?;
+    Ok(copier.total_bytes)
+}
+
+struct TreeCopier<'a, F> {
+    options: &'a CopyOptions,
+    bytes_copied: F,
+    total_bytes: u64,
+
+    /// Canonical paths of the directories currently being copied, to detect symlink cycles.
+    active_dirs: Vec<PathBuf>,
+}
+
+impl<'a, F> TreeCopier<'a, F>
+where
+    F: FnMut(u64),
+{
+    /// Copy a directory.
+    ///
+    /// Exclusions of our own output and of gitignored files apply only in the top-level
+    /// source tree, not in directories reached by following symlinks.
+    fn copy_dir(&mut self, from: &Path, to: &Path, is_top: bool) -> Result<()> {
+        let canonical = fs::canonicalize(from)
+            .with_context(|| format!("canonicalize {}", from.to_slash_lossy()))?;
+        self.active_dirs.push(canonical);
+        let options = self.options;
+        let mut gitignore_filter = GitignoreFilter::new(from);
+        let mut symlinks = Vec::new();
+        let base_bytes = self.total_bytes;
+        let bytes_copied = &mut self.bytes_copied;
+        let stats = cp_r::CopyOptions::new()
+            .filter(|path, dir_entry| {
+                if is_top
+                    && ALWAYS_EXCLUDED
+                        .iter()
+                        .any(|excluded| path == Path::new(excluded))
+                {
+                    return Ok(false);
+                }
+                if is_top
+                    && !options.copy_vcs
+                    && VCS_DIRS.iter().any(|vcs_dir| path == Path::new(vcs_dir))
+                {
+                    return Ok(false);
+                }
+                let file_type = dir_entry.file_type().ok();
+                if is_top
+                    && options.gitignore
+                    && path != options.target_dir
+                    && !path.ends_with("Cargo.lock")
+                {
+                    let is_dir = matches!(file_type, Some(ft) if ft.is_dir());
+                    if gitignore_filter.is_ignored(path, is_dir) {
+                        return Ok(false);
+                    }
+                }
+                if matches!(file_type, Some(ft) if ft.is_symlink()) {
+                    // Copied afterwards, once their parent directories exist.
+                    symlinks.push(path.to_owned());
+                    return Ok(false);
+                }
+                Ok(true)
+            })
+            .after_entry_copied(|_path, _ft, stats| bytes_copied(base_bytes + stats.file_bytes))
+            .copy_tree(from, to)?;
+        self.total_bytes = base_bytes + stats.file_bytes;
+        for symlink in symlinks {
+            if self.options.follow_symlinks {
+                self.follow_symlink(&from.join(&symlink), &to.join(&symlink))?;
+            } else {
+                preserve_symlink(from, &symlink, &to.join(&symlink))?;
+            }
+        }
+        self.active_dirs.pop();
+        Ok(())
+    }
+
+    /// Copy whatever a symlink points to.
+    fn follow_symlink(&mut self, from: &Path, to: &Path) -> Result<()> {
+        let metadata = match fs::metadata(from) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                eprintln!(
+                    "warning: not copying broken symlink {}: {}",
+                    from.to_slash_lossy(),
+                    err
+                );
+                return Ok(());
+            }
+        };
+        if metadata.is_dir() {
+            let canonical = fs::canonicalize(from)
+                .with_context(|| format!("canonicalize {}", from.to_slash_lossy()))?;
+            if self.active_dirs.contains(&canonical) {
+                eprintln!(
+                    "warning: not following symlink {} because it makes a cycle",
+                    from.to_slash_lossy()
+                );
+                return Ok(());
+            }
+            self.copy_dir(from, to, false)
+        } else {
+            self.total_bytes +=
+                fs::copy(from, to).with_context(|| format!("copy {}", from.to_slash_lossy()))?;
+            (self.bytes_copied)(self.total_bytes);
+            Ok(())
+        }
+    }
+}
+
+/// Copy a symlink as a symlink.
+///
+/// If it's a relative link pointing outside the tree, rewrite it to point to the absolute
+/// path of its target, since the relative path won't resolve from the copy.
+fn preserve_symlink(root: &Path, tree_relative: &Path, to: &Path) -> Result<()> {
+    let from = root.join(tree_relative);
+    let mut target =
+        fs::read_link(&from).with_context(|| format!("read symlink {}", from.to_slash_lossy()))?;
+    let parent = tree_relative.parent().unwrap_or_else(|| Path::new(""));
+    if target.is_relative() && normalize_relative_path(&parent.join(&target)).is_none() {
+        target = std::env::current_dir()?
+            .join(root)
+            .join(parent)
+            .join(target);
+    }
+    make_symlink(&target, to, from.is_dir())
+        .with_context(|| format!("create symlink {}", to.to_slash_lossy()))
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &Path, link: &Path, _is_dir: bool) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn make_symlink(target: &Path, link: &Path, is_dir: bool) -> std::io::Result<()> {
+    if is_dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// Matches paths against the `.gitignore` files in a tree.
+struct GitignoreFilter {
+    root: PathBuf,
+
+    /// Parsed `.gitignore` files, keyed by the tree-relative directory containing them.
+    ///
+    /// Directories without a `.gitignore` have an empty matcher.
+    matchers: HashMap<PathBuf, Gitignore>,
+}
+
+impl GitignoreFilter {
+    fn new(root: &Path) -> GitignoreFilter {
+        GitignoreFilter {
+            root: root.to_owned(),
+            matchers: HashMap::new(),
+        }
+    }
+
+    /// True if a tree-relative path is ignored by the `.gitignore` in any of its parent
+    /// directories.
+    ///
+    /// Rules in deeper directories take precedence, as they do in git.
+    fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let full_path = self.root.join(path);
+        for dir in path.ancestors().skip(1) {
+            let matched = self.matcher(dir).matched(&full_path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            } else if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn matcher(&mut self, dir: &Path) -> &Gitignore {
+        let root = &self.root;
+        self.matchers.entry(dir.to_owned()).or_insert_with(|| {
+            let dir = root.join(dir);
+            let mut builder = GitignoreBuilder::new(&dir);
+            let gitignore_path = dir.join(".gitignore");
+            if gitignore_path.is_file() {
+                if let Some(err) = builder.add(&gitignore_path) {
+                    eprintln!("error reading {}: {}", gitignore_path.to_slash_lossy(), err);
+                }
+            }
+            builder.build().unwrap_or_else(|err| {
+                eprintln!("error parsing {}: {}", gitignore_path.to_slash_lossy(), err);
+                Gitignore::empty()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::write_tree;
+
+    #[test]
+    fn vcs_dir_is_only_copied_if_requested() {
+        let src = tempfile::tempdir().unwrap();
+        let src = src.path();
+        fs::create_dir_all(src.join(".git/refs")).unwrap();
+        fs::write(src.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(src.join("Cargo.toml"), "").unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().join("build");
+        copy_tree(src, &dest, &CopyOptions::default(), |_| ()).unwrap();
+        assert!(dest.join("Cargo.toml").is_file());
+        assert!(!dest.join(".git").exists());
+
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().join("build");
+        let options = CopyOptions {
+            copy_vcs: true,
+            ..CopyOptions::default()
+        };
+        copy_tree(src, &dest, &options, |_| ()).unwrap();
+        assert!(dest.join(".git/HEAD").is_file());
+    }
+
+    #[test]
+    fn copy_respects_gitignore() {
+        let src = write_tree(&[
+            (
+                ".gitignore",
+                "data/\n*.bin\n!keep.bin\ntarget\nCargo.lock\n",
+            ),
+            ("Cargo.lock", ""),
+            ("Cargo.toml", ""),
+            ("data/huge.dat", ""),
+            ("src/lib.rs", ""),
+            ("src/big.bin", ""),
+            ("src/keep.bin", ""),
+            ("src/fixtures/.gitignore", "*.tmp\n"),
+            ("src/fixtures/a.tmp", ""),
+            ("src/fixtures/a.txt", ""),
+            ("target/debug/out", ""),
+            ("mutants.out/log", ""),
+        ]);
+        let src = src.path();
+
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().join("build");
+        copy_tree(src, &dest, &CopyOptions::default(), |_| ()).unwrap();
+        for copied in [
+            "Cargo.lock",
+            "Cargo.toml",
+            "src/lib.rs",
+            "src/keep.bin",
+            "src/fixtures/a.txt",
+            "target/debug/out",
+        ] {
+            assert!(dest.join(copied).is_file(), "{} should be copied", copied);
+        }
+        for skipped in ["data", "src/big.bin", "src/fixtures/a.tmp", "mutants.out"] {
+            assert!(
+                !dest.join(skipped).exists(),
+                "{} should be skipped",
+                skipped
+            );
+        }
+
+        let dest = tempfile::tempdir().unwrap();
+        let dest = dest.path().join("build");
+        copy_tree(
+            src,
+            &dest,
+            &CopyOptions {
+                gitignore: false,
+                ..CopyOptions::default()
+            },
+            |_| (),
+        )
+        .unwrap();
+        assert!(dest.join("data/huge.dat").is_file());
+        assert!(dest.join("src/fixtures/a.tmp").is_file());
+        assert!(!dest.join("mutants.out").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let outer = tempfile::tempdir().unwrap();
+        let src = outer.path().join("src");
+        fs::create_dir_all(src.join("data/sub")).unwrap();
+        fs::create_dir_all(outer.path().join("shared")).unwrap();
+        fs::write(src.join("data/a.txt"), "a").unwrap();
+        fs::write(outer.path().join("shared/s.txt"), "s").unwrap();
+        symlink("data/a.txt", src.join("inside")).unwrap();
+        symlink("../shared", src.join("outside")).unwrap();
+        symlink("../..", src.join("data/sub/loop")).unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let preserved = dest.path().join("preserved");
+        copy_tree(&src, &preserved, &CopyOptions::default(), |_| ()).unwrap();
+        assert_eq!(
+            fs::read_link(preserved.join("inside")).unwrap(),
+            Path::new("data/a.txt")
+        );
+        assert!(fs::read_link(preserved.join("outside"))
+            .unwrap()
+            .is_absolute());
+        assert_eq!(
+            fs::read_to_string(preserved.join("outside/s.txt")).unwrap(),
+            "s"
+        );
+        assert_eq!(
+            fs::read_link(preserved.join("data/sub/loop")).unwrap(),
+            Path::new("../..")
+        );
+
+        let followed = dest.path().join("followed");
+        let options = CopyOptions {
+            follow_symlinks: true,
+            ..CopyOptions::default()
+        };
+        copy_tree(&src, &followed, &options, |_| ()).unwrap();
+        assert!(followed.join("inside").is_file());
+        assert!(!fs::symlink_metadata(followed.join("inside"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(followed.join("outside/s.txt").is_file());
+        assert!(!followed.join("data/sub/loop").exists());
+    }
+}
@@ -25,3 +25,8 @@ pub const TIMEOUT: i32 = 3;
 
 /// The tests are already failing in a copy of the clean tree.
 pub const CLEAN_TESTS_FAILED: i32 = 4;
+
+/// Some mutants produced code that doesn't parse, which is a bug in cargo-mutants or a plugin.
+///
+/// (70 is `EX_SOFTWARE` from `sysexits.h`.)
+pub const SOFTWARE: i32 = 70;
@@ -0,0 +1,628 @@
+// Copyright 2022 Martin Pool
+
+//! A builder for the options of a mutation testing run, for programs that use cargo-mutants as
+//! a library.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use cargo_mutants::console::Console;
+//! use cargo_mutants::experiment::Experiment;
+//!
+//! let experiment = Experiment::builder()
+//!     .dir("path/to/crate")
+//!     .timeout(Duration::from_secs(120))
+//!     .build()?;
+//! let lab_outcome = experiment.run(&mut Console::new())?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Like the `cargo mutants` command, the builder reads the tree's `.cargo/mutants.toml` and
+//! `mutants-ignore.toml`, and options set on the builder add to, or override, those in the
+//! config file.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::config::Config;
+use crate::console::Theme;
+use crate::copy_tree::CopyOptions;
+use crate::ignore_list::IgnoreList;
+use crate::lab::{self, ExperimentOptions, LabOutcome};
+use crate::mutate::Mutation;
+use crate::observer::Observer;
+use crate::operators::{Operators, Plugin};
+use crate::plan::Plan;
+use crate::source::{DiscoveryOptions, Skipped, SourceTree, TargetSelection};
+use crate::verdict::VerdictCommand;
+
+/// A source tree and the options for testing its mutants.
+#[derive(Debug)]
+pub struct Experiment {
+    source_tree: SourceTree,
+    options: ExperimentOptions,
+}
+
+impl Experiment {
+    /// Start building an experiment, with default options, for the crate in the current
+    /// directory.
+    pub fn builder() -> ExperimentBuilder {
+        ExperimentBuilder::default()
+    }
+
+    pub fn source_tree(&self) -> &SourceTree {
+        &self.source_tree
+    }
+
+    pub fn options(&self) -> &ExperimentOptions {
+        &self.options
+    }
+
+    /// Return the mutants that would be tested, without testing them.
+    pub fn mutations(&self) -> Result<Vec<Mutation>> {
+        self.options
+            .mutations(&self.source_tree.discover_files(&self.options.discovery))
+    }
+
+    /// Return the mutants that would be tested, and the functions, items, and files that are
+    /// skipped, without testing anything.
+    pub fn discover(&self) -> Result<(Vec<Mutation>, Vec<Skipped>)> {
+        let (mutations, skipped) = self
+            .source_tree
+            .discover_files(&self.options.discovery)
+            .discover()?;
+        Ok((self.options.select_mutations(mutations)?, skipped))
+    }
+
+    /// Test all the mutants, reporting progress and results to `observer`.
+    pub fn run(&self, observer: &mut dyn Observer) -> Result<LabOutcome> {
+        lab::experiment(&self.source_tree, &self.options, observer)
+    }
+}
+
+/// Builds an [Experiment].
+///
+/// Every option has a default that matches the `cargo mutants` command with no arguments.
+/// Nothing is checked until [ExperimentBuilder::build] is called.
+#[derive(Debug)]
+pub struct ExperimentBuilder {
+    dir: PathBuf,
+    use_config: bool,
+    check_only: bool,
+    gitignore: bool,
+    copy_vcs: bool,
+    follow_symlinks: bool,
+    offline: bool,
+    locked: bool,
+    frozen: bool,
+    include_examples: bool,
+    include_benches: bool,
+    include_test_helpers: bool,
+    mutate_unsafe: bool,
+    skip_trivial: bool,
+    skip_unsafe_blocks: bool,
+    leak_references: bool,
+    exclude_globs: Vec<String>,
+    exclude_cfgs: Vec<String>,
+    targets: TargetSelection,
+    skip_operators: Vec<String>,
+    plugins: Vec<PathBuf>,
+    feature_sets: Vec<Vec<String>>,
+    timeout: Option<Duration>,
+    plan: Option<Plan>,
+    verdict_command: Option<Vec<String>>,
+    max_mutants_per_function: Option<usize>,
+    seed: u64,
+    diff_command: Option<Vec<String>>,
+    ascii: bool,
+}
+
+impl Default for ExperimentBuilder {
+    fn default() -> Self {
+        ExperimentBuilder {
+            dir: PathBuf::from("."),
+            use_config: true,
+            check_only: false,
+            gitignore: true,
+            copy_vcs: false,
+            follow_symlinks: false,
+            offline: false,
+            locked: false,
+            frozen: false,
+            include_examples: false,
+            include_benches: false,
+            include_test_helpers: false,
+            mutate_unsafe: false,
+            skip_trivial: false,
+            skip_unsafe_blocks: false,
+            leak_references: false,
+            exclude_globs: Vec::new(),
+            exclude_cfgs: Vec::new(),
+            targets: TargetSelection::default(),
+            skip_operators: Vec::new(),
+            plugins: Vec::new(),
+            feature_sets: Vec::new(),
+            timeout: None,
+            plan: None,
+            verdict_command: None,
+            max_mutants_per_function: None,
+            seed: 0,
+            diff_command: None,
+            ascii: false,
+        }
+    }
+}
+
+impl ExperimentBuilder {
+    /// The crate directory to test, which must contain a `Cargo.toml`.
+    pub fn dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = dir.as_ref().to_owned();
+        self
+    }
+
+    /// Read `.cargo/mutants.toml` and `mutants-ignore.toml` from the tree (default true).
+    pub fn use_config(mut self, use_config: bool) -> Self {
+        self.use_config = use_config;
+        self
+    }
+
+    /// Only `cargo check` each mutant, rather than running its tests.
+    pub fn check_only(mut self, check_only: bool) -> Self {
+        self.check_only = check_only;
+        self
+    }
+
+    /// Skip copying files excluded by `.gitignore` to the scratch directory (default true).
+    pub fn gitignore(mut self, gitignore: bool) -> Self {
+        self.gitignore = gitignore;
+        self
+    }
+
+    /// Copy version control directories into the scratch directory.
+    pub fn copy_vcs(mut self, copy_vcs: bool) -> Self {
+        self.copy_vcs = copy_vcs;
+        self
+    }
+
+    /// Copy the targets of symlinks into the scratch directory, rather than the symlinks.
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Pass `--offline` to cargo.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Pass `--locked` to cargo.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Pass `--frozen` to cargo.
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Also mutate code in `examples/`.
+    pub fn include_examples(mut self, include_examples: bool) -> Self {
+        self.include_examples = include_examples;
+        self
+    }
+
+    /// Also mutate code in `benches/`.
+    pub fn include_benches(mut self, include_benches: bool) -> Self {
+        self.include_benches = include_benches;
+        self
+    }
+
+    /// Also mutate non-test helper code in `tests/`.
+    pub fn include_test_helpers(mut self, include_test_helpers: bool) -> Self {
+        self.include_test_helpers = include_test_helpers;
+        self
+    }
+
+    /// Also mutate `unsafe fn`s, which are skipped by default.
+    pub fn mutate_unsafe(mut self, mutate_unsafe: bool) -> Self {
+        self.mutate_unsafe = mutate_unsafe;
+        self
+    }
+
+    /// Don't mutate trivial accessors, whose body only reads or assigns a field, such as
+    /// `self.name` or `self.name = name;`.
+    pub fn skip_trivial(mut self, skip_trivial: bool) -> Self {
+        self.skip_trivial = skip_trivial;
+        self
+    }
+
+    /// Don't mutate functions that contain `unsafe` blocks, where a mutant might cause
+    /// undefined behavior.
+    pub fn skip_unsafe_blocks(mut self, skip_unsafe_blocks: bool) -> Self {
+        self.skip_unsafe_blocks = skip_unsafe_blocks;
+        self
+    }
+
+    /// Mutate functions returning references to return a leaked default value, which is off
+    /// by default.
+    pub fn leak_references(mut self, leak_references: bool) -> Self {
+        self.leak_references = leak_references;
+        self
+    }
+
+    /// Don't mutate files matching this gitignore-style pattern (can be repeated).
+    pub fn exclude_glob<S: Into<String>>(mut self, glob: S) -> Self {
+        self.exclude_globs.push(glob.into());
+        self
+    }
+
+    /// Don't mutate items marked with a `#[cfg(...)]` that's false when this cfg predicate, such
+    /// as `fuzzing` or `feature = "bench"`, is false (can be repeated).
+    pub fn exclude_cfg<S: Into<String>>(mut self, cfg: S) -> Self {
+        self.exclude_cfgs.push(cfg.into());
+        self
+    }
+
+    /// Only mutate the selected targets.
+    pub fn targets(mut self, targets: TargetSelection) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Don't generate mutants from the named operator (can be repeated).
+    pub fn skip_operator<S: Into<String>>(mut self, name: S) -> Self {
+        self.skip_operators.push(name.into());
+        self
+    }
+
+    /// Run this plugin program to suggest more mutants (can be repeated).
+    pub fn plugin<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.plugins.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Test each mutant with these cargo feature arguments (can be repeated).
+    ///
+    /// If none are given, the feature sets from the config file are used, and otherwise the
+    /// default features.
+    pub fn feature_set<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.feature_sets
+            .push(args.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Stop the tests for any mutant after this long, and count it as a timeout.
+    ///
+    /// By default there's no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Test only the mutants in this plan.
+    pub fn plan(mut self, plan: Plan) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+
+    /// Run this command, with its arguments, after testing each mutant, to review its outcome.
+    ///
+    /// This overrides any verdict command in the config file. See [crate::verdict].
+    pub fn verdict_command<I, S>(mut self, argv: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.verdict_command = Some(argv.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Test at most this many mutants of each function, choosing a representative sample of
+    /// them if there are more.
+    ///
+    /// This overrides any limit in the config file.
+    pub fn max_mutants_per_function(mut self, max: usize) -> Self {
+        self.max_mutants_per_function = Some(max);
+        self
+    }
+
+    /// Seed the choice of mutants from functions with more than the maximum (default 0).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Format diffs shown on a terminal by piping them through this command, with its
+    /// arguments, such as `delta`.
+    ///
+    /// This overrides any diff command in the config file.
+    pub fn diff_command<I, S>(mut self, argv: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.diff_command = Some(argv.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Draw progress bars using only ASCII characters, rather than the theme from the config
+    /// file or the default Unicode characters.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Check the options, read the config file, and return the experiment.
+    pub fn build(self) -> Result<Experiment> {
+        let source_tree = SourceTree::new(&self.dir)?;
+        let (config, ignore_list) = if self.use_config {
+            (
+                Config::read_tree_config(source_tree.root())?,
+                IgnoreList::read_tree(source_tree.root())?,
+            )
+        } else {
+            Default::default()
+        };
+        if self.timeout == Some(Duration::ZERO) {
+            return Err(anyhow!("timeout must be greater than zero"));
+        }
+        let max_mutants_per_function = self
+            .max_mutants_per_function
+            .or(config.max_mutants_per_function);
+        if max_mutants_per_function == Some(0) {
+            return Err(anyhow!(
+                "max_mutants_per_function must be greater than zero"
+            ));
+        }
+        let plugins = self
+            .plugins
+            .iter()
+            .cloned()
+            .chain(
+                config
+                    .plugins
+                    .iter()
+                    .map(|path| source_tree.root().join(path)),
+            )
+            .map(|path| Plugin::new(&path))
+            .collect();
+        let operators = Operators::all()
+            .leak_references(self.leak_references || config.leak_references)
+            .with_impl_trait_replacements(config.impl_trait_replacements)
+            .with_constructors(config.constructors)
+            .with_result_aliases(&config.result_aliases)
+            .without(&[self.skip_operators, config.skip_operators].concat())?
+            .with_plugins(plugins);
+        let verdict_command = self
+            .verdict_command
+            .or(config.verdict_command)
+            .map(|argv| VerdictCommand::new(&argv, source_tree.root()))
+            .transpose()?;
+        let diff_command = self.diff_command.or(config.diff_command);
+        if diff_command.as_ref().is_some_and(Vec::is_empty) {
+            return Err(anyhow!("diff command is empty"));
+        }
+        let theme = if self.ascii || config.ascii {
+            Theme::ascii()
+        } else {
+            let default = Theme::default();
+            Theme::new(
+                config
+                    .spinner_chars
+                    .as_deref()
+                    .unwrap_or_else(|| default.spinner_chars()),
+                config
+                    .progress_chars
+                    .as_deref()
+                    .unwrap_or_else(|| default.progress_chars()),
+            )?
+        };
+        let feature_sets = if self.feature_sets.is_empty() {
+            config.feature_sets
+        } else {
+            self.feature_sets
+        };
+        let options = ExperimentOptions {
+            check_only: self.check_only,
+            copy: CopyOptions {
+                gitignore: self.gitignore,
+                follow_symlinks: self.follow_symlinks || config.follow_symlinks,
+                copy_vcs: self.copy_vcs || config.copy_vcs,
+                ..Default::default()
+            },
+            discovery: DiscoveryOptions {
+                include_examples: self.include_examples || config.include_examples,
+                include_benches: self.include_benches || config.include_benches,
+                include_test_helpers: self.include_test_helpers || config.include_test_helpers,
+                exclude_globs: [config.exclude_globs, self.exclude_globs].concat(),
+                exclude_cfgs: [config.exclude_cfgs, self.exclude_cfgs].concat(),
+                targets: self.targets,
+                mutate_unsafe: self.mutate_unsafe || config.mutate_unsafe,
+                skip_trivial: self.skip_trivial || config.skip_trivial,
+                skip_unsafe_blocks: self.skip_unsafe_blocks || config.skip_unsafe_blocks,
+                operators,
+            },
+            feature_sets,
+            offline: self.offline,
+            locked: self.locked,
+            frozen: self.frozen,
+            test_timeout: self.timeout,
+            plan: self.plan,
+            ignore_list,
+            verdict_command,
+            max_mutants_per_function,
+            seed: self.seed,
+            diff_command,
+            theme,
+        };
+        Ok(Experiment {
+            source_tree,
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn builder_defaults_match_the_command_line() {
+        let experiment = Experiment::builder()
+            .dir("testdata/tree/factorial")
+            .build()
+            .unwrap();
+        let options = experiment.options();
+        assert!(!options.check_only);
+        assert!(options.copy.gitignore);
+        assert_eq!(options.test_timeout, None);
+        assert!(options.feature_sets.is_empty());
+        assert_eq!(experiment.mutations().unwrap().len(), 9);
+    }
+
+    #[test]
+    fn builder_options_are_merged_with_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("Cargo.toml"), "").unwrap();
+        fs::create_dir(tmp.path().join(".cargo")).unwrap();
+        fs::write(
+            tmp.path().join(".cargo/mutants.toml"),
+            "include_benches = true\nexclude_globs = [\"src/gen.rs\"]\nexclude_cfgs = [\"fuzzing\"]\nfeature_sets = [[\"--all-features\"]]\n",
+        )
+        .unwrap();
+        let experiment = Experiment::builder()
+            .dir(tmp.path())
+            .include_examples(true)
+            .exclude_glob("src/big.rs")
+            .exclude_cfg("windows")
+            .timeout(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let options = experiment.options();
+        assert!(options.discovery.include_examples);
+        assert!(options.discovery.include_benches);
+        assert_eq!(
+            options.discovery.exclude_globs,
+            ["src/gen.rs", "src/big.rs"]
+        );
+        assert_eq!(options.discovery.exclude_cfgs, ["fuzzing", "windows"]);
+        assert_eq!(options.feature_sets, [["--all-features"]]);
+        assert_eq!(options.test_timeout, Some(Duration::from_secs(60)));
+
+        // Feature sets given to the builder replace those from the config.
+        let experiment = Experiment::builder()
+            .dir(tmp.path())
+            .feature_set(["--no-default-features"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            experiment.options().feature_sets,
+            [["--no-default-features"]]
+        );
+
+        let experiment = Experiment::builder()
+            .dir(tmp.path())
+            .use_config(false)
+            .build()
+            .unwrap();
+        assert!(!experiment.options().discovery.include_benches);
+
+        fs::write(
+            tmp.path().join(".cargo/mutants.toml"),
+            "diff_command = [\"delta\", \"--side-by-side\"]\n",
+        )
+        .unwrap();
+        let experiment = Experiment::builder().dir(tmp.path()).build().unwrap();
+        assert_eq!(
+            experiment.options().diff_command.as_deref(),
+            Some(&["delta".to_owned(), "--side-by-side".to_owned()][..])
+        );
+        let experiment = Experiment::builder()
+            .dir(tmp.path())
+            .diff_command(["bat", "-l", "diff"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            experiment.options().diff_command.as_deref(),
+            Some(&["bat".to_owned(), "-l".to_owned(), "diff".to_owned()][..])
+        );
+    }
+
+    #[test]
+    fn theme_comes_from_config_unless_ascii() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("Cargo.toml"), "").unwrap();
+        fs::create_dir(tmp.path().join(".cargo")).unwrap();
+        fs::write(
+            tmp.path().join(".cargo/mutants.toml"),
+            "spinner_chars = \"oO.\"\n",
+        )
+        .unwrap();
+        let experiment = Experiment::builder().dir(tmp.path()).build().unwrap();
+        let theme = &experiment.options().theme;
+        assert_eq!(theme.spinner_chars(), "oO.");
+        assert_eq!(theme.progress_chars(), Theme::default().progress_chars());
+
+        let experiment = Experiment::builder()
+            .dir(tmp.path())
+            .ascii(true)
+            .build()
+            .unwrap();
+        assert_eq!(experiment.options().theme, Theme::ascii());
+
+        fs::write(
+            tmp.path().join(".cargo/mutants.toml"),
+            "progress_chars = \"#\"\n",
+        )
+        .unwrap();
+        let err = Experiment::builder().dir(tmp.path()).build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "progress_chars needs at least two characters"
+        );
+    }
+
+    #[test]
+    fn builder_checks_options() {
+        let err = Experiment::builder()
+            .dir("testdata/tree/factorial")
+            .timeout(Duration::ZERO)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "timeout must be greater than zero");
+
+        let err = Experiment::builder()
+            .dir("testdata/tree/factorial")
+            .max_mutants_per_function(0)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "max_mutants_per_function must be greater than zero"
+        );
+
+        let err = Experiment::builder()
+            .dir("testdata/tree/factorial")
+            .skip_operator("wibble")
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unknown mutation operator \"wibble\""));
+
+        assert!(Experiment::builder().dir("testdata").build().is_err());
+    }
+}
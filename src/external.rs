@@ -0,0 +1,183 @@
+// Copyright 2022 Martin Pool
+
+//! Offload building and testing mutants to an external system, such as a build farm or another
+//! build system, while still using cargo-mutants to find mutants and report the results.
+//!
+//! Exporting writes `plan.json`, listing the mutants each with its stable id, and a patch for
+//! each mutant in `patches/<id>.diff`, which can be applied to the tree with `patch -p1`.
+//!
+//! The external system then tests each patched tree, and writes `results.json` into the same
+//! directory, mapping mutant ids to verdicts:
+//!
+//! ```json
+//! { "results": { "0c1f6e0f5b8a2d7e": "caught", "9d4e2c1a6b3f8e05": "missed" } }
+//! ```
+//!
+//! Importing reads the plan and results and reports them, just as if cargo-mutants had tested
+//! the mutants itself.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use path_slash::PathExt;
+use serde::Deserialize;
+
+use crate::lab::{with_reporters, LabOutcome, Outcome, Status, LOG_MARKER};
+use crate::mutate::Mutation;
+use crate::observer::{Observer, Scenario};
+use crate::plan::Plan;
+use crate::source::{DiscoveryOptions, SourceTree};
+
+/// The name of the plan file in the exchange directory.
+pub const PLAN_NAME: &str = "plan.json";
+
+/// The name of the results file in the exchange directory.
+pub const RESULTS_NAME: &str = "results.json";
+
+/// The name of the directory of patches in the exchange directory.
+pub const PATCHES_DIR: &str = "patches";
+
+/// The result of testing one mutant externally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// The tests failed with the mutant applied.
+    Caught,
+    /// The tests passed with the mutant applied.
+    Missed,
+    /// The tests ran too long and were stopped.
+    Timeout,
+    /// The mutant didn't build.
+    Unviable,
+}
+
+impl Verdict {
+    fn status(self) -> Status {
+        match self {
+            Verdict::Caught => Status::MutantCaught,
+            Verdict::Missed => Status::MutantMissed,
+            Verdict::Timeout => Status::Timeout,
+            Verdict::Unviable => Status::BuildFailed,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Results {
+    results: HashMap<String, Verdict>,
+}
+
+/// Write a plan for the mutations, and a patch for each of them, into `dir`.
+pub fn export(mutations: &[Mutation], dir: &Path) -> Result<Plan> {
+    let patches_dir = dir.join(PATCHES_DIR);
+    fs::create_dir_all(&patches_dir)
+        .with_context(|| format!("create {}", patches_dir.to_slash_lossy()))?;
+    let plan = Plan::new(mutations);
+    for (planned, mutation) in plan.mutants.iter().zip(mutations) {
+        let path = patches_dir.join(format!("{}.diff", planned.id));
+        fs::write(&path, mutation.patch())
+            .with_context(|| format!("write {}", path.to_slash_lossy()))?;
+    }
+    plan.write(&dir.join(PLAN_NAME))?;
+    Ok(plan)
+}
+
+/// Read the plan and results from `dir`, and report the outcome of each mutant to `observer`
+/// and into `mutants.out`, as if they'd been tested here.
+///
+/// Mutants with no result are reported as a warning, and not counted.
+pub fn import_results(
+    source_tree: &SourceTree,
+    options: &DiscoveryOptions,
+    dir: &Path,
+    observer: &mut dyn Observer,
+) -> Result<LabOutcome> {
+    let plan = Plan::read(&dir.join(PLAN_NAME))?;
+    let results = read_results(&dir.join(RESULTS_NAME))?;
+    let planned_ids: HashSet<&str> = plan.mutants.iter().map(|m| m.id.as_str()).collect();
+    let mut unknown_ids: Vec<&str> = results
+        .keys()
+        .map(String::as_str)
+        .filter(|id| !planned_ids.contains(id))
+        .collect();
+    if !unknown_ids.is_empty() {
+        unknown_ids.sort_unstable();
+        return Err(anyhow!(
+            "{} has results for mutants that aren't in the plan: {}",
+            RESULTS_NAME,
+            unknown_ids.join(", ")
+        ));
+    }
+    let (ids, mutations): (Vec<String>, Vec<Mutation>) = plan
+        .select(source_tree.mutations(options)?)?
+        .into_iter()
+        .unzip();
+    with_reporters(source_tree, observer, |output_dir, observer| {
+        let mut lab_outcome = LabOutcome::default();
+        observer.mutants_discovered(&mutations);
+        for (id, mutation) in ids.iter().zip(&mutations) {
+            let verdict = match results.get(id) {
+                Some(verdict) => verdict,
+                None => {
+                    observer.warning(&format!("no result for mutant {}: {}", id, mutation));
+                    continue;
+                }
+            };
+            let scenario = Scenario::Mutant(mutation);
+            let start = Instant::now();
+            let scenario_name = mutation.to_string();
+            let (mut out_file, log_file) = output_dir.create_log(&scenario_name)?;
+            observer.scenario_started(&scenario, &log_file);
+            writeln!(out_file, "{} {}", LOG_MARKER, scenario_name)?;
+            writeln!(out_file, "{} mutant id {}", LOG_MARKER, mutation.id())?;
+            writeln!(out_file, "{}", mutation.diff())?;
+            writeln!(
+                out_file,
+                "{} result imported from {}: {:?}",
+                LOG_MARKER, RESULTS_NAME, verdict
+            )?;
+            let outcome = Outcome::new(&log_file, &start, verdict.status());
+            lab_outcome.add(&outcome);
+            observer.scenario_finished(&scenario, &outcome);
+        }
+        Ok(lab_outcome)
+    })
+}
+
+fn read_results(path: &Path) -> Result<HashMap<String, Verdict>> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("read {}", path.to_slash_lossy()))?;
+    let results: Results = serde_json::from_str(&json)
+        .with_context(|| format!("parse results {}", path.to_slash_lossy()))?;
+    Ok(results.results)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn read_results_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(RESULTS_NAME);
+        fs::write(
+            &path,
+            r#"{"results": {"1": "caught", "2": "missed", "3": "unviable", "4": "timeout"}}"#,
+        )
+        .unwrap();
+        let results = read_results(&path).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results["1"], Verdict::Caught);
+        assert_eq!(results["3"].status(), Status::BuildFailed);
+
+        fs::write(&path, r#"{"results": {"1": "maybe"}}"#).unwrap();
+        assert!(read_results(&path).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2022 Martin Pool
+
+//! `mutants-ignore.toml`: mutants that people have judged to be equivalent to the original
+//! code, or otherwise acceptable to miss.
+//!
+//! Ignored mutants aren't tested, so they never count as missed and don't affect the exit code.
+//! The file is read from the top of the source tree, so it can be committed and reviewed
+//! alongside the code. For example:
+//!
+//! ```toml
+//! [[ignore]]
+//! function = "Money::round"
+//! op = "default"
+//! reason = "Rounding zero gives zero."
+//!
+//! [[ignore]]
+//! id = "5f0b7d1c9e2a4b68"
+//! reason = "Logging only."
+//! ```
+//!
+//! Entries can name a mutant by its stable id, as shown in `mutants.json` or by
+//! `--list --json`, which picks out exactly one mutant and keeps matching it when other edits
+//! move it to a different line, but stops matching if the function itself is edited.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use path_slash::PathExt;
+use serde::Deserialize;
+
+use crate::mutate::Mutation;
+
+/// The name of the ignore list file, in the root of the source tree.
+pub const IGNORE_LIST_NAME: &str = "mutants-ignore.toml";
+
+/// A list of mutants that shouldn't be tested.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IgnoreList {
+    pub ignore: Vec<IgnoreEntry>,
+}
+
+/// One entry in the ignore list, matching mutants by their id or function, and optionally by
+/// their operator, file, and replacement text.
+///
+/// Every entry must have at least an id or a function.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IgnoreEntry {
+    /// The stable id of the mutant, from [Mutation::id].
+    pub id: Option<String>,
+
+    /// The name of the function, including enclosing modules and types, as shown by `--list`.
+    pub function: Option<String>,
+
+    /// The name of the operator, or None to ignore mutants from every operator.
+    pub op: Option<String>,
+
+    /// The path of the source file, relative to the tree root, with forward slashes.
+    pub file: Option<String>,
+
+    /// The text that replaces the function body.
+    pub replacement: Option<String>,
+
+    /// Why the mutant is ignored: not used by cargo-mutants, but helpful to readers.
+    pub reason: Option<String>,
+}
+
+impl IgnoreEntry {
+    fn matches(&self, mutation: &Mutation) -> bool {
+        self.id.as_ref().is_none_or(|id| id == mutation.id())
+            && self
+                .function
+                .as_ref()
+                .is_none_or(|function| function == mutation.function_name())
+            && self.op.as_ref().is_none_or(|op| *op == mutation.op)
+            && self
+                .file
+                .as_ref()
+                .is_none_or(|file| *file == mutation.source_file.tree_relative_slashes())
+            && self
+                .replacement
+                .as_ref()
+                .is_none_or(|replacement| replacement == mutation.replacement_text())
+    }
+}
+
+impl IgnoreList {
+    /// Read the ignore list from a source tree, or return an empty list if there is none.
+    pub fn read_tree(tree_root: &Path) -> Result<IgnoreList> {
+        let path = tree_root.join(IGNORE_LIST_NAME);
+        if path.is_file() {
+            IgnoreList::read_file(&path)
+        } else {
+            Ok(IgnoreList::default())
+        }
+    }
+
+    /// Read an ignore list from a given path.
+    pub fn read_file(path: &Path) -> Result<IgnoreList> {
+        let toml =
+            fs::read_to_string(path).with_context(|| format!("read {}", path.to_slash_lossy()))?;
+        let list: IgnoreList =
+            toml::from_str(&toml).with_context(|| format!("parse {}", path.to_slash_lossy()))?;
+        if list
+            .ignore
+            .iter()
+            .any(|entry| entry.id.is_none() && entry.function.is_none())
+        {
+            return Err(anyhow!(
+                "{}: every entry needs an id or a function",
+                path.to_slash_lossy()
+            ));
+        }
+        Ok(list)
+    }
+
+    /// True if the mutation matches any entry in the list.
+    pub fn is_ignored(&self, mutation: &Mutation) -> bool {
+        self.ignore.iter().any(|entry| entry.matches(mutation))
+    }
+
+    /// Split mutations into those that should be tested, and those that are ignored.
+    pub fn partition(&self, mutations: Vec<Mutation>) -> (Vec<Mutation>, Vec<Mutation>) {
+        mutations
+            .into_iter()
+            .partition(|mutation| !self.is_ignored(mutation))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::operators::Operators;
+    use crate::source::SourceFile;
+
+    #[test]
+    fn ignored_mutants_match_function_and_optional_fields() {
+        let mutations = SourceFile::new(
+            Path::new("testdata/tree/well_tested"),
+            Path::new("src/simple_fns.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        let list: IgnoreList = toml::from_str(
+            r#"
+            [[ignore]]
+            function = "returns_unit"
+
+            [[ignore]]
+            function = "divisible_by_three"
+            op = "false"
+            reason = "not interesting"
+
+            [[ignore]]
+            function = "double_string"
+            file = "src/other.rs"
+            "#,
+        )
+        .unwrap();
+        let (tested, ignored) = list.partition(mutations);
+        assert_eq!(
+            ignored.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace returns_unit with () in src/simple_fns.rs:7",
+                "delete *a += 1; in returns_unit in src/simple_fns.rs:8",
+                "replace += with -= in returns_unit in src/simple_fns.rs:8",
+                "replace += with *= in returns_unit in src/simple_fns.rs:8",
+                "replace divisible_by_three with false in src/simple_fns.rs:17",
+            ]
+        );
+        assert!(tested.iter().any(|m| m
+            .to_string()
+            .starts_with("replace divisible_by_three with true")));
+        assert!(tested.iter().any(|m| m.function_name() == "double_string"));
+    }
+
+    #[test]
+    fn ignored_mutants_match_ids() {
+        let mutations = SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        let list = IgnoreList {
+            ignore: vec![IgnoreEntry {
+                id: Some(mutations[3].id().to_owned()),
+                function: None,
+                op: None,
+                file: None,
+                replacement: None,
+                reason: None,
+            }],
+        };
+        let (tested, ignored) = list.partition(mutations);
+        assert_eq!(tested.len(), 8);
+        assert_eq!(tested[0].function_name(), "main");
+        assert_eq!(
+            tested[6].describe_change(),
+            "replace *= with /= in factorial"
+        );
+        assert_eq!(ignored.len(), 1);
+        assert_eq!(ignored[0].function_name(), "factorial");
+    }
+
+    #[test]
+    fn unknown_fields_are_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(IGNORE_LIST_NAME);
+        fs::write(&path, "[[ignore]]\nfunction = \"a\"\nwibble = 1\n").unwrap();
+        assert!(IgnoreList::read_file(&path).is_err());
+        fs::write(&path, "[[ignore]]\nop = \"unit\"\n").unwrap();
+        assert!(IgnoreList::read_file(&path)
+            .unwrap_err()
+            .to_string()
+            .ends_with("every entry needs an id or a function"));
+        assert_eq!(
+            IgnoreList::read_tree(Path::new("testdata/tree/factorial")).unwrap(),
+            IgnoreList::default()
+        );
+    }
+}
@@ -3,21 +3,30 @@
 //! Successively apply mutations to the source code and run cargo to check, build, and test them.
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::env;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, Result};
 use path_slash::PathExt;
-use tempfile::TempDir;
+use serde::Serialize;
 
-use crate::console::{self, Activity, Console};
+use crate::cargo_config::CargoConfig;
+use crate::clean::{create_scratch_dir, extended_length_path};
+use crate::console::Theme;
+use crate::copy_tree::{copy_tree, CopyOptions};
 use crate::exit_code;
-use crate::mutate::Mutation;
+use crate::ignore_list::IgnoreList;
+use crate::manifest::fix_manifests;
+use crate::mutate::{cap_per_function, Mutation};
+use crate::observer::{Observer, Observers, Phase, Scenario};
 use crate::output::{LogFile, OutputDir};
+use crate::plan::Plan;
+use crate::report::{JsonReporter, ListFilesReporter};
 use crate::run::{run_cargo, CargoResult};
-use crate::source::SourceTree;
+use crate::source::{DiscoveredFiles, DiscoveryOptions, SourceTree, Target};
+use crate::verdict::VerdictCommand;
 
 /// Text inserted in log files to make important sections more visible.
 pub const LOG_MARKER: &str = "***";
@@ -27,50 +36,206 @@ pub const LOG_MARKER: &str = "***";
 pub struct ExperimentOptions {
     /// Don't run the tests, just see if each mutant builds.
     pub check_only: bool,
+
+    /// How to copy the source tree to the scratch directory.
+    pub copy: CopyOptions,
+
+    /// Which source files and functions to mutate.
+    pub discovery: DiscoveryOptions,
+
+    /// Pass `--offline` to cargo, so that it never accesses the network.
+    pub offline: bool,
+
+    /// Pass `--locked` to cargo, so that it fails rather than updating `Cargo.lock`.
+    pub locked: bool,
+
+    /// Pass `--frozen` to cargo: both `--offline` and `--locked`.
+    pub frozen: bool,
+
+    /// Sets of cargo arguments selecting features, such as `--no-default-features`, to test
+    /// each mutant under.
+    ///
+    /// A mutant is caught if the tests catch it under any of them. If there are none, only the
+    /// default features are tested.
+    pub feature_sets: Vec<Vec<String>>,
+
+    /// Stop running the tests for any scenario after this long, and count it as a timeout.
+    pub test_timeout: Option<Duration>,
+
+    /// Test only the mutants in this plan, rather than all those discovered.
+    pub plan: Option<Plan>,
+
+    /// Mutants that shouldn't be tested, because they're known to be equivalent or acceptable.
+    pub ignore_list: IgnoreList,
+
+    /// A command to review, and perhaps reclassify, the outcome of each mutant.
+    pub verdict_command: Option<VerdictCommand>,
+
+    /// Test at most this many mutants of each function.
+    pub max_mutants_per_function: Option<usize>,
+
+    /// Seeds the choice of mutants when there are more than `max_mutants_per_function`.
+    pub seed: u64,
+
+    /// A command and its arguments, such as `delta`, to format diffs shown on a terminal.
+    pub diff_command: Option<Vec<String>>,
+
+    /// The characters to draw progress bars on the console.
+    pub theme: Theme,
+}
+
+impl ExperimentOptions {
+    /// Return the full arguments for a cargo command, with any options that should be
+    /// passed to every cargo invocation, and the arguments selecting features, inserted after
+    /// the subcommand.
+    fn cargo_args<'a>(&self, args: &[&'a str], features: &'a [String]) -> Vec<&'a str> {
+        let (subcommand, rest) = args.split_first().expect("cargo subcommand");
+        let mut full_args = vec![*subcommand];
+        for (enabled, flag) in [
+            (self.offline, "--offline"),
+            (self.locked, "--locked"),
+            (self.frozen, "--frozen"),
+        ] {
+            if enabled {
+                full_args.push(flag);
+            }
+        }
+        full_args.extend(features.iter().map(String::as_str));
+        full_args.extend(rest);
+        full_args
+    }
+
+    /// Return the mutants to test: those in the plan if there is one, or otherwise all those
+    /// discovered, up to the limit for each function.
+    pub fn mutations(&self, discovered: &DiscoveredFiles) -> Result<Vec<Mutation>> {
+        self.select_mutations(discovered.mutations()?)
+    }
+
+    /// Choose which of the discovered mutants to test, according to the plan or the limit for
+    /// each function.
+    pub fn select_mutations(&self, mutations: Vec<Mutation>) -> Result<Vec<Mutation>> {
+        match (&self.plan, self.max_mutants_per_function) {
+            (Some(plan), _) => Ok(plan
+                .select(mutations)?
+                .into_iter()
+                .map(|(_id, mutation)| mutation)
+                .collect()),
+            (None, Some(max)) => Ok(cap_per_function(mutations, max, self.seed)),
+            (None, None) => Ok(mutations),
+        }
+    }
+
+    /// Return the feature arguments for each feature set to test under.
+    fn feature_sets(&self) -> Vec<&[String]> {
+        if self.feature_sets.is_empty() {
+            vec![&[]]
+        } else {
+            self.feature_sets.iter().map(Vec::as_slice).collect()
+        }
+    }
 }
 
 /// Run all possible mutation experiments.
 ///
 /// Before testing the mutations, the lab checks that the source tree passes its tests with no
 /// mutations applied.
+///
+/// Progress and results are reported to `observer`, and also written into `mutants.out`.
 pub fn experiment(
     source_tree: &SourceTree,
     options: &ExperimentOptions,
-    console: &Console,
+    observer: &mut dyn Observer,
 ) -> Result<LabOutcome> {
-    let mut lab_outcome = LabOutcome::default();
-    let output_dir = OutputDir::new(source_tree.root())?;
-    build_source_tree(source_tree, &output_dir, options, console)?;
+    with_reporters(source_tree, observer, |output_dir, observer| {
+        run_experiment(source_tree, options, output_dir, observer)
+    })
+}
 
-    let tmp_dir = TempDir::new()?;
-    let build_dir = copy_source_to_scratch(source_tree, tmp_dir.path(), console)?;
+/// Create `mutants.out`, and run `func` with an observer that reports both to `observer` and
+/// into files in the output directory.
+pub(crate) fn with_reporters<F>(
+    source_tree: &SourceTree,
+    observer: &mut dyn Observer,
+    func: F,
+) -> Result<LabOutcome>
+where
+    F: FnOnce(&OutputDir, &mut dyn Observer) -> Result<LabOutcome>,
+{
+    let output_dir = OutputDir::new(source_tree.root())?;
+    let mut json_reporter = JsonReporter::new(&output_dir);
+    let mut list_files_reporter = ListFilesReporter::new(&output_dir);
+    let mut observers = Observers::new();
+    observers.add(observer);
+    observers.add(&mut json_reporter);
+    observers.add(&mut list_files_reporter);
+    let lab_outcome = func(&output_dir, &mut observers)?;
+    observers.run_finished(&lab_outcome);
+    Ok(lab_outcome)
+}
 
-    let clean_outcome = test_clean(&build_dir, &output_dir, options, console)?;
+fn run_experiment(
+    source_tree: &SourceTree,
+    options: &ExperimentOptions,
+    output_dir: &OutputDir,
+    observer: &mut dyn Observer,
+) -> Result<LabOutcome> {
+    let mut lab_outcome = LabOutcome::default();
+    build_source_tree(source_tree, output_dir, options, observer)?;
+
+    let tmp_dir = create_scratch_dir(source_tree.root())?;
+    let build_dir = copy_source_to_scratch(source_tree, tmp_dir.path(), &options.copy, observer)?;
+
+    let discovered = source_tree.discover_files(&options.discovery);
+    let clean_outcome = test_clean(
+        &build_dir,
+        output_dir,
+        options,
+        &baseline_test_args(&discovered),
+        observer,
+    )?;
     lab_outcome.add(&clean_outcome);
     if !clean_outcome.status.passed() {
-        console::print_error("tests failed in a clean copy of the tree, so no mutants were tested");
         return Ok(lab_outcome); // TODO: Maybe should be Err?
     }
 
-    let mutations = source_tree.mutations()?;
-    serde_json::to_writer_pretty(
-        BufWriter::new(File::create(output_dir.path().join("mutants.json"))?),
-        &mutations,
-    )?;
-    for mutation in mutations {
+    let (mutations, ignored) = options
+        .ignore_list
+        .partition(options.mutations(&discovered)?);
+    observer.mutants_discovered(&mutations);
+    if !ignored.is_empty() {
+        observer.mutants_ignored(&ignored);
+    }
+    // They're still tested, in case they fail to build, but can't be caught.
+    let uncatchable: Vec<Mutation> = mutations
+        .iter()
+        .filter(|mutation| !discovered.can_be_caught(&mutation.source_file.target))
+        .cloned()
+        .collect();
+    if !uncatchable.is_empty() {
+        for bin_name in &discovered.bins_without_unit_tests {
+            if uncatchable
+                .iter()
+                .any(|mutation| mutation.source_file.target == Target::Bin(bin_name.clone()))
+            {
+                observer.warning(&format!(
+                    "binary {} has no tests of its own and there are no integration tests, so mutants in it can't be caught",
+                    bin_name
+                ));
+            }
+        }
+        observer.mutants_uncatchable(&uncatchable);
+    }
+    for mutation in &mutations {
+        let test_args = cargo_test_args(&mutation.source_file.target, &discovered);
         lab_outcome.add(&test_mutation(
-            &mutation,
-            &build_dir,
-            &output_dir,
-            options,
-            console,
+            mutation, &build_dir, output_dir, options, &test_args, observer,
         )?);
     }
     Ok(lab_outcome)
 }
 
 /// The bottom line of trying a mutation: it was caught, missed, failed to build, etc.
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
 #[must_use]
 pub enum Status {
     // TODO: Maybe these would be better as an Error type and in the Err branch of a Result?
@@ -93,6 +258,11 @@ pub enum Status {
     SourceBuildFailed,
     /// Build passed in the original source tree.
     SourceBuildPassed,
+    /// The verdict command decided the mutant should be ignored.
+    Ignored,
+    /// The mutated code didn't parse, so it wasn't built or tested. This is a bug in
+    /// cargo-mutants or a plugin, not a problem with the code being mutated.
+    BadPatch,
 }
 
 impl Status {
@@ -126,16 +296,16 @@ impl Status {
     /// failed that should not have.
     pub fn should_show_logs(&self) -> bool {
         use Status::*;
-        matches!(self, CleanTestFailed | SourceBuildFailed)
+        matches!(self, CleanTestFailed | SourceBuildFailed | BadPatch)
     }
 
     /// True if the scenario succeeded.
     pub fn passed(&self) -> bool {
         use Status::*;
         match self {
-            MutantCaught | CheckPassed | CleanTestPassed | SourceBuildPassed => true,
+            MutantCaught | CheckPassed | CleanTestPassed | SourceBuildPassed | Ignored => true,
             MutantMissed | CheckFailed | CleanTestFailed | SourceBuildFailed | Timeout
-            | BuildFailed => false,
+            | BuildFailed | BadPatch => false,
         }
     }
 }
@@ -168,6 +338,8 @@ impl LabOutcome {
         use Status::*;
         if self.count(CleanTestFailed) > 0 || self.count(SourceBuildFailed) > 4 {
             exit_code::CLEAN_TESTS_FAILED
+        } else if self.count(BadPatch) > 0 {
+            exit_code::SOFTWARE
         } else if self.count(Timeout) > 0 {
             exit_code::TIMEOUT
         } else if self.count(MutantMissed) > 0 {
@@ -187,44 +359,59 @@ fn build_source_tree(
     source_tree: &SourceTree,
     output_dir: &OutputDir,
     options: &ExperimentOptions,
-    console: &Console,
+    observer: &mut dyn Observer,
 ) -> Result<()> {
+    let scenario = Scenario::SourceTree {
+        check_only: options.check_only,
+    };
     let scenario_name = if options.check_only {
         "check source tree"
     } else {
         "build source tree"
     };
-    let mut activity = console.start_activity(scenario_name);
     let (mut out_file, log_file) = output_dir.create_log(scenario_name)?;
     writeln!(out_file, "{} {}", LOG_MARKER, scenario_name)?;
+    observer.scenario_started(&scenario, &log_file);
     let start = Instant::now();
 
-    activity.set_phase("check");
-    let test_result = run_cargo(
-        &["check", "--tests"],
+    let test_result = run_phase(
+        &scenario,
+        Phase::Check,
+        &options.cargo_args(&["check", "--tests"], options.feature_sets()[0]),
         source_tree.root(),
-        &mut activity,
+        &[],
+        None,
+        observer,
         &log_file,
     )?;
     if !test_result.success() {
-        activity.outcome(&Outcome::new(&log_file, &start, Status::SourceBuildFailed))?;
+        observer.scenario_finished(
+            &scenario,
+            &Outcome::new(&log_file, &start, Status::SourceBuildFailed),
+        );
         return Err(anyhow!("check failed in source tree, not continuing"));
     }
     if options.check_only {
-        activity.outcome(&Outcome::new(&log_file, &start, Status::CheckPassed))?;
+        observer.scenario_finished(
+            &scenario,
+            &Outcome::new(&log_file, &start, Status::CheckPassed),
+        );
         return Ok(());
     }
 
-    activity.set_phase("build");
-    let test_result = run_cargo(
-        &["build", "--tests"],
+    let test_result = run_phase(
+        &scenario,
+        Phase::Build,
+        &options.cargo_args(&["build", "--tests"], options.feature_sets()[0]),
         source_tree.root(),
-        &mut activity,
+        &[],
+        None,
+        observer,
         &log_file,
     )?;
     let status = Status::from_source_build(&test_result);
     let outcome = Outcome::new(&log_file, &start, status);
-    activity.outcome(&outcome)?;
+    observer.scenario_finished(&scenario, &outcome);
     if test_result.success() {
         Ok(())
     } else {
@@ -237,37 +424,69 @@ fn build_source_tree(
 /// If there are already-failing tests, proceeding to test mutations
 /// won't give a clear signal.
 fn test_clean(
-    build_dir: &Path,
+    build_dir: &BuildDir,
     output_dir: &OutputDir,
     options: &ExperimentOptions,
-    console: &Console,
+    test_args: &[&str],
+    observer: &mut dyn Observer,
 ) -> Result<Outcome> {
-    let mut activity = console.start_activity("baseline test with no mutations");
+    let scenario = Scenario::Baseline;
     let scenario_name = "baseline";
     let (mut out_file, log_file) = output_dir.create_log(scenario_name)?;
     writeln!(out_file, "{} {}", LOG_MARKER, scenario_name)?;
-    let outcome = run_scenario(build_dir, &mut activity, &log_file, options, true)?;
-    activity.outcome(&outcome)?;
+    observer.scenario_started(&scenario, &log_file);
+    let outcome = run_scenario(
+        &scenario, build_dir, observer, &log_file, options, test_args,
+    )?;
+    observer.scenario_finished(&scenario, &outcome);
     Ok(outcome)
 }
 
 /// Test with one mutation applied.
 fn test_mutation(
     mutation: &Mutation,
-    build_dir: &Path,
+    build_dir: &BuildDir,
     output_dir: &OutputDir,
     options: &ExperimentOptions,
-    console: &Console,
+    test_args: &[&str],
+    observer: &mut dyn Observer,
 ) -> Result<Outcome> {
-    let mut activity = console.start_mutation(mutation);
+    let scenario = Scenario::Mutant(mutation);
     let scenario_name = mutation.to_string();
     let (mut out_file, log_file) = output_dir.create_log(&scenario_name)?;
     writeln!(out_file, "{} {}", LOG_MARKER, scenario_name)?;
+    writeln!(out_file, "{} mutant id {}", LOG_MARKER, mutation.id())?;
     writeln!(out_file, "{}", mutation.diff())?;
-    let outcome = mutation.with_mutation_applied(build_dir, || {
-        run_scenario(build_dir, &mut activity, &log_file, options, false)
+    observer.scenario_started(&scenario, &log_file);
+    if let Some(syntax_error) = mutation.syntax_error() {
+        // Building it would only fail, and look like the mutant is unviable; instead keep the
+        // text that was generated, for a bug report.
+        let start = Instant::now();
+        writeln!(
+            out_file,
+            "{} bad patch: mutated code doesn't parse: {}",
+            LOG_MARKER, syntax_error
+        )?;
+        writeln!(
+            out_file,
+            "{} mutated {}:\n{}",
+            LOG_MARKER,
+            mutation.source_file.tree_relative_slashes(),
+            mutation.mutated_code()
+        )?;
+        let outcome = Outcome::new(&log_file, &start, Status::BadPatch);
+        observer.scenario_finished(&scenario, &outcome);
+        return Ok(outcome);
+    }
+    let mut outcome = mutation.with_mutation_applied(&build_dir.path, || {
+        run_scenario(
+            &scenario, build_dir, observer, &log_file, options, test_args,
+        )
     })?;
-    activity.outcome(&outcome)?;
+    if let Some(verdict_command) = &options.verdict_command {
+        outcome.status = verdict_command.review(mutation, &outcome)?;
+    }
+    observer.scenario_finished(&scenario, &outcome);
     Ok(outcome)
 }
 
@@ -292,63 +511,189 @@ impl Outcome {
     }
 }
 
-/// Successively run cargo check, build, test, and return the overall outcome.
+/// Run the scenario under each feature set, and return the overall outcome.
+///
+/// A mutant is caught if it's caught under any feature set, so once it's caught the remaining
+/// feature sets are skipped. The clean tree must pass under every feature set.
+///
+/// `test_args` are the arguments to `cargo` to run the tests.
 fn run_scenario(
-    build_dir: &Path,
-    activity: &mut Activity,
+    scenario: &Scenario,
+    build_dir: &BuildDir,
+    observer: &mut dyn Observer,
     log_file: &LogFile,
     options: &ExperimentOptions,
-    is_clean: bool,
+    test_args: &[&str],
 ) -> Result<Outcome> {
+    let is_clean = matches!(scenario, Scenario::Baseline);
+    let start = Instant::now();
+    let mut statuses = Vec::new();
+    for features in options.feature_sets() {
+        if !features.is_empty() {
+            writeln!(
+                log_file.open_append()?,
+                "\n{} features: {}",
+                LOG_MARKER,
+                features.join(" ")
+            )?;
+        }
+        let status = run_with_features(
+            scenario, build_dir, observer, log_file, options, test_args, features,
+        )?;
+        statuses.push(status);
+        if status == Status::MutantCaught || (is_clean && !status.passed()) {
+            break;
+        }
+    }
+    Ok(Outcome::new(log_file, &start, combine_statuses(&statuses)))
+}
+
+/// Successively run cargo check, build, test with one set of feature arguments, and return
+/// the status.
+fn run_with_features(
+    scenario: &Scenario,
+    build_dir: &BuildDir,
+    observer: &mut dyn Observer,
+    log_file: &LogFile,
+    options: &ExperimentOptions,
+    test_args: &[&str],
+    features: &[String],
+) -> Result<Status> {
     // TODO: Maybe separate launching and collecting the result, so
     // that we can run several in parallel.
 
-    let start = Instant::now();
-
-    activity.set_phase("check");
-    if !run_cargo(&["check"], build_dir, activity, log_file)?.success() {
-        return Ok(Outcome::new(log_file, &start, Status::CheckFailed));
+    if !build_dir
+        .run_phase(
+            scenario,
+            Phase::Check,
+            &options.cargo_args(&["check"], features),
+            None,
+            observer,
+            log_file,
+        )?
+        .success()
+    {
+        return Ok(Status::CheckFailed);
     }
     if options.check_only {
-        return Ok(Outcome::new(log_file, &start, Status::CheckPassed));
+        return Ok(Status::CheckPassed);
     }
 
-    activity.set_phase("build");
-    if !run_cargo(&["build", "--tests"], build_dir, activity, log_file)?.success() {
-        return Ok(Outcome::new(log_file, &start, Status::BuildFailed));
+    if !build_dir
+        .run_phase(
+            scenario,
+            Phase::Build,
+            &options.cargo_args(&["build", "--tests"], features),
+            None,
+            observer,
+            log_file,
+        )?
+        .success()
+    {
+        return Ok(Status::BuildFailed);
     }
 
-    activity.set_phase("test");
-    let test_result = run_cargo(&["test"], build_dir, activity, log_file)?;
-    let status = if is_clean {
+    let test_result = build_dir.run_phase(
+        scenario,
+        Phase::Test,
+        &options.cargo_args(test_args, features),
+        scenario
+            .mutation()
+            .and_then(Mutation::timeout)
+            .or(options.test_timeout),
+        observer,
+        log_file,
+    )?;
+    Ok(if matches!(scenario, Scenario::Baseline) {
         Status::from_clean_test(&test_result)
     } else {
         Status::from_mutant_test(&test_result)
-    };
+    })
+}
 
-    Ok(Outcome::new(log_file, &start, status))
+/// Combine the statuses from running a scenario under several feature sets into one.
+///
+/// A mutant is caught if it was caught under any of them; otherwise, and for the clean tree,
+/// the most significant problem is reported.
+fn combine_statuses(statuses: &[Status]) -> Status {
+    use Status::*;
+    [
+        CleanTestFailed,
+        MutantCaught,
+        Timeout,
+        MutantMissed,
+        BuildFailed,
+        CheckFailed,
+        CleanTestPassed,
+        CheckPassed,
+    ]
+    .iter()
+    .copied()
+    .find(|status| statuses.contains(status))
+    .expect("a known status from at least one feature set")
+}
+
+/// Return the cargo arguments to run the tests that could catch a mutant in a given target.
+///
+/// Mutants in a binary can only be caught by that binary's own unit tests, or by integration
+/// tests that run it, so there's no need to run the tests of the library or of other binaries.
+///
+/// A proc macro is mostly exercised by the tests of the crates that use it, which may be other
+/// packages in the workspace, so the tests of the whole workspace are run.
+///
+/// For any other target, all the tests of the package are run.
+fn cargo_test_args<'a>(target: &'a Target, discovered: &DiscoveredFiles) -> Vec<&'a str> {
+    match target {
+        Target::ProcMacro => vec!["test", "--workspace"],
+        Target::Bin(name) => {
+            let mut args = vec!["test", "--bin", name.as_str()];
+            if discovered.has_integration_tests {
+                args.extend(["--test", "*"]);
+            }
+            args
+        }
+        _ => vec!["test"],
+    }
+}
+
+/// Return the cargo arguments to run the baseline tests: all the tests that will be run for
+/// any mutant.
+fn baseline_test_args(discovered: &DiscoveredFiles) -> Vec<&'static str> {
+    if discovered
+        .files
+        .iter()
+        .any(|file| file.target == Target::ProcMacro)
+    {
+        vec!["test", "--workspace"]
+    } else {
+        vec!["test"]
+    }
 }
 
 fn copy_source_to_scratch(
     source: &SourceTree,
     tmp_path: &Path,
-    console: &Console,
-) -> Result<PathBuf> {
+    copy_options: &CopyOptions,
+    observer: &mut dyn Observer,
+) -> Result<BuildDir> {
     let build_dir = tmp_path.join("build");
-    let mut activity =
-        console.start_copy_activity("copy source and build products to scratch directory");
+    let cargo_config = CargoConfig::read(source.root(), |var| env::var_os(var))?;
+    let copy_options = CopyOptions {
+        target_dir: cargo_config.tree_target_dir(source.root()),
+        ..copy_options.clone()
+    };
+    observer.copy_started();
     // I thought we could skip copying /target here, but it turns out that copying
-    // it does speed up the first build.
-    match cp_r::CopyOptions::new()
-        .after_entry_copied(|_path, _ft, stats| {
-            activity.bytes_copied(stats.file_bytes);
-        })
-        .copy_tree(source.root(), &build_dir)
-        .context("copy source tree to lab directory")
-    {
-        Ok(stats) => activity.succeed(stats.file_bytes),
+    // it does speed up the first build. Files deep within it can have long paths.
+    match copy_tree(
+        source.root(),
+        &extended_length_path(&build_dir),
+        &copy_options,
+        |bytes| observer.copy_progress(bytes),
+    ) {
+        Ok(total_bytes) => observer.copy_finished(Some(total_bytes)),
         Err(err) => {
-            activity.fail();
+            observer.copy_finished(None);
             eprintln!(
                 "error copying source tree {} to {}: {:?}",
                 &source.root().to_slash_lossy(),
@@ -358,5 +703,142 @@ fn copy_source_to_scratch(
             return Err(err);
         }
     }
-    Ok(build_dir)
+    fix_manifests(&build_dir, source.root())?;
+    cargo_config.write_scratch_patch(source.root(), &build_dir)?;
+    Ok(BuildDir {
+        cargo_env: cargo_config.scratch_env(source.root(), &build_dir),
+        path: build_dir,
+    })
+}
+
+/// A scratch copy of the source tree, where mutants are built and tested.
+struct BuildDir {
+    path: PathBuf,
+
+    /// Environment variables to set for cargo in this directory.
+    cargo_env: Vec<(String, String)>,
+}
+
+impl BuildDir {
+    fn run_phase(
+        &self,
+        scenario: &Scenario,
+        phase: Phase,
+        cargo_args: &[&str],
+        timeout: Option<Duration>,
+        observer: &mut dyn Observer,
+        log_file: &LogFile,
+    ) -> Result<CargoResult> {
+        run_phase(
+            scenario,
+            phase,
+            cargo_args,
+            &self.path,
+            &self.cargo_env,
+            timeout,
+            observer,
+            log_file,
+        )
+    }
+}
+
+/// Run one phase of a scenario, telling the observer when it starts and finishes.
+#[allow(clippy::too_many_arguments)]
+fn run_phase(
+    scenario: &Scenario,
+    phase: Phase,
+    cargo_args: &[&str],
+    in_dir: &Path,
+    cargo_env: &[(String, String)],
+    timeout: Option<Duration>,
+    observer: &mut dyn Observer,
+    log_file: &LogFile,
+) -> Result<CargoResult> {
+    let start = Instant::now();
+    observer.phase_started(scenario, phase);
+    let result = run_cargo(cargo_args, in_dir, cargo_env, timeout, observer, log_file)?;
+    observer.phase_finished(scenario, phase, &result, start.elapsed());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn cargo_args_pass_through_offline_and_locked() {
+        let options = ExperimentOptions::default();
+        assert_eq!(
+            options.cargo_args(&["test", "--bin", "foo"], &[]),
+            ["test", "--bin", "foo"]
+        );
+        let options = ExperimentOptions {
+            offline: true,
+            locked: true,
+            ..ExperimentOptions::default()
+        };
+        assert_eq!(
+            options.cargo_args(&["test", "--bin", "foo"], &[]),
+            ["test", "--offline", "--locked", "--bin", "foo"]
+        );
+        let options = ExperimentOptions {
+            frozen: true,
+            ..ExperimentOptions::default()
+        };
+        assert_eq!(options.cargo_args(&["check"], &[]), ["check", "--frozen"]);
+    }
+
+    #[test]
+    fn feature_args_follow_subcommand() {
+        let options = ExperimentOptions {
+            offline: true,
+            feature_sets: vec![vec![], vec!["--features".to_owned(), "foo".to_owned()]],
+            ..ExperimentOptions::default()
+        };
+        let feature_sets = options.feature_sets();
+        assert_eq!(feature_sets.len(), 2);
+        assert_eq!(
+            options.cargo_args(&["test", "--lib"], feature_sets[1]),
+            ["test", "--offline", "--features", "foo", "--lib"]
+        );
+        assert_eq!(
+            ExperimentOptions::default().feature_sets(),
+            [&[] as &[String]]
+        );
+    }
+
+    #[test]
+    fn mutant_is_caught_if_caught_under_any_feature_set() {
+        use Status::*;
+        assert_eq!(
+            combine_statuses(&[MutantMissed, MutantCaught]),
+            MutantCaught
+        );
+        assert_eq!(combine_statuses(&[BuildFailed, MutantMissed]), MutantMissed);
+        assert_eq!(combine_statuses(&[MutantMissed, Timeout]), Timeout);
+        assert_eq!(combine_statuses(&[CheckPassed, CheckFailed]), CheckFailed);
+        assert_eq!(
+            combine_statuses(&[CleanTestPassed, CleanTestFailed]),
+            CleanTestFailed
+        );
+        assert_eq!(combine_statuses(&[CleanTestPassed]), CleanTestPassed);
+    }
+
+    #[test]
+    fn bad_patches_give_a_software_error() {
+        use Status::*;
+        let log_file = LogFile {
+            path: PathBuf::from("mutants.out/log/bad_patch.log"),
+        };
+        let start = Instant::now();
+        let mut lab_outcome = LabOutcome::default();
+        lab_outcome.add(&Outcome::new(&log_file, &start, MutantCaught));
+        assert_eq!(lab_outcome.exit_code(), exit_code::SUCCESS);
+        lab_outcome.add(&Outcome::new(&log_file, &start, BadPatch));
+        assert_eq!(lab_outcome.exit_code(), exit_code::SOFTWARE);
+        lab_outcome.add(&Outcome::new(&log_file, &start, MutantMissed));
+        assert_eq!(lab_outcome.exit_code(), exit_code::SOFTWARE);
+    }
 }
@@ -0,0 +1,33 @@
+// Copyright 2021, 2022 Martin Pool
+
+//! `cargo-mutants`: Find inadequately-tested code that can be removed without any tests failing.
+//!
+//! This library is the implementation of the `cargo mutants` command. It can also be used to
+//! run mutation tests from other programs: build the options with
+//! [experiment::Experiment::builder], then run the tests, reporting progress and results to an
+//! [observer::Observer], which can be implemented to send them anywhere.
+
+mod cargo_config;
+pub mod clean;
+pub mod config;
+pub mod console;
+pub mod copy_tree;
+pub mod exit_code;
+pub mod experiment;
+pub mod external;
+pub mod ignore_list;
+pub mod lab;
+mod manifest;
+pub mod mutate;
+pub mod observer;
+pub mod operators;
+pub mod output;
+pub mod plan;
+mod report;
+pub mod run;
+pub mod source;
+#[cfg(test)]
+mod test_util;
+pub mod textedit;
+pub mod verdict;
+mod visit;
@@ -2,27 +2,20 @@
 
 //! `cargo-mutants`: Find inadequately-tested code that can be removed without any tests failing.
 
-mod console;
-mod exit_code;
-mod lab;
-mod mutate;
-mod output;
-mod run;
-mod source;
-mod textedit;
-mod visit;
-
 use std::env;
 use std::io;
 use std::path::PathBuf;
 use std::process::exit;
+use std::time::Duration;
 
 use anyhow::Result;
 use argh::FromArgs;
-#[allow(unused)]
 use path_slash::PathExt;
 
-use source::SourceTree;
+use cargo_mutants::experiment::Experiment;
+use cargo_mutants::plan::Plan;
+use cargo_mutants::source::{SourceTree, TargetSelection};
+use cargo_mutants::{clean, console, exit_code, external};
 
 /// Find inadequately-tested code that can be removed without any tests failing.
 #[derive(FromArgs, PartialEq, Debug)]
@@ -35,7 +28,15 @@ struct Args {
     #[argh(switch)]
     list: bool,
 
-    /// output json (only for --list).
+    /// just list the source files that would be mutated, and any that are excluded.
+    #[argh(switch)]
+    list_files: bool,
+
+    /// just list the functions, items, and files that are skipped, and why.
+    #[argh(switch)]
+    list_skipped: bool,
+
+    /// output json (only for --list, --list-files, and --list-skipped).
     #[argh(switch)]
     json: bool,
 
@@ -43,10 +44,57 @@ struct Args {
     #[argh(switch)]
     diff: bool,
 
+    /// format diffs on a terminal by piping them through this command, quoted as one argument,
+    /// such as "delta --side-by-side".
+    #[argh(option)]
+    diff_command: Option<String>,
+
+    /// list every mutant, including those that were caught; with --list, also list the
+    /// functions and files that are skipped, and why, except in json.
+    #[argh(switch, short = 'v')]
+    verbose: bool,
+
+    /// print only mutants that were missed, timed out, or failed to build, and a summary.
+    #[argh(switch, short = 'q')]
+    quiet: bool,
+
+    /// group results by source file, with a subtotal for each, and print them at the end.
+    #[argh(switch)]
+    group_by_file: bool,
+
+    /// draw progress bars using only ASCII characters.
+    #[argh(switch)]
+    ascii: bool,
+
+    /// don't link result lines to source files and logs on a terminal.
+    #[argh(switch)]
+    no_hyperlinks: bool,
+
     /// show cargo output for all invocations (very verbose).
     #[argh(switch)]
     all_logs: bool,
 
+    /// print this many lines from the end of the log of each mutant that's not caught.
+    #[argh(option, default = "0")]
+    missed_log_lines: usize,
+
+    /// write the mutants that would be tested into this json file, rather than testing them.
+    #[argh(option)]
+    emit_plan: Option<PathBuf>,
+
+    /// test only the mutants in this json file, written earlier by --emit-plan.
+    #[argh(option)]
+    plan: Option<PathBuf>,
+
+    /// write the mutants, and a patch for each, into this directory to be tested by another
+    /// system, rather than testing them.
+    #[argh(option)]
+    export_mutants: Option<PathBuf>,
+
+    /// report the results of testing exported mutants, from results.json in this directory.
+    #[argh(option)]
+    import_results: Option<PathBuf>,
+
     /// cargo check generated mutants, but don't run tests.
     #[argh(switch)]
     check: bool,
@@ -54,8 +102,123 @@ struct Args {
     /// don't print times or tree sizes, to make output deterministic.
     #[argh(switch)]
     no_times: bool,
+
+    /// skip copying files excluded by .gitignore to the scratch directory (default true).
+    #[argh(option, default = "true")]
+    gitignore: bool,
+
+    /// copy version control directories such as .git into the scratch directory.
+    #[argh(switch)]
+    copy_vcs: bool,
+
+    /// copy the targets of symlinks into the scratch directory, rather than the symlinks.
+    #[argh(switch)]
+    follow_symlinks: bool,
+
+    /// pass --offline to cargo, so that it doesn't access the network.
+    #[argh(switch)]
+    offline: bool,
+
+    /// pass --locked to cargo, so that it fails rather than updating Cargo.lock.
+    #[argh(switch)]
+    locked: bool,
+
+    /// pass --frozen to cargo: equivalent to --offline and --locked.
+    #[argh(switch)]
+    frozen: bool,
+
+    /// test each mutant with these cargo feature arguments, quoted as one argument, such as
+    /// "--no-default-features" (can be repeated; caught if caught under any of them).
+    #[argh(option)]
+    feature_set: Vec<String>,
+
+    /// test each mutant with default features, --no-default-features, and --all-features.
+    #[argh(switch)]
+    feature_matrix: bool,
+
+    /// stop the tests for a mutant after this many seconds, and count it as a timeout.
+    #[argh(option)]
+    timeout: Option<f64>,
+
+    /// test at most this many mutants of each function, chosen to cover every mutation operator.
+    #[argh(option)]
+    max_mutants_per_function: Option<usize>,
+
+    /// seed the choice of mutants from functions with more than the maximum.
+    #[argh(option, default = "0")]
+    seed: u64,
+
+    /// only mutate the library.
+    #[argh(switch)]
+    lib: bool,
+
+    /// only mutate the named binary (can be repeated).
+    #[argh(option)]
+    bin: Vec<String>,
+
+    /// only mutate binaries.
+    #[argh(switch)]
+    bins: bool,
+
+    /// only mutate the named example (can be repeated).
+    #[argh(option)]
+    example: Vec<String>,
+
+    /// only mutate examples.
+    #[argh(switch)]
+    examples: bool,
+
+    /// don't generate mutants from the named mutation operator (can be repeated).
+    #[argh(option)]
+    skip_operator: Vec<String>,
+
+    /// run this plugin program to suggest more mutants for each function (can be repeated).
+    #[argh(option)]
+    plugin: Vec<PathBuf>,
+
+    /// also mutate code in examples/.
+    #[argh(switch)]
+    include_examples: bool,
+
+    /// also mutate code in benches/.
+    #[argh(switch)]
+    include_benches: bool,
+
+    /// also mutate non-test helper code in tests/.
+    #[argh(switch)]
+    include_test_helpers: bool,
+
+    /// also mutate unsafe functions, which are skipped by default.
+    #[argh(switch)]
+    mutate_unsafe: bool,
+
+    /// don't mutate trivial accessors, whose body only reads or assigns a field.
+    #[argh(switch)]
+    skip_trivial: bool,
+
+    /// don't mutate functions containing unsafe blocks.
+    #[argh(switch)]
+    skip_unsafe_blocks: bool,
+
+    /// mutate functions returning references to return a leaked default value.
+    #[argh(switch)]
+    leak_references: bool,
+
+    #[argh(subcommand)]
+    command: Option<Command>,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand)]
+enum Command {
+    Clean(Clean),
+}
+
+/// Remove mutants.out, mutants.out.old, and scratch directories leaked by earlier runs.
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "clean")]
+struct Clean {}
+
 fn main() -> Result<()> {
     if let Some(subcommand) = env::args().nth(1) {
         if subcommand != "mutants" {
@@ -68,14 +231,104 @@ fn main() -> Result<()> {
     }
     let args: Args = argh::cargo_from_env();
     let source_tree = SourceTree::new(&args.dir)?;
-    let console = console::Console::new()
+    if let Some(Command::Clean(_)) = args.command {
+        let removed = clean::clean(source_tree.root())?;
+        console::list_removed(&removed, !args.no_times);
+        return Ok(());
+    }
+    let mut console = console::Console::new()
         .show_all_logs(args.all_logs)
-        .show_times(!args.no_times);
-    let options = lab::ExperimentOptions {
-        check_only: args.check,
-    };
-    if args.list {
-        let mutations = source_tree.mutations()?;
+        .show_times(!args.no_times)
+        .quiet(args.quiet)
+        .verbose(args.verbose)
+        .missed_log_lines(args.missed_log_lines)
+        .group_by_file(args.group_by_file)
+        .source_root(source_tree.root());
+    if args.no_hyperlinks {
+        console = console.hyperlinks(false);
+    }
+    if args
+        .timeout
+        .is_some_and(|timeout| !(timeout > 0.0 && timeout.is_finite()))
+    {
+        eprintln!("--timeout must be a positive number of seconds");
+        exit(exit_code::USAGE);
+    }
+    let mut builder = Experiment::builder()
+        .dir(&args.dir)
+        .check_only(args.check)
+        .gitignore(args.gitignore)
+        .copy_vcs(args.copy_vcs)
+        .follow_symlinks(args.follow_symlinks)
+        .offline(args.offline)
+        .locked(args.locked)
+        .frozen(args.frozen)
+        .include_examples(args.include_examples)
+        .include_benches(args.include_benches)
+        .include_test_helpers(args.include_test_helpers)
+        .mutate_unsafe(args.mutate_unsafe)
+        .skip_trivial(args.skip_trivial)
+        .skip_unsafe_blocks(args.skip_unsafe_blocks)
+        .leak_references(args.leak_references)
+        .targets(TargetSelection {
+            lib: args.lib,
+            bins: args.bin.clone(),
+            all_bins: args.bins,
+            examples: args.example.clone(),
+            all_examples: args.examples,
+        });
+    for name in &args.skip_operator {
+        builder = builder.skip_operator(name);
+    }
+    for path in &args.plugin {
+        builder = builder.plugin(path);
+    }
+    for feature_set in feature_sets(&args) {
+        builder = builder.feature_set(feature_set);
+    }
+    if let Some(timeout) = args.timeout {
+        builder = builder.timeout(Duration::from_secs_f64(timeout));
+    }
+    if let Some(max) = args.max_mutants_per_function {
+        builder = builder.max_mutants_per_function(max);
+    }
+    builder = builder.seed(args.seed).ascii(args.ascii);
+    if let Some(diff_command) = &args.diff_command {
+        builder = builder.diff_command(diff_command.split_whitespace());
+    }
+    if let Some(plan_path) = &args.plan {
+        builder = builder.plan(Plan::read(plan_path)?);
+    }
+    let experiment = builder.build()?;
+    let options = experiment.options();
+    let mut console = console.theme(options.theme.clone());
+    if args.list_files {
+        let discovered = source_tree.discover_files(&options.discovery);
+        if args.json {
+            let files = discovered
+                .files
+                .iter()
+                .map(|source_file| source_file.tree_relative_slashes())
+                .collect::<Vec<String>>();
+            serde_json::to_writer_pretty(
+                io::BufWriter::new(io::stdout()),
+                &serde_json::json!({
+                    "files": files,
+                    "excluded": discovered.excluded,
+                }),
+            )?;
+        } else {
+            console::list_files(&discovered);
+        }
+    } else if args.list_skipped {
+        let (_mutations, skipped) = experiment.discover()?;
+        if args.json {
+            serde_json::to_writer_pretty(io::BufWriter::new(io::stdout()), &skipped)?;
+        } else {
+            console::list_skipped(&skipped);
+        }
+    } else if args.list {
+        let (mutations, skipped) = experiment.discover()?;
         if args.json {
             if args.diff {
                 eprintln!("--list --diff --json is not (yet) supported");
@@ -83,11 +336,53 @@ fn main() -> Result<()> {
             }
             serde_json::to_writer_pretty(io::BufWriter::new(io::stdout()), &mutations)?;
         } else {
-            console::list_mutations(&mutations, args.diff);
+            console::list_mutations(&mutations, args.diff, options.diff_command.as_deref())?;
+            if args.verbose {
+                console::list_skipped(&skipped);
+            }
         }
+    } else if let Some(plan_path) = &args.emit_plan {
+        let mutations = experiment.mutations()?;
+        Plan::new(&mutations).write(plan_path)?;
+        println!(
+            "Wrote {} mutants to {}",
+            mutations.len(),
+            plan_path.to_slash_lossy()
+        );
+    } else if let Some(export_dir) = &args.export_mutants {
+        let mutations = experiment.mutations()?;
+        let plan = external::export(&mutations, export_dir)?;
+        println!(
+            "Exported {} mutants to {}",
+            plan.mutants.len(),
+            export_dir.to_slash_lossy()
+        );
+    } else if let Some(import_dir) = &args.import_results {
+        let lab_outcome =
+            external::import_results(&source_tree, &options.discovery, import_dir, &mut console)?;
+        exit(lab_outcome.exit_code());
     } else {
-        let lab_outcome = lab::experiment(&source_tree, &options, &console)?;
+        let lab_outcome = experiment.run(&mut console)?;
         exit(lab_outcome.exit_code());
     }
     Ok(())
 }
+
+/// Return the sets of feature arguments to test each mutant under, from the command line.
+fn feature_sets(args: &Args) -> Vec<Vec<String>> {
+    let mut feature_sets: Vec<Vec<String>> = if args.feature_matrix {
+        vec![
+            vec![],
+            vec!["--no-default-features".to_owned()],
+            vec!["--all-features".to_owned()],
+        ]
+    } else {
+        Vec::new()
+    };
+    feature_sets.extend(
+        args.feature_set
+            .iter()
+            .map(|set| set.split_whitespace().map(str::to_owned).collect()),
+    );
+    feature_sets
+}
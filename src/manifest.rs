@@ -0,0 +1,447 @@
+// Copyright 2022 Martin Pool
+
+//! Read `Cargo.toml` manifests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+
+use crate::source::normalize_relative_path;
+
+/// Tables within a manifest, or within a `target.'cfg'` table, that list dependencies.
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Read and parse the `Cargo.toml` in a directory.
+pub fn read_manifest(dir: &Path) -> Result<toml::Value> {
+    let path = dir.join("Cargo.toml");
+    let toml = fs::read_to_string(&path)
+        .with_context(|| format!("read manifest {}", path.to_slash_lossy()))?;
+    toml.parse()
+        .with_context(|| format!("parse manifest {}", path.to_slash_lossy()))
+}
+
+/// Return the path, relative to the package directory, of the package's build script, if it
+/// has one.
+///
+/// This is `build.rs` if it exists, unless the manifest names a different file or turns off
+/// the build script with `build = false`.
+pub fn build_script_path(manifest: &toml::Value, package_dir: &Path) -> Option<PathBuf> {
+    match manifest
+        .get("package")
+        .and_then(|package| package.get("build"))
+    {
+        Some(toml::Value::String(path)) => Some(PathBuf::from(path)),
+        Some(toml::Value::Boolean(false)) => None,
+        _ => Some(PathBuf::from("build.rs")).filter(|path| package_dir.join(path).is_file()),
+    }
+}
+
+/// Return true if the package's library is a procedural macro crate.
+pub fn is_proc_macro(manifest: &toml::Value) -> bool {
+    let lib = match manifest.get("lib") {
+        Some(lib) => lib,
+        None => return false,
+    };
+    ["proc-macro", "proc_macro"]
+        .iter()
+        .any(|key| matches!(lib.get(key), Some(toml::Value::Boolean(true))))
+}
+
+/// Return the name of the binary target built from `src/main.rs`.
+///
+/// This is the name of a `[[bin]]` whose path is `src/main.rs`, or of one with no explicit path
+/// and no corresponding file in `src/bin`, or otherwise the package name.
+pub fn main_bin_name(manifest: &toml::Value, package_dir: &Path) -> Option<String> {
+    let bins = manifest
+        .get("bin")
+        .and_then(|bins| bins.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for bin in bins {
+        let name = match bin.get("name").and_then(|name| name.as_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        match bin.get("path").and_then(|path| path.as_str()) {
+            Some(path) if Path::new(path) == Path::new("src/main.rs") => {
+                return Some(name.to_owned())
+            }
+            Some(_) => (),
+            None => {
+                let bin_dir = package_dir.join("src/bin");
+                if !bin_dir.join(format!("{}.rs", name)).is_file()
+                    && !bin_dir.join(name).join("main.rs").is_file()
+                {
+                    return Some(name.to_owned());
+                }
+            }
+        }
+    }
+    package_name(manifest)
+}
+
+/// Return the name of the package, if the manifest defines one.
+pub fn package_name(manifest: &toml::Value) -> Option<String> {
+    manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str())
+        .map(str::to_owned)
+}
+
+/// Return the tree-relative directories of the members of the workspace defined by a manifest,
+/// if it has a `[workspace]` section.
+///
+/// Globs in `workspace.members` are expanded, and directories listed in `workspace.exclude` or
+/// that don't contain a `Cargo.toml` are skipped. Members outside the tree aren't returned.
+pub fn workspace_members(manifest: &toml::Value, root: &Path) -> Vec<PathBuf> {
+    let workspace = match manifest.get("workspace") {
+        Some(workspace) => workspace,
+        None => return Vec::new(),
+    };
+    let string_list = |key: &str| -> Vec<&str> {
+        workspace
+            .get(key)
+            .and_then(|list| list.as_array())
+            .map(|list| list.iter().filter_map(|item| item.as_str()).collect())
+            .unwrap_or_default()
+    };
+    let excludes: Vec<PathBuf> = string_list("exclude")
+        .into_iter()
+        .filter_map(|path| normalize_relative_path(Path::new(path)))
+        .collect();
+    let mut members = Vec::new();
+    for pattern in string_list("members") {
+        for member in expand_member_glob(root, pattern) {
+            if root.join(&member).join("Cargo.toml").is_file()
+                && !excludes.iter().any(|exclude| member.starts_with(exclude))
+                && !members.contains(&member)
+            {
+                members.push(member);
+            }
+        }
+    }
+    members.sort();
+    members
+}
+
+/// Expand a workspace member path, which may contain glob characters, into tree-relative
+/// directories.
+fn expand_member_glob(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let pattern = match normalize_relative_path(Path::new(pattern)) {
+        Some(pattern) => pattern,
+        None => {
+            eprintln!("workspace member {} is outside the source tree", pattern);
+            return Vec::new();
+        }
+    };
+    let is_glob = |component: &str| component.contains(['*', '?', '[']);
+    let components: Vec<String> = pattern
+        .iter()
+        .map(|c| c.to_string_lossy().into_owned())
+        .collect();
+    let literal_len = components
+        .iter()
+        .take_while(|component| !is_glob(component))
+        .count();
+    if literal_len == components.len() {
+        return vec![pattern];
+    }
+    let matcher = match globset::GlobBuilder::new(&pattern.to_slash_lossy())
+        .literal_separator(true)
+        .build()
+    {
+        Ok(glob) => glob.compile_matcher(),
+        Err(err) => {
+            eprintln!(
+                "invalid workspace member glob {}: {}",
+                pattern.to_slash_lossy(),
+                err
+            );
+            return Vec::new();
+        }
+    };
+    let base: PathBuf = components[..literal_len].iter().collect();
+    walkdir::WalkDir::new(root.join(&base))
+        .min_depth(components.len() - literal_len)
+        .max_depth(components.len() - literal_len)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_owned))
+        .filter(|path| matcher.is_match(path))
+        .collect()
+}
+
+/// Rewrite the manifests in a scratch copy of a source tree so that relative path dependencies
+/// that point outside the tree refer back to the original locations.
+///
+/// The top-level manifest, and those of any workspace members, are fixed. Dependencies inside
+/// the tree are left alone, so that they refer to the copy.
+///
+/// `scratch_dir` is the root of the copy, and `source_dir` is the directory it was copied from.
+pub fn fix_manifests(scratch_dir: &Path, source_dir: &Path) -> Result<()> {
+    let source_dir = if source_dir.is_absolute() {
+        source_dir.to_owned()
+    } else {
+        std::env::current_dir()
+            .context("get current directory")?
+            .join(source_dir)
+    };
+    let root_manifest = read_manifest(scratch_dir)?;
+    let mut package_dirs = vec![PathBuf::new()];
+    package_dirs.extend(workspace_members(&root_manifest, scratch_dir));
+    for package_dir in package_dirs {
+        let scratch_package_dir = scratch_dir.join(&package_dir);
+        let mut manifest = read_manifest(&scratch_package_dir)?;
+        if fix_manifest_toml(&mut manifest, &source_dir, &package_dir) {
+            let path = scratch_package_dir.join("Cargo.toml");
+            let toml = toml::to_string_pretty(&manifest).context("serialize manifest")?;
+            fs::write(&path, toml)
+                .with_context(|| format!("write manifest {}", path.to_slash_lossy()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite relative path dependencies that point outside of `source_dir` to be absolute.
+///
+/// `package_dir` is the tree-relative directory containing the manifest.
+///
+/// Returns true if anything was changed.
+fn fix_manifest_toml(manifest: &mut toml::Value, source_dir: &Path, package_dir: &Path) -> bool {
+    let mut changed = false;
+    if let Some(manifest) = manifest.as_table_mut() {
+        for table_name in DEPENDENCY_TABLES {
+            if let Some(deps) = manifest.get_mut(*table_name) {
+                changed |= fix_dependency_table(deps, source_dir, package_dir);
+            }
+        }
+        if let Some(targets) = manifest.get_mut("target").and_then(|t| t.as_table_mut()) {
+            for (_name, target) in targets.iter_mut() {
+                for table_name in DEPENDENCY_TABLES {
+                    if let Some(deps) = target.get_mut(*table_name) {
+                        changed |= fix_dependency_table(deps, source_dir, package_dir);
+                    }
+                }
+            }
+        }
+        if let Some(deps) = manifest
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("dependencies"))
+        {
+            changed |= fix_dependency_table(deps, source_dir, package_dir);
+        }
+        if let Some(patches) = manifest.get_mut("patch").and_then(|p| p.as_table_mut()) {
+            for (_registry, patch) in patches.iter_mut() {
+                changed |= fix_dependency_table(patch, source_dir, package_dir);
+            }
+        }
+        if let Some(replace) = manifest.get_mut("replace") {
+            changed |= fix_dependency_table(replace, source_dir, package_dir);
+        }
+    }
+    changed
+}
+
+fn fix_dependency_table(deps: &mut toml::Value, source_dir: &Path, package_dir: &Path) -> bool {
+    let mut changed = false;
+    if let Some(deps) = deps.as_table_mut() {
+        for (_name, dep) in deps.iter_mut() {
+            if let Some(toml::Value::String(path)) = dep.get_mut("path") {
+                if let Some(fixed) = fix_path(path, source_dir, package_dir) {
+                    *path = fixed;
+                    changed = true;
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// If `path`, relative to `package_dir`, points outside of the source tree, return an absolute
+/// path to it.
+fn fix_path(path: &str, source_dir: &Path, package_dir: &Path) -> Option<String> {
+    let path = Path::new(path);
+    if path.is_absolute() || normalize_relative_path(&package_dir.join(path)).is_some() {
+        None
+    } else {
+        Some(source_dir.join(package_dir).join(path).to_slash_lossy())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_script_path_from_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let no_build: toml::Value = "[package]\nname = 'a'\n".parse().unwrap();
+        assert_eq!(build_script_path(&no_build, tmp.path()), None);
+
+        fs::write(tmp.path().join("build.rs"), "fn main() {}").unwrap();
+        assert_eq!(
+            build_script_path(&no_build, tmp.path()),
+            Some(PathBuf::from("build.rs"))
+        );
+
+        let disabled: toml::Value = "[package]\nbuild = false\n".parse().unwrap();
+        assert_eq!(build_script_path(&disabled, tmp.path()), None);
+
+        let renamed: toml::Value = "[package]\nbuild = 'src/gen.rs'\n".parse().unwrap();
+        assert_eq!(
+            build_script_path(&renamed, tmp.path()),
+            Some(PathBuf::from("src/gen.rs"))
+        );
+    }
+
+    #[test]
+    fn proc_macro_from_manifest() {
+        let manifest: toml::Value = "[lib]\nproc-macro = true\n".parse().unwrap();
+        assert!(is_proc_macro(&manifest));
+        let manifest: toml::Value = "[lib]\nproc_macro = true\n".parse().unwrap();
+        assert!(is_proc_macro(&manifest));
+        let manifest: toml::Value = "[package]\nname = 'foo'\n".parse().unwrap();
+        assert!(!is_proc_macro(&manifest));
+    }
+
+    #[test]
+    fn main_bin_name_from_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("src/bin")).unwrap();
+        fs::write(tmp.path().join("src/bin/tool.rs"), "fn main() {}").unwrap();
+        let package: toml::Value = "[package]\nname = 'pkg'\n".parse().unwrap();
+        assert_eq!(main_bin_name(&package, tmp.path()).as_deref(), Some("pkg"));
+        let renamed: toml::Value =
+            "[package]\nname = 'pkg'\n[[bin]]\nname = 'tool'\n[[bin]]\nname = 'renamed'\n"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            main_bin_name(&renamed, tmp.path()).as_deref(),
+            Some("renamed")
+        );
+        let explicit: toml::Value =
+            "[package]\nname = 'pkg'\n[[bin]]\nname = 'x'\npath = 'src/main.rs'\n"
+                .parse()
+                .unwrap();
+        assert_eq!(main_bin_name(&explicit, tmp.path()).as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn workspace_members_from_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in [
+            "app",
+            "crates/a",
+            "crates/b",
+            "crates/skipped",
+            "crates/not_a_crate",
+        ] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            if dir != "crates/not_a_crate" {
+                fs::write(tmp.path().join(dir).join("Cargo.toml"), "").unwrap();
+            }
+        }
+        let manifest: toml::Value = r#"
+            [workspace]
+            members = ["app", "crates/*"]
+            exclude = ["crates/skipped"]
+            "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            workspace_members(&manifest, tmp.path()),
+            [
+                PathBuf::from("app"),
+                PathBuf::from("crates/a"),
+                PathBuf::from("crates/b")
+            ]
+        );
+        let package: toml::Value = "[package]\nname = 'a'\n".parse().unwrap();
+        assert!(workspace_members(&package, tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn fix_relative_dependencies_outside_tree() {
+        let mut manifest: toml::Value = r#"
+            [package]
+            name = "a"
+
+            [dependencies]
+            outside = { path = "../outside" }
+            inside = { path = "crates/inside" }
+            registry = "1.0"
+
+            [target.'cfg(unix)'.dev-dependencies]
+            unix_only = { path = "../../unix" }
+
+            [patch.crates-io]
+            patched = { path = "../patched" }
+
+            [replace]
+            "replaced:1.0.0" = { path = "../replaced" }
+            "#
+        .parse()
+        .unwrap();
+        let source_dir = Path::new("/src/a");
+        assert!(fix_manifest_toml(&mut manifest, source_dir, Path::new("")));
+        let path_of = |value: &toml::Value| value.get("path").unwrap().as_str().unwrap().to_owned();
+        assert_eq!(
+            path_of(&manifest["dependencies"]["outside"]),
+            "/src/a/../outside"
+        );
+        assert_eq!(
+            path_of(&manifest["dependencies"]["inside"]),
+            "crates/inside"
+        );
+        assert_eq!(
+            path_of(&manifest["target"]["cfg(unix)"]["dev-dependencies"]["unix_only"]),
+            "/src/a/../../unix"
+        );
+        assert_eq!(
+            path_of(&manifest["patch"]["crates-io"]["patched"]),
+            "/src/a/../patched"
+        );
+        assert_eq!(
+            path_of(&manifest["replace"]["replaced:1.0.0"]),
+            "/src/a/../replaced"
+        );
+        assert_eq!(manifest["dependencies"]["registry"].as_str(), Some("1.0"));
+    }
+
+    #[test]
+    fn manifest_without_outside_dependencies_is_unchanged() {
+        let mut manifest: toml::Value = "[dependencies]\ninside = { path = 'inside' }\n"
+            .parse()
+            .unwrap();
+        assert!(!fix_manifest_toml(
+            &mut manifest,
+            Path::new("/src/a"),
+            Path::new("")
+        ));
+    }
+
+    #[test]
+    fn member_dependencies_are_relative_to_the_member() {
+        let mut manifest: toml::Value = "[dependencies]\nup = { path = '../../up' }\n"
+            .parse()
+            .unwrap();
+        assert!(!fix_manifest_toml(
+            &mut manifest.clone(),
+            Path::new("/src/a"),
+            Path::new("crates/b")
+        ));
+        assert!(fix_manifest_toml(
+            &mut manifest,
+            Path::new("/src/a"),
+            Path::new("b")
+        ));
+        assert_eq!(
+            manifest["dependencies"]["up"]["path"].as_str(),
+            Some("/src/a/b/../../up")
+        );
+    }
+}
@@ -2,57 +2,72 @@
 
 //! Mutations to source files, and inference of interesting mutations to apply.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
+use path_slash::PathExt;
 use serde::ser::{SerializeStruct, Serializer};
 use serde::Serialize;
 use similar::TextDiff;
 
+use crate::operators;
 use crate::source::SourceFile;
-use crate::textedit::{replace_region, Span};
+use crate::textedit::{Edit, LineColumn, Span};
 
 /// A comment marker inserted next to changes, so they can be easily found.
 const MUTATION_MARKER_COMMENT: &str = "/* ~ changed by cargo-mutants ~ */";
 
-/// A type of mutation operation that could be applied to a source file.
-#[derive(Debug, Eq, PartialEq, Serialize)]
-pub enum MutationOp {
-    /// Return [Default::default].
-    Default,
-    /// Replace the function body with nothing (for functions that return `()`.
-    ///
-    /// We use `()` rather than just nothing because it's clearer in messages about the mutation.
-    Unit,
-    /// Return true.
-    True,
-    /// Return false.
-    False,
-    /// Return empty string.
-    EmptyString,
-    /// Return `"xyzzy"`.
-    Xyzzy,
-    /// Return `Ok(Default::default())`
-    OkDefault,
-}
+/// What part of a function a mutation replaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Genre {
+    /// The whole function body is replaced by a value.
+    FnValue,
 
-impl MutationOp {
-    /// Return the text that replaces the body of the mutated span, without the marker comment.
-    fn replacement(&self) -> &'static str {
-        use MutationOp::*;
-        match self {
-            Default => "Default::default()",
-            Unit => "()",
-            True => "true",
-            False => "false",
-            EmptyString => "\"\".into()",
-            Xyzzy => "\"xyzzy\".into()",
-            OkDefault => "Ok(Default::default())",
-        }
-    }
+    /// A binary operator within the function body, such as `+`, is replaced by another.
+    BinaryOperator,
+
+    /// The condition of an `if` within the function body is changed.
+    Condition,
+
+    /// An arm of a `match` within the function body is deleted.
+    MatchArm,
+
+    /// A literal within the function body, such as `true`, is replaced.
+    Literal,
+
+    /// A unary operator within the function body, such as `-`, is deleted, so the expression
+    /// is replaced by its operand.
+    UnaryOperator,
+
+    /// The limits of a range within the function body, such as `..`, are replaced.
+    Range,
+
+    /// The body of a closure within the function body is replaced.
+    Closure,
+
+    /// A statement within the function body is deleted.
+    Statement,
+
+    /// A `break` or `continue` within a loop in the function body is replaced by the other.
+    LoopControl,
+
+    /// The condition of a `while` loop, or the `loop` keyword, within the function body is
+    /// replaced, so that the loop doesn't run.
+    Loop,
+
+    /// An `if let` or `while let` within the function body is replaced by its `else` branch,
+    /// or an empty block.
+    IfLet,
+
+    /// Two parameters of the same type are swapped in the function's signature, by replacing
+    /// the text from the first to the second with their names exchanged.
+    Arguments,
 }
 
 /// A mutation that could possibly be applied to source code.
@@ -61,6 +76,7 @@ impl MutationOp {
 /// * which file to modify,
 /// * which function and span in that file,
 /// * and what type of mutation to apply.
+#[derive(Clone)]
 pub struct Mutation {
     pub source_file: SourceFile,
 
@@ -70,42 +86,187 @@ pub struct Mutation {
     /// The return type of the function, as a fragment of Rust syntax.
     return_type: String,
 
+    /// What part of the function is replaced.
+    genre: Genre,
+
     /// The mutated textual region.
     span: Span,
 
-    /// The type of change to apply.
-    pub op: MutationOp,
+    /// The body of the function containing the mutation, which for [Genre::FnValue] is the
+    /// same as the mutated span.
+    body_span: Span,
+
+    /// The name of the operator that generated this mutation.
+    pub op: String,
+
+    /// The text that replaces the body of the mutated span, without the marker comment.
+    replacement: String,
+
+    /// How long to let the tests run, from a `#[mutants::timeout]` attribute, overriding the
+    /// timeout for the whole run.
+    timeout: Option<Duration>,
+
+    /// A stable identifier for the mutant: see [Mutation::id].
+    id: String,
 }
 
 impl Mutation {
+    /// Make a mutation that replaces the body of a function, in `span`.
     pub fn new(
         source_file: SourceFile,
-        op: MutationOp,
+        op: String,
+        replacement: String,
+        function_name: String,
+        return_type: String,
+        span: Span,
+        timeout: Option<Duration>,
+    ) -> Mutation {
+        let target = source_file.target.to_string();
+        let id = format!(
+            "{:016x}",
+            stable_hash(&[
+                &source_file.package,
+                &target,
+                &source_file.tree_relative_slashes(),
+                &function_name,
+                &return_type,
+                &op,
+                &replacement,
+                source_file.span_text(&span),
+            ])
+        );
+        Mutation {
+            source_file,
+            op,
+            replacement,
+            function_name,
+            return_type,
+            genre: Genre::FnValue,
+            span,
+            body_span: span,
+            timeout,
+            id,
+        }
+    }
+
+    /// Make a mutation of part of a function body, of the given genre, that replaces the text
+    /// in `span` within a function whose body is in `body_span`.
+    ///
+    /// For [Genre::MatchArm] and [Genre::Statement], the span is deleted and the replacement
+    /// is empty.
+    ///
+    /// The id also depends on the position of the span relative to the start of the function
+    /// body, so that the same operator in different places in a function gives different
+    /// mutants, while it stays the same if the function moves. For [Genre::Arguments], the
+    /// span is in the signature, which can start on an earlier line than the body.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_in_body(
+        source_file: SourceFile,
+        genre: Genre,
+        op: String,
+        replacement: String,
         function_name: String,
         return_type: String,
+        body_span: Span,
         span: Span,
+        timeout: Option<Duration>,
     ) -> Mutation {
+        let relative_position = format!(
+            "{}:{}",
+            span.start.line as isize - body_span.start.line as isize,
+            span.start.column
+        );
+        let id = format!(
+            "{:016x}",
+            stable_hash(&[
+                &source_file.package,
+                &source_file.target.to_string(),
+                &source_file.tree_relative_slashes(),
+                &function_name,
+                &return_type,
+                &op,
+                &replacement,
+                source_file.span_text(&body_span),
+                source_file.span_text(&span),
+                &relative_position,
+            ])
+        );
         Mutation {
             source_file,
             op,
+            replacement,
             function_name,
             return_type,
+            genre,
             span,
+            body_span,
+            timeout,
+            id,
         }
     }
 
+    /// Return a stable identifier for this mutant.
+    ///
+    /// The id is a hash of the package, target, file, function, operator, replacement, and the
+    /// original text of the function body, but not the line number, so it stays the same when
+    /// unrelated edits move the function up or down the file. It changes when the function
+    /// itself is edited, since that's effectively a different mutant.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     /// Return text of the whole file with the mutation applied.
     pub fn mutated_code(&self) -> String {
-        replace_region(
-            &self.source_file.code,
-            &self.span.start,
-            &self.span.end,
-            &format!(
-                "{{\n{} {}\n}}\n",
-                self.op.replacement(),
-                MUTATION_MARKER_COMMENT
+        let edit = self.edit();
+        self.source_file.replace_span(&edit.span, &edit.replacement)
+    }
+
+    /// Check that the file still parses with the mutation applied, and if not return a
+    /// description of where and why.
+    ///
+    /// Every mutant should parse, so a syntax error means there's a bug in cargo-mutants, or a
+    /// plugin suggested something that isn't an expression, rather than that the mutant is
+    /// unviable because of the code it's in.
+    pub fn syntax_error(&self) -> Option<String> {
+        syn::parse_file(&self.mutated_code()).err().map(|err| {
+            let start = err.span().start();
+            format!(
+                "{}:{}:{}: {}",
+                self.source_file.tree_relative_slashes(),
+                start.line,
+                start.column + 1,
+                err
+            )
+        })
+    }
+
+    /// Return the edit to the original file that applies this mutation.
+    ///
+    /// Edits for mutants in the same file can be combined with [SourceFile::apply_edits],
+    /// so long as they're in different functions.
+    pub fn edit(&self) -> Edit {
+        match self.genre {
+            Genre::FnValue => Edit::new(
+                self.span,
+                format!("{{\n{} {}\n}}\n", self.replacement, MUTATION_MARKER_COMMENT),
             ),
-        )
+            Genre::BinaryOperator
+            | Genre::Condition
+            | Genre::Literal
+            | Genre::UnaryOperator
+            | Genre::Range
+            | Genre::Closure
+            | Genre::LoopControl
+            | Genre::Loop
+            | Genre::IfLet
+            | Genre::Arguments => Edit::new(
+                self.span,
+                format!("{} {}", self.replacement, MUTATION_MARKER_COMMENT),
+            ),
+            Genre::MatchArm | Genre::Statement => {
+                Edit::new(self.span, MUTATION_MARKER_COMMENT.to_owned())
+            }
+        }
     }
 
     /// Return the original code for the entire file affected by this mutation.
@@ -113,6 +274,85 @@ impl Mutation {
         &self.source_file.code
     }
 
+    /// Return the span of the original code that's replaced.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Return what part of the function is replaced.
+    pub fn genre(&self) -> Genre {
+        self.genre
+    }
+
+    /// Return the original text that's replaced by a mutation within a function body, such as
+    /// `+`, the condition of an `if`, a whole `match` arm, a literal, an expression whose
+    /// unary operator is deleted, the limits of a range, the body of a closure, a deleted
+    /// statement, a `break` or `continue`, the head of a loop, a whole `if let` or
+    /// `while let`, or the swapped parameters, or None for mutations of the whole function
+    /// body.
+    pub fn original_text(&self) -> Option<&str> {
+        match self.genre {
+            Genre::FnValue => None,
+            Genre::BinaryOperator
+            | Genre::Condition
+            | Genre::MatchArm
+            | Genre::Literal
+            | Genre::UnaryOperator
+            | Genre::Range
+            | Genre::Closure
+            | Genre::Statement
+            | Genre::LoopControl
+            | Genre::Loop
+            | Genre::IfLet
+            | Genre::Arguments => Some(self.source_file.span_text(&self.span)),
+        }
+    }
+
+    /// Return the pattern, and guard if any, of a deleted `match` arm, or None for other
+    /// mutations.
+    pub fn match_arm_pattern(&self) -> Option<String> {
+        match self.genre {
+            Genre::MatchArm => {
+                let arm = self.source_file.span_text(&self.span);
+                Some(one_line(arm.split("=>").next().unwrap_or(arm)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the text of a deleted statement, on one line, or None for other mutations.
+    pub fn deleted_statement(&self) -> Option<String> {
+        match self.genre {
+            Genre::Statement => Some(one_line(self.source_file.span_text(&self.span))),
+            _ => None,
+        }
+    }
+
+    /// Return the names of the two parameters whose names are swapped, or None for other
+    /// mutations.
+    pub fn swapped_arguments(&self) -> Option<(&str, &str)> {
+        match self.genre {
+            Genre::Arguments => {
+                // The span runs from the first name to the second.
+                let text = self.source_file.span_text(&self.span);
+                let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '#';
+                let first_end = text.find(|c| !is_ident(c)).unwrap_or(text.len());
+                let second_start = text
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| !is_ident(*c))
+                    .map_or(0, |(i, c)| i + c.len_utf8());
+                Some((&text[..first_end], &text[second_start..]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the timeout for the tests of this mutant, if its function sets one.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     pub fn return_type(&self) -> &str {
         &self.return_type
     }
@@ -127,17 +367,37 @@ impl Mutation {
     }
 
     /// Describe the mutation briefly, not including the location.
+    ///
+    /// Conditions that span several lines are described on one line.
     pub fn describe_change(&self) -> String {
-        format!(
-            "replace {} with {}",
-            self.function_name(),
-            self.op.replacement()
-        )
+        if let Some(pattern) = self.match_arm_pattern() {
+            return format!("delete match arm {} in {}", pattern, self.function_name());
+        }
+        if let Some(statement) = self.deleted_statement() {
+            return format!("delete {} in {}", statement, self.function_name());
+        }
+        if let Some((first, second)) = self.swapped_arguments() {
+            return format!(
+                "swap arguments {} and {} in {}",
+                first,
+                second,
+                self.function_name()
+            );
+        }
+        match self.original_text() {
+            None => format!("replace {} with {}", self.function_name(), self.replacement),
+            Some(original) => format!(
+                "replace {} with {} in {}",
+                one_line(original),
+                one_line(&self.replacement),
+                self.function_name()
+            ),
+        }
     }
 
     /// Return the text inserted for this mutation.
-    pub fn replacement_text(&self) -> &'static str {
-        self.op.replacement()
+    pub fn replacement_text(&self) -> &str {
+        &self.replacement
     }
 
     /// Return the name of the function to be mutated.
@@ -159,6 +419,16 @@ impl Mutation {
             .to_string()
     }
 
+    /// Return the mutation as a patch that can be applied to the tree by `patch -p1` or
+    /// `git apply`.
+    pub fn patch(&self) -> String {
+        let path = self.source_file.tree_relative_slashes();
+        TextDiff::from_lines(self.original_code(), &self.mutated_code())
+            .unified_diff()
+            .header(&format!("a/{}", path), &format!("b/{}", path))
+            .to_string()
+    }
+
     /// Change the file affected by this mutation in the given directory.
     fn apply_in_dir(&self, dir: &Path) -> Result<()> {
         self.write_in_dir(dir, &self.mutated_code())
@@ -186,7 +456,7 @@ impl Mutation {
         // for safety, don't follow symlinks
         assert!(path.is_file(), "{:?} is not a file", path);
         fs::write(&path, code.as_bytes())
-            .with_context(|| format!("failed to write mutated code to {:?}", path))
+            .with_context(|| format!("failed to write mutated code to {}", path.to_slash_lossy()))
     }
 }
 
@@ -214,18 +484,140 @@ impl fmt::Display for Mutation {
     }
 }
 
+/// Collapse each run of whitespace, including newlines, to a single space.
+pub(crate) fn one_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Give any mutants with the same id, such as identical functions under different `cfg`
+/// attributes, distinct ids, by appending `-2`, `-3`, etc to all but the first.
+pub(crate) fn disambiguate_ids(mutations: &mut [Mutation]) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for mutation in mutations {
+        let count = seen.entry(mutation.id.clone()).or_default();
+        *count += 1;
+        if *count > 1 {
+            mutation.id = format!("{}-{}", mutation.id, count);
+        }
+    }
+}
+
+/// Sort the mutants of one file into the order in which they're listed and tested: by the
+/// position of the mutated span, then by operator, in the order they're enabled in
+/// `operators`, with plugins last, and then by the order in which a binary operator lists its
+/// replacements. Mutants that are otherwise equal, such as several from one
+/// plugin, stay in the order they were generated.
+pub(crate) fn sort_mutations(mutations: &mut [Mutation], operators: &operators::Operators) {
+    let names = operators.names();
+    mutations.sort_by_cached_key(|mutation| {
+        let replacement_index = mutation
+            .original_text()
+            .filter(|_| mutation.genre == Genre::BinaryOperator)
+            .and_then(|original| syn::parse_str::<syn::BinOp>(original).ok())
+            .and_then(|bin_op| {
+                operators
+                    .binary_replacements(&bin_op)
+                    .position(|(name, replacement)| {
+                        name == mutation.op && replacement == mutation.replacement
+                    })
+            })
+            .unwrap_or(0);
+        (
+            mutation.span.start,
+            names
+                .iter()
+                .position(|name| *name == mutation.op)
+                .unwrap_or(names.len()),
+            replacement_index,
+        )
+    });
+}
+
+/// Keep at most `max` mutants of each function, choosing a representative subset.
+///
+/// The mutants of each function are grouped by operator, and taken from each operator in
+/// turn, so that every kind of mutation is represented before any has a second mutant. Within
+/// an operator, the order is a hash of the mutant's id and `seed`, so the choice is the same on
+/// every run with the same seed, but a different seed gives a different sample.
+///
+/// The chosen mutants are returned in their original order.
+pub fn cap_per_function(mutations: Vec<Mutation>, max: usize, seed: u64) -> Vec<Mutation> {
+    // For each function, for each of its operators in order of first appearance, the indexes
+    // of its mutants.
+    let mut functions: Vec<Vec<(String, Vec<usize>)>> = Vec::new();
+    let mut function_index: HashMap<(String, LineColumn), usize> = HashMap::new();
+    for (i, mutation) in mutations.iter().enumerate() {
+        let function_key = (
+            mutation.source_file.tree_relative_slashes(),
+            mutation.body_span.start,
+        );
+        let index = *function_index.entry(function_key).or_insert_with(|| {
+            functions.push(Vec::new());
+            functions.len() - 1
+        });
+        let ops = &mut functions[index];
+        match ops.iter_mut().find(|(op, _)| *op == mutation.op) {
+            Some((_, indexes)) => indexes.push(i),
+            None => ops.push((mutation.op.clone(), vec![i])),
+        }
+    }
+    let seed = seed.to_string();
+    let mut keep = vec![false; mutations.len()];
+    for mut ops in functions {
+        for (_, indexes) in &mut ops {
+            indexes.sort_by_key(|&i| stable_hash(&[&seed, mutations[i].id()]));
+            indexes.reverse(); // so that pop takes them in order
+        }
+        let mut taken = 0;
+        while taken < max && ops.iter().any(|(_, indexes)| !indexes.is_empty()) {
+            for (_, indexes) in &mut ops {
+                if taken == max {
+                    break;
+                }
+                if let Some(i) = indexes.pop() {
+                    keep[i] = true;
+                    taken += 1;
+                }
+            }
+        }
+    }
+    mutations
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(mutation, keep)| keep.then_some(mutation))
+        .collect()
+}
+
+/// Hash strings with 64-bit FNV-1a, which is simple and, unlike the standard library's hasher,
+/// guaranteed to give the same result on every platform and release.
+fn stable_hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // Terminate each part so that moving text between parts changes the hash.
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
 impl Serialize for Mutation {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         // custom serialize to omit inessential info
-        let mut ss = serializer.serialize_struct("Mutation", 4)?;
+        let mut ss = serializer.serialize_struct("Mutation", 9)?;
+        ss.serialize_field("id", &self.id)?;
         ss.serialize_field("file", &self.source_file.tree_relative_slashes())?;
         ss.serialize_field("line", &self.span.start.line)?;
+        ss.serialize_field("span", &self.span)?;
         ss.serialize_field("function", &self.function_name)?;
         ss.serialize_field("return_type", &self.return_type)?;
-        ss.serialize_field("replacement", self.op.replacement())?;
+        ss.serialize_field("genre", &self.genre)?;
+        ss.serialize_field("replacement", &self.replacement)?;
+        ss.serialize_field("explanation", &operators::explain(&self.op))?;
         ss.end()
     }
 }
@@ -238,6 +630,7 @@ mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::operators::Operators;
 
     #[test]
     fn discover_mutations() {
@@ -246,15 +639,206 @@ mod test {
             Path::new("src/bin/main.rs"),
         )
         .unwrap();
-        let muts = source_file.mutations().unwrap();
-        assert_eq!(muts.len(), 2);
+        let muts = source_file.mutations(&Operators::all()).unwrap();
+        assert_eq!(muts.len(), 9);
         assert_eq!(
             format!("{:?}", muts[0]),
-            r#"Mutation { op: Unit, function_name: "main", return_type: "", start: (1, 11), end: (5, 2) }"#
+            r#"Mutation { op: "unit", function_name: "main", return_type: "", start: (1, 11), end: (5, 2) }"#
         );
         assert_eq!(
             format!("{:?}", muts[1]),
-            r#"Mutation { op: Default, function_name: "factorial", return_type: "-> u32", start: (7, 29), end: (13, 2) }"#
+            r#"Mutation { op: "range_boundary", function_name: "main", return_type: "", start: (2, 15), end: (2, 17) }"#
+        );
+        assert_eq!(muts[2].genre(), Genre::Statement);
+        assert_eq!(
+            muts[2].describe_change(),
+            r#"delete println!("{}! = {}", i, factorial(i)); in main"#
+        );
+        assert_eq!(
+            format!("{:?}", muts[3]),
+            r#"Mutation { op: "default", function_name: "factorial", return_type: "-> u32", start: (7, 29), end: (13, 2) }"#
+        );
+        assert_eq!(
+            format!("{:?}", muts[6]),
+            r#"Mutation { op: "delete_statement", function_name: "factorial", return_type: "-> u32", start: (10, 9), end: (10, 15) }"#
+        );
+        assert_eq!(
+            format!("{:?}", muts[7]),
+            r#"Mutation { op: "arithmetic", function_name: "factorial", return_type: "-> u32", start: (10, 11), end: (10, 12) }"#
+        );
+        assert_eq!(muts[7].genre(), Genre::BinaryOperator);
+        assert_eq!(muts[7].original_text(), Some("*="));
+        assert_eq!(muts[7].replacement_text(), "/=");
+        assert_eq!(muts[8].replacement_text(), "+=");
+        assert_ne!(muts[7].id(), muts[8].id());
+    }
+
+    #[test]
+    fn edits_for_mutants_in_one_file_can_be_combined() {
+        let muts = SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        let source_file = &muts[0].source_file;
+        let both = source_file
+            .apply_edits(&[muts[3].edit(), muts[0].edit()])
+            .unwrap();
+        assert_eq!(
+            source_file.apply_edits(&[muts[0].edit()]).unwrap(),
+            muts[0].mutated_code()
+        );
+        assert!(both.starts_with("fn main() {\n() /* ~ changed by cargo-mutants ~ */\n}\n"));
+        assert!(both.contains(
+            "fn factorial(n: u32) -> u32 {\nDefault::default() /* ~ changed by cargo-mutants ~ */\n}\n"
+        ));
+        assert!(source_file
+            .apply_edits(&[muts[3].edit(), muts[3].edit()])
+            .is_err());
+    }
+
+    #[test]
+    fn mutants_that_dont_parse_have_syntax_errors() {
+        let muts = SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        assert_eq!(muts[3].syntax_error(), None);
+        let mut bad = muts[3].clone();
+        bad.replacement = "1 +".to_owned();
+        assert_eq!(
+            bad.syntax_error().unwrap(),
+            "src/bin/main.rs:9:1: unexpected end of input, expected expression"
+        );
+    }
+
+    #[test]
+    fn mutations_are_sorted_by_position_then_operator() {
+        let operators = Operators::all();
+        let mutations = SourceFile::new(
+            Path::new("testdata/tree/well_tested"),
+            Path::new("src/simple_fns.rs"),
+        )
+        .unwrap()
+        .mutations(&operators)
+        .unwrap();
+        let described = mutations.iter().map(|m| m.to_string()).collect_vec();
+        assert_eq!(
+            &described[5..7],
+            [
+                "replace divisible_by_three with true in src/simple_fns.rs:17",
+                "replace divisible_by_three with false in src/simple_fns.rs:17",
+            ]
+        );
+        let mut shuffled = mutations;
+        shuffled.reverse();
+        sort_mutations(&mut shuffled, &operators);
+        assert_eq!(
+            shuffled.iter().map(|m| m.to_string()).collect_vec(),
+            described
+        );
+    }
+
+    #[test]
+    fn serialized_mutations_have_spans() {
+        let mutations = SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        let json = serde_json::to_value(&mutations[3]).unwrap();
+        assert_eq!(json["line"], 7);
+        assert_eq!(
+            json["span"],
+            serde_json::json!({
+                "start": { "line": 7, "column": 29 },
+                "end": { "line": 13, "column": 2 },
+            })
+        );
+    }
+
+    #[test]
+    fn ids_are_stable_when_lines_move() {
+        let mutations = |code: &str| {
+            SourceFile::from_bytes(Path::new("src/lib.rs"), code.as_bytes())
+                .unwrap()
+                .mutations(&Operators::all())
+                .unwrap()
+                .iter()
+                .map(|m| (m.describe_change(), m.id().to_owned()))
+                .collect_vec()
+        };
+        let original = mutations("fn a() -> u32 {\n    1\n}\n\nfn b() -> u32 {\n    2\n}\n");
+        let moved = mutations(
+            "// A new comment.\n\nfn a() -> u32 {\n    1\n}\n\nfn b() -> u32 {\n    2\n}\n",
+        );
+        assert_eq!(original, moved);
+        assert_eq!(original[0].1.len(), 16);
+        assert_ne!(original[0].1, original[1].1);
+
+        // Editing a function changes only its own id.
+        let edited = mutations("fn a() -> u32 {\n    1\n}\n\nfn b() -> u32 {\n    3\n}\n");
+        assert_eq!(edited[0], original[0]);
+        assert_ne!(edited[1], original[1]);
+
+        // Identical functions get distinct ids.
+        let twins = mutations("#[cfg(unix)]\nfn a() {\n}\n#[cfg(windows)]\nfn a() {\n}\n");
+        assert_eq!(twins[1].1, format!("{}-2", twins[0].1));
+    }
+
+    #[test]
+    fn cap_mutants_per_function() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            b"fn a() -> u32 {\n    1\n}\n\nfn b() -> u32 {\n    2\n}\n",
+        )
+        .unwrap();
+        let function = |mutations: &mut Vec<Mutation>, line, op: &str, replacements: &[&str]| {
+            for replacement in replacements {
+                mutations.push(Mutation::new(
+                    source_file.clone(),
+                    op.to_owned(),
+                    replacement.to_string(),
+                    format!("f{}", line),
+                    "-> u32".to_owned(),
+                    Span::new(LineColumn::new(line, 15), LineColumn::new(line + 2, 1)),
+                    None,
+                ));
+            }
+        };
+        let mutations = || {
+            let mut mutations = Vec::new();
+            function(&mut mutations, 1, "default", &["Default::default()"]);
+            function(&mut mutations, 1, "plugin", &["2", "3", "4", "5", "6"]);
+            function(&mut mutations, 5, "default", &["Default::default()"]);
+            mutations
+        };
+        let describe = |mutations: &[Mutation]| {
+            mutations
+                .iter()
+                .map(|m| format!("{} {}", m.function_name(), m.replacement_text()))
+                .collect_vec()
+        };
+        let capped = cap_per_function(mutations(), 3, 0);
+        let described = describe(&capped);
+        // Every operator is represented, and other functions are untouched.
+        assert_eq!(described.len(), 4);
+        assert_eq!(described[0], "f1 Default::default()");
+        assert_eq!(described[3], "f5 Default::default()");
+        // The choice is repeatable, and depends on the seed.
+        assert_eq!(describe(&cap_per_function(mutations(), 3, 0)), described);
+        assert!((1..10).any(|seed| describe(&cap_per_function(mutations(), 3, seed)) != described));
+        assert_eq!(cap_per_function(mutations(), 10, 0).len(), 7);
+        assert_eq!(
+            describe(&cap_per_function(mutations(), 1, 0)),
+            ["f1 Default::default()", "f5 Default::default()"]
         );
     }
 
@@ -265,11 +849,16 @@ mod test {
             Path::new("src/lib.rs"),
         )
         .unwrap();
-        let muts = source_file.mutations().unwrap();
+        let muts = source_file.mutations(&Operators::all()).unwrap();
         let descriptions = muts.iter().map(Mutation::describe_change).collect_vec();
         insta::assert_snapshot!(
             descriptions.join("\n"),
-            @"replace controlled_loop with ()"
+            @r###"
+        replace controlled_loop with ()
+        delete println!("{}", i); in controlled_loop
+        replace should_stop() with !(should_stop()) in controlled_loop
+        replace break with continue in controlled_loop
+        "###
         );
     }
 
@@ -280,8 +869,8 @@ mod test {
             Path::new("src/bin/main.rs"),
         )
         .unwrap();
-        let muts = source_file.mutations().unwrap();
-        assert_eq!(muts.len(), 2);
+        let muts = source_file.mutations(&Operators::all()).unwrap();
+        assert_eq!(muts.len(), 9);
 
         let mut mutated_code = muts[0].mutated_code();
         assert_eq!(muts[0].function_name(), "main");
@@ -307,8 +896,8 @@ fn test_factorial() {
 "#
         );
 
-        let mut mutated_code = muts[1].mutated_code();
-        assert_eq!(muts[1].function_name(), "factorial");
+        let mut mutated_code = muts[3].mutated_code();
+        assert_eq!(muts[3].function_name(), "factorial");
         mutated_code.retain(|c| c != '\r');
         assert_eq!(
             mutated_code,
@@ -328,5 +917,19 @@ fn test_factorial() {
 }
 "#
         );
+
+        assert!(muts[5]
+            .mutated_code()
+            .contains("\n    for i in 2.. /* ~ changed by cargo-mutants ~ */n {\n"));
+        assert!(muts[6].mutated_code().contains(
+            "\n    for i in 2..=n {\n        /* ~ changed by cargo-mutants ~ */\n    }\n"
+        ));
+        assert!(muts[7]
+            .mutated_code()
+            .contains("\n        a /= /* ~ changed by cargo-mutants ~ */ i;\n"));
+        assert_eq!(
+            muts[7].to_string(),
+            "replace *= with /= in factorial in src/bin/main.rs:10"
+        );
     }
 }
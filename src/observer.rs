@@ -0,0 +1,249 @@
+// Copyright 2022 Martin Pool
+
+//! Observe the progress of a lab run, to report it.
+//!
+//! The lab tells an [Observer] about each event as it happens. The console, and the JSON and
+//! text files in `mutants.out`, are all written by observers, and library users can add their
+//! own to report results elsewhere.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::lab::{LabOutcome, Outcome};
+use crate::mutate::Mutation;
+use crate::output::LogFile;
+use crate::run::CargoResult;
+
+/// Something that is built and tested: the source tree, the unmutated baseline, or a mutant.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum Scenario<'a> {
+    /// Building the original source tree, or only checking it.
+    SourceTree { check_only: bool },
+    /// Testing a clean copy of the tree with no mutations.
+    Baseline,
+    /// Testing a mutant.
+    Mutant(&'a Mutation),
+}
+
+impl<'a> Scenario<'a> {
+    /// Return the mutation, if this scenario tests a mutant.
+    pub fn mutation(&self) -> Option<&'a Mutation> {
+        match self {
+            Scenario::Mutant(mutation) => Some(mutation),
+            _ => None,
+        }
+    }
+}
+
+/// A step in testing a scenario, which runs one cargo command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Phase {
+    Check,
+    Build,
+    Test,
+}
+
+impl Phase {
+    /// The name of the phase, which is also the cargo subcommand it runs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Check => "check",
+            Phase::Build => "build",
+            Phase::Test => "test",
+        }
+    }
+}
+
+/// Receives events from the lab as it runs.
+///
+/// All the methods do nothing by default, so implementations can handle only the events they
+/// care about.
+#[allow(unused_variables)]
+pub trait Observer {
+    /// Copying the source tree to the scratch directory is starting.
+    fn copy_started(&mut self) {}
+
+    /// Some bytes of the tree have been copied, out of an unknown total.
+    fn copy_progress(&mut self, bytes_copied: u64) {}
+
+    /// Copying the tree finished, or failed if `bytes_copied` is None.
+    fn copy_finished(&mut self, bytes_copied: Option<u64>) {}
+
+    /// The mutants to be tested have been found.
+    fn mutants_discovered(&mut self, mutations: &[Mutation]) {}
+
+    /// These mutants matched the ignore list, so they won't be tested.
+    fn mutants_ignored(&mut self, mutations: &[Mutation]) {}
+
+    /// Something is probably wrong, but the run continues.
+    fn warning(&mut self, message: &str) {}
+
+    /// Called with the mutants that no tests could catch, such as those in a binary with no
+    /// unit tests when there are no integration tests, before they're tested.
+    fn mutants_uncatchable(&mut self, mutations: &[Mutation]) {}
+
+    /// A scenario is starting, and the output of the commands it runs will be written to
+    /// `log_file`.
+    fn scenario_started(&mut self, scenario: &Scenario, log_file: &LogFile) {}
+
+    /// A phase within the current scenario is starting.
+    fn phase_started(&mut self, scenario: &Scenario, phase: Phase) {}
+
+    /// A phase within the current scenario finished.
+    fn phase_finished(
+        &mut self,
+        scenario: &Scenario,
+        phase: Phase,
+        result: &CargoResult,
+        duration: Duration,
+    ) {
+    }
+
+    /// A scenario finished.
+    fn scenario_finished(&mut self, scenario: &Scenario, outcome: &Outcome) {}
+
+    /// The whole run finished.
+    fn run_finished(&mut self, lab_outcome: &LabOutcome) {}
+
+    /// Called periodically while cargo is running, for example to animate progress bars.
+    fn tick(&mut self) {}
+}
+
+/// An observer that passes every event on to several others, in order.
+#[derive(Default)]
+pub struct Observers<'o> {
+    observers: Vec<&'o mut dyn Observer>,
+}
+
+impl<'o> Observers<'o> {
+    pub fn new() -> Observers<'o> {
+        Observers::default()
+    }
+
+    /// Add an observer, which will see events after all those added before it.
+    pub fn add(&mut self, observer: &'o mut dyn Observer) {
+        self.observers.push(observer);
+    }
+}
+
+impl<'o> Observer for Observers<'o> {
+    fn copy_started(&mut self) {
+        self.observers.iter_mut().for_each(|o| o.copy_started())
+    }
+
+    fn copy_progress(&mut self, bytes_copied: u64) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.copy_progress(bytes_copied))
+    }
+
+    fn copy_finished(&mut self, bytes_copied: Option<u64>) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.copy_finished(bytes_copied))
+    }
+
+    fn mutants_discovered(&mut self, mutations: &[Mutation]) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.mutants_discovered(mutations))
+    }
+
+    fn mutants_ignored(&mut self, mutations: &[Mutation]) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.mutants_ignored(mutations))
+    }
+
+    fn warning(&mut self, message: &str) {
+        self.observers.iter_mut().for_each(|o| o.warning(message))
+    }
+
+    fn mutants_uncatchable(&mut self, mutations: &[Mutation]) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.mutants_uncatchable(mutations))
+    }
+
+    fn scenario_started(&mut self, scenario: &Scenario, log_file: &LogFile) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.scenario_started(scenario, log_file))
+    }
+
+    fn phase_started(&mut self, scenario: &Scenario, phase: Phase) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.phase_started(scenario, phase))
+    }
+
+    fn phase_finished(
+        &mut self,
+        scenario: &Scenario,
+        phase: Phase,
+        result: &CargoResult,
+        duration: Duration,
+    ) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.phase_finished(scenario, phase, result, duration))
+    }
+
+    fn scenario_finished(&mut self, scenario: &Scenario, outcome: &Outcome) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.scenario_finished(scenario, outcome))
+    }
+
+    fn run_finished(&mut self, lab_outcome: &LabOutcome) {
+        self.observers
+            .iter_mut()
+            .for_each(|o| o.run_finished(lab_outcome))
+    }
+
+    fn tick(&mut self) {
+        self.observers.iter_mut().for_each(|o| o.tick())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Observer for Recorder {
+        fn scenario_started(&mut self, scenario: &Scenario, _log_file: &LogFile) {
+            self.events.push(format!("start {:?}", scenario));
+        }
+
+        fn phase_started(&mut self, _scenario: &Scenario, phase: Phase) {
+            self.events.push(phase.name().to_owned());
+        }
+    }
+
+    #[test]
+    fn observers_see_events_in_order() {
+        let mut a = Recorder::default();
+        let mut b = Recorder::default();
+        let log_file = LogFile {
+            path: "baseline.log".into(),
+        };
+        {
+            let mut observers = Observers::new();
+            observers.add(&mut a);
+            observers.add(&mut b);
+            observers.scenario_started(&Scenario::Baseline, &log_file);
+            observers.phase_started(&Scenario::Baseline, Phase::Check);
+            observers.tick();
+        }
+        assert_eq!(a.events, ["start Baseline", "check"]);
+        assert_eq!(a.events, b.events);
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2022 Martin Pool
+
+//! Swap the arguments of functions that take several parameters of the same type.
+
+use quote::ToTokens;
+
+use super::{ArgumentOperator, Describe};
+
+/// Swap the names of two parameters of the same type in the function's signature, so that
+/// the body uses each argument where it used the other, as if every caller passed them in
+/// the wrong order. Any `mut` and the types stay where they are.
+///
+/// Only parameters bound to a plain name, like `a` or `mut a`, are swapped, and `self` never
+/// is. Types are compared as they're written, so `&'a str` and `&'b str` aren't swapped,
+/// which would often not build.
+pub struct Swap;
+
+impl Describe for Swap {
+    fn name(&self) -> &'static str {
+        "swap_arguments"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Swaps the names of two parameters of the same type, so that the function uses each argument where it used the other, as if every caller passed them in the wrong order."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that passes different values for the two arguments, and checks a result that depends on which is which."
+    }
+}
+
+impl ArgumentOperator for Swap {
+    fn pairs_to_swap(&self, sig: &syn::Signature) -> Vec<(usize, usize)> {
+        let types = sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                syn::FnArg::Typed(pat_type) if pat_is_name(&pat_type.pat) => {
+                    Some(pat_type.ty.to_token_stream().to_string())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+        for (i, typ) in types.iter().enumerate() {
+            if let Some(typ) = typ {
+                for (j, other) in types.iter().enumerate().skip(i + 1) {
+                    if other.as_ref() == Some(typ) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// True if the pattern just binds a name, possibly `mut` or `ref`, other than `self`.
+fn pat_is_name(pat: &syn::Pat) -> bool {
+    matches!(pat, syn::Pat::Ident(pat_ident) if pat_ident.subpat.is_none() && pat_ident.ident != "self")
+}
@@ -0,0 +1,100 @@
+// Copyright 2022 Martin Pool
+
+//! Replace binary operators within function bodies.
+
+use super::{BinaryOperator, Describe};
+
+/// Swap arithmetic operators, such as `+` for `-`, including in compound assignments like `+=`.
+pub struct Arithmetic;
+
+impl Describe for Arithmetic {
+    fn name(&self) -> &'static str {
+        "arithmetic"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces an arithmetic operator with another, such as `+` with `-`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the two operators give different results, which usually means neither operand is zero or one."
+    }
+}
+
+impl BinaryOperator for Arithmetic {
+    fn replacements(&self, op: &syn::BinOp) -> &'static [&'static str] {
+        use syn::BinOp::*;
+        match op {
+            Add(_) => &["-", "*"],
+            Sub(_) => &["+", "/"],
+            Mul(_) => &["/", "+"],
+            Div(_) => &["*", "%"],
+            Rem(_) => &["/", "+"],
+            AddEq(_) => &["-=", "*="],
+            SubEq(_) => &["+=", "/="],
+            MulEq(_) => &["/=", "+="],
+            DivEq(_) => &["*=", "%="],
+            RemEq(_) => &["/=", "+="],
+            _ => &[],
+        }
+    }
+}
+
+/// Move the boundary of a comparison, such as `<` to `<=`, or invert an equality test.
+pub struct Comparison;
+
+impl Describe for Comparison {
+    fn name(&self) -> &'static str {
+        "comparison"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces a comparison with one that differs when the operands are equal, such as `<` with `<=`, or `==` with `!=`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of the boundary case, where the two operands are equal."
+    }
+}
+
+impl BinaryOperator for Comparison {
+    fn replacements(&self, op: &syn::BinOp) -> &'static [&'static str] {
+        use syn::BinOp::*;
+        match op {
+            Lt(_) => &["<="],
+            Le(_) => &["<"],
+            Gt(_) => &[">="],
+            Ge(_) => &[">"],
+            Eq(_) => &["!="],
+            Ne(_) => &["=="],
+            _ => &[],
+        }
+    }
+}
+
+/// Swap the logical connectives `&&` and `||`.
+pub struct Logical;
+
+impl Describe for Logical {
+    fn name(&self) -> &'static str {
+        "logical"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces `&&` with `||`, or `||` with `&&`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test where exactly one of the two conditions is true."
+    }
+}
+
+impl BinaryOperator for Logical {
+    fn replacements(&self, op: &syn::BinOp) -> &'static [&'static str] {
+        match op {
+            syn::BinOp::And(_) => &["||"],
+            syn::BinOp::Or(_) => &["&&"],
+            _ => &[],
+        }
+    }
+}
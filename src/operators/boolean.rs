@@ -0,0 +1,86 @@
+// Copyright 2022 Martin Pool
+
+//! Return a constant from functions that return `bool`, or flip boolean literals within them.
+
+use super::{Describe, Function, LiteralOperator, Operator};
+
+/// Return `true`.
+pub struct True;
+
+impl Describe for True {
+    fn name(&self) -> &'static str {
+        "true"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `true`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return `false`."
+    }
+}
+
+impl Operator for True {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_bool()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "true"
+    }
+}
+
+/// Return `false`.
+pub struct False;
+
+impl Describe for False {
+    fn name(&self) -> &'static str {
+        "false"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `false`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return `true`."
+    }
+}
+
+impl Operator for False {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_bool()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "false"
+    }
+}
+
+/// Flip a `true` literal to `false`, or `false` to `true`, anywhere in a function body.
+pub struct FlipLiteral;
+
+impl Describe for FlipLiteral {
+    fn name(&self) -> &'static str {
+        "flip_bool"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces a `true` literal with `false`, or `false` with `true`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks a result that depends on the value of the literal, such as a flag passed to another function or set in a struct."
+    }
+}
+
+impl LiteralOperator for FlipLiteral {
+    fn replacement(&self, lit: &syn::Lit) -> Option<&'static str> {
+        match lit {
+            syn::Lit::Bool(syn::LitBool { value: true, .. }) => Some("false"),
+            syn::Lit::Bool(syn::LitBool { value: false, .. }) => Some("true"),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2022 Martin Pool
+
+//! Return constants from functions that return `char`.
+
+use super::{Describe, Function, Operator};
+
+/// Return `'\0'`.
+pub struct Nul;
+
+impl Describe for Nul {
+    fn name(&self) -> &'static str {
+        "char_nul"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return the NUL character, `'\\0'`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a printable character, that checks which one."
+    }
+}
+
+impl Operator for Nul {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_char()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "'\\0'"
+    }
+}
+
+/// Return `'x'`.
+pub struct X;
+
+impl Describe for X {
+    fn name(&self) -> &'static str {
+        "char_x"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `'x'`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a character other than `'x'`, that checks which one."
+    }
+}
+
+impl Operator for X {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_char()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "'x'"
+    }
+}
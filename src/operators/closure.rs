@@ -0,0 +1,51 @@
+// Copyright 2022 Martin Pool
+
+//! Replace the bodies of closures that return `bool` within function bodies.
+
+use super::{Closure, ClosureOperator, Describe};
+
+/// Make a closure that returns `bool` always return `true`.
+pub struct True;
+
+impl Describe for True {
+    fn name(&self) -> &'static str {
+        "closure_true"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes a closure that returns `bool`, such as the predicate of a `filter`, always return `true`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test where the closure should return `false` for some item, that checks the item is treated differently."
+    }
+}
+
+impl ClosureOperator for True {
+    fn replacement(&self, closure: &Closure) -> Option<&'static str> {
+        (closure.returns_bool() && !closure.body_is_bool_lit(true)).then_some("true")
+    }
+}
+
+/// Make a closure that returns `bool` always return `false`.
+pub struct False;
+
+impl Describe for False {
+    fn name(&self) -> &'static str {
+        "closure_false"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes a closure that returns `bool`, such as the predicate of a `filter`, always return `false`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test where the closure should return `true` for some item, that checks the item is treated differently."
+    }
+}
+
+impl ClosureOperator for False {
+    fn replacement(&self, closure: &Closure) -> Option<&'static str> {
+        (closure.returns_bool() && !closure.body_is_bool_lit(false)).then_some("false")
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2022 Martin Pool
+
+//! Return a collection with one entry from functions that return a map or a set.
+//!
+//! The empty collection comes from [Default](super::default::Default).
+
+use super::{Describe, Function, Operator};
+
+/// Return a map with one entry, whose key and value are both their type's default.
+pub struct OneEntryMap;
+
+impl Describe for OneEntryMap {
+    fn name(&self) -> &'static str {
+        "one_entry_map"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return a map with one entry, whose key and value are the defaults of their types."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the keys and values in the map, not only whether it's empty."
+    }
+}
+
+impl Operator for OneEntryMap {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_map()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "::core::iter::once((Default::default(), Default::default())).collect()"
+    }
+}
+
+/// Return a set with one member, the default value of its type.
+pub struct OneEntrySet;
+
+impl Describe for OneEntrySet {
+    fn name(&self) -> &'static str {
+        "one_entry_set"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return a set with one member, the default value of its type."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the members of the set, not only whether it's empty."
+    }
+}
+
+impl Operator for OneEntrySet {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_set()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "::core::iter::once(Default::default()).collect()"
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright 2022 Martin Pool
+
+//! Change the condition of an `if` expression.
+
+use super::{ConditionOperator, Describe};
+
+/// Negate the condition of an `if` or `else if`, so that the other branch is taken.
+pub struct Negate;
+
+impl Describe for Negate {
+    fn name(&self) -> &'static str {
+        "negate_condition"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Negates the condition of an `if`, so that the other branch is taken."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that takes each branch of the `if`, and checks a result that depends on which branch was taken."
+    }
+}
+
+impl ConditionOperator for Negate {
+    fn replacement(&self, condition: &str) -> String {
+        format!("!({})", condition)
+    }
+}
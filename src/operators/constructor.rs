@@ -0,0 +1,197 @@
+// Copyright 2022 Martin Pool
+
+//! Return values made by constructors from functions returning types, such as `NonZeroUsize`
+//! or `Ipv4Addr`, that can't be made by `Default::default()`.
+//!
+//! Values for other types can be configured by type path: see
+//! [Operators::with_constructors](super::Operators::with_constructors).
+
+use super::impl_trait::path_ends_with;
+
+/// The name given to values made by constructors, whether built in or configured.
+pub const CONSTRUCTOR: &str = "constructor";
+
+/// Values for a standard library type that can only be made by calling one of its
+/// constructors.
+pub(crate) struct Constructors {
+    /// The path of the type from its module, such as `time::Duration`.
+    path: &'static str,
+
+    /// Expressions that make values of the type.
+    pub values: &'static [&'static str],
+
+    /// True if the type implements `Default`, so it's also mutated to `Default::default()`.
+    pub has_default: bool,
+
+    /// True if the type is in `std` but not `core`, so can't be made in a `no_std` crate.
+    std_only: bool,
+}
+
+/// The built-in constructors for types in the standard library.
+static BUILTIN: &[Constructors] = &[
+    Constructors {
+        path: "num::NonZeroU8",
+        values: &["::core::num::NonZeroU8::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroU16",
+        values: &["::core::num::NonZeroU16::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroU32",
+        values: &["::core::num::NonZeroU32::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroU64",
+        values: &["::core::num::NonZeroU64::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroU128",
+        values: &["::core::num::NonZeroU128::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroUsize",
+        values: &["::core::num::NonZeroUsize::new(1).unwrap()"],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroI8",
+        values: &[
+            "::core::num::NonZeroI8::new(1).unwrap()",
+            "::core::num::NonZeroI8::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroI16",
+        values: &[
+            "::core::num::NonZeroI16::new(1).unwrap()",
+            "::core::num::NonZeroI16::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroI32",
+        values: &[
+            "::core::num::NonZeroI32::new(1).unwrap()",
+            "::core::num::NonZeroI32::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroI64",
+        values: &[
+            "::core::num::NonZeroI64::new(1).unwrap()",
+            "::core::num::NonZeroI64::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroI128",
+        values: &[
+            "::core::num::NonZeroI128::new(1).unwrap()",
+            "::core::num::NonZeroI128::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "num::NonZeroIsize",
+        values: &[
+            "::core::num::NonZeroIsize::new(1).unwrap()",
+            "::core::num::NonZeroIsize::new(-1).unwrap()",
+        ],
+        has_default: false,
+        std_only: false,
+    },
+    Constructors {
+        path: "time::Duration",
+        values: &["::core::time::Duration::from_secs(1)"],
+        has_default: true,
+        std_only: false,
+    },
+    Constructors {
+        path: "time::Instant",
+        values: &["::std::time::Instant::now()"],
+        has_default: false,
+        std_only: true,
+    },
+    Constructors {
+        path: "time::SystemTime",
+        values: &[
+            "::std::time::SystemTime::UNIX_EPOCH",
+            "::std::time::SystemTime::now()",
+        ],
+        has_default: false,
+        std_only: true,
+    },
+    Constructors {
+        path: "net::Ipv4Addr",
+        values: &[
+            "::std::net::Ipv4Addr::UNSPECIFIED",
+            "::std::net::Ipv4Addr::LOCALHOST",
+        ],
+        has_default: false,
+        std_only: true,
+    },
+    Constructors {
+        path: "net::Ipv6Addr",
+        values: &[
+            "::std::net::Ipv6Addr::UNSPECIFIED",
+            "::std::net::Ipv6Addr::LOCALHOST",
+        ],
+        has_default: false,
+        std_only: true,
+    },
+    Constructors {
+        path: "net::IpAddr",
+        values: &[
+            "::std::net::IpAddr::V4(::std::net::Ipv4Addr::LOCALHOST)",
+            "::std::net::IpAddr::V6(::std::net::Ipv6Addr::LOCALHOST)",
+        ],
+        has_default: false,
+        std_only: true,
+    },
+    Constructors {
+        path: "net::SocketAddr",
+        values: &["::std::net::SocketAddr::from((::std::net::Ipv4Addr::LOCALHOST, 0))"],
+        has_default: false,
+        std_only: true,
+    },
+];
+
+/// Return the built-in constructors for a type, if it's one of the standard library types they
+/// make, written either by its name alone, like `Duration`, or with its module, like
+/// `std::time::Duration`.
+///
+/// Types in `std` but not `core` have no constructors in a `no_std` crate.
+pub(crate) fn builtin(path: &syn::Path, no_std: bool) -> Option<&'static Constructors> {
+    BUILTIN.iter().find(|constructors| {
+        !(no_std && constructors.std_only)
+            && path
+                .segments
+                .iter()
+                .all(|segment| segment.arguments.is_none())
+            && (path_ends_with(path, constructors.path)
+                || constructors
+                    .path
+                    .rsplit("::")
+                    .next()
+                    .is_some_and(|name| path.is_ident(name)))
+    })
+}
@@ -0,0 +1,46 @@
+// Copyright 2022 Martin Pool
+
+//! Return `Default::default()` from functions with no more specific operator.
+
+use super::{Describe, Function, Operator};
+
+/// Return [Default::default].
+///
+/// This is the fallback for functions whose return type isn't handled by any more specific
+/// operator.
+pub struct Default;
+
+impl Describe for Default {
+    fn name(&self) -> &'static str {
+        "default"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return the default value of its return type, such as zero or an empty collection."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return something other than the default value, that checks the value returned."
+    }
+}
+
+impl Operator for Default {
+    fn applies_to(&self, function: &Function) -> bool {
+        !(function.is_const()
+            || function.returns_unit()
+            || function.returns_never()
+            || function.returns_bool()
+            || function.returns_float()
+            || function.returns_small_int()
+            || function.returns_char()
+            || function.returns_string()
+            || function.returns_result()
+            || function.returns_option()
+            || function.returns_impl_trait()
+            || function.returns_reference())
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Default::default()"
+    }
+}
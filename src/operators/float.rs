@@ -0,0 +1,86 @@
+// Copyright 2022 Martin Pool
+
+//! Return constants from functions that return `f32` or `f64`.
+
+use super::{Describe, Function, Operator};
+
+/// Return `0.0`.
+pub struct Zero;
+
+impl Describe for Zero {
+    fn name(&self) -> &'static str {
+        "float_zero"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `0.0`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than zero, that checks the value returned."
+    }
+}
+
+impl Operator for Zero {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_float()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "0.0"
+    }
+}
+
+/// Return `1.0`.
+pub struct One;
+
+impl Describe for One {
+    fn name(&self) -> &'static str {
+        "float_one"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `1.0`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than one, that checks the value returned, rather than only that it's finite or positive."
+    }
+}
+
+impl Operator for One {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_float()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "1.0"
+    }
+}
+
+/// Return `-1.0`.
+pub struct MinusOne;
+
+impl Describe for MinusOne {
+    fn name(&self) -> &'static str {
+        "float_minus_one"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `-1.0`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than minus one, that checks the value returned, rather than only that it's finite."
+    }
+}
+
+impl Operator for MinusOne {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_float()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "-1.0"
+    }
+}
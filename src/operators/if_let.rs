@@ -0,0 +1,35 @@
+// Copyright 2022 Martin Pool
+
+//! Skip the code that runs when the pattern of an `if let` or `while let` matches.
+
+use super::{Describe, LetOperator};
+
+/// Replace an `if let` with its `else` branch, and a `while let` with an empty block, as if
+/// the pattern never matched.
+pub struct SkipMatch;
+
+impl Describe for SkipMatch {
+    fn name(&self) -> &'static str {
+        "skip_let_match"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces an `if let` with its `else` branch, or a `while let` with an empty block, as if the pattern never matched."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input that matches the pattern, and checks a result that depends on what's done with the bindings."
+    }
+}
+
+impl LetOperator for SkipMatch {
+    fn skips(&self, expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::If(syn::ExprIf { cond, .. })
+            | syn::Expr::While(syn::ExprWhile { cond, .. }) => {
+                matches!(**cond, syn::Expr::Let(_))
+            }
+            _ => false,
+        }
+    }
+}
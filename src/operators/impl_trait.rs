@@ -0,0 +1,56 @@
+// Copyright 2022 Martin Pool
+
+//! Return a value from functions that return `impl Trait`, which has no `Default`.
+//!
+//! Replacements for other traits can be configured by trait path: see
+//! [Operators::with_impl_trait_replacements](super::Operators::with_impl_trait_replacements).
+
+use super::{Describe, Function, Operator};
+
+/// The name given to replacements configured for an `impl Trait` return type.
+pub const CONFIGURED: &str = "impl_trait";
+
+/// Return an empty iterator.
+pub struct EmptyIter;
+
+impl Describe for EmptyIter {
+    fn name(&self) -> &'static str {
+        "empty_iter"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return an empty iterator."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return at least one item, that checks the items returned."
+    }
+}
+
+impl Operator for EmptyIter {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const()
+            && function.impl_trait_paths().any(|path| {
+                ["Iterator", "DoubleEndedIterator", "ExactSizeIterator"]
+                    .iter()
+                    .any(|name| path_ends_with(path, name))
+            })
+    }
+
+    fn replacement(&self) -> &'static str {
+        "::core::iter::empty()"
+    }
+}
+
+/// True if the path, ignoring any generic arguments, ends with the `::`-separated segments of
+/// `suffix`, so that `Iterator` matches `std::iter::Iterator<Item = u8>`.
+pub(crate) fn path_ends_with(path: &syn::Path, suffix: &str) -> bool {
+    let suffix: Vec<&str> = suffix.split("::").collect();
+    path.segments.len() >= suffix.len()
+        && path
+            .segments
+            .iter()
+            .rev()
+            .zip(suffix.iter().rev())
+            .all(|(segment, name)| segment.ident == name)
+}
@@ -0,0 +1,92 @@
+// Copyright 2022 Martin Pool
+
+//! Return constants from functions that return small integer types.
+//!
+//! For these types, `Default::default()` is often a value the function legitimately returns,
+//! so more distinct values are tried instead.
+//!
+//! `Default::default()` can't be called in a `const fn`, so there `0` and `1` are returned
+//! from functions returning any integer type.
+
+use super::{Describe, Function, Operator};
+
+/// Return `0`.
+pub struct Zero;
+
+impl Describe for Zero {
+    fn name(&self) -> &'static str {
+        "int_zero"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `0`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than zero, that checks the value returned."
+    }
+}
+
+impl Operator for Zero {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_small_int() || (function.is_const() && function.returns_int())
+    }
+
+    fn replacement(&self) -> &'static str {
+        "0"
+    }
+}
+
+/// Return `1`.
+pub struct One;
+
+impl Describe for One {
+    fn name(&self) -> &'static str {
+        "int_one"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `1`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than one, that checks the value returned."
+    }
+}
+
+impl Operator for One {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_small_int() || (function.is_const() && function.returns_int())
+    }
+
+    fn replacement(&self) -> &'static str {
+        "1"
+    }
+}
+
+/// Return `255` from functions returning `u8`.
+pub struct U8Max;
+
+impl Describe for U8Max {
+    fn name(&self) -> &'static str {
+        "u8_max"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `255`, the largest `u8`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a value other than 255, that checks the value returned."
+    }
+}
+
+impl Operator for U8Max {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_u8()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "255"
+    }
+}
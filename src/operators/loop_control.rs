@@ -0,0 +1,33 @@
+// Copyright 2022 Martin Pool
+
+//! Change `break` and `continue` within loops in function bodies.
+
+use super::{Describe, LoopControlOperator};
+
+/// Swap `break` for `continue`, and `continue` for `break`.
+pub struct SwapBreakContinue;
+
+impl Describe for SwapBreakContinue {
+    fn name(&self) -> &'static str {
+        "swap_break_continue"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces `break` with `continue`, or `continue` with `break`, so that a loop keeps going when it should stop, or stops when it should skip to the next iteration."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test where the loop reaches the `break` or `continue` while there are more iterations to come, and checks what happens in them."
+    }
+}
+
+impl LoopControlOperator for SwapBreakContinue {
+    fn replacement(&self, expr: &syn::Expr) -> Option<&'static str> {
+        match expr {
+            // A `break` with a value gives the value of a `loop`, which `continue` can't.
+            syn::Expr::Break(syn::ExprBreak { expr: None, .. }) => Some("continue"),
+            syn::Expr::Continue(_) => Some("break"),
+            _ => None,
+        }
+    }
+}
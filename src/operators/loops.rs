@@ -0,0 +1,59 @@
+// Copyright 2022 Martin Pool
+
+//! Stop loops within function bodies from running.
+
+use syn::visit::Visit;
+
+use super::{Describe, LoopOperator};
+
+/// Replace the condition of a `while` loop with `false`, and `loop` with `while false`, so
+/// that the body never runs.
+pub struct Skip;
+
+impl Describe for Skip {
+    fn name(&self) -> &'static str {
+        "skip_loop"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces the condition of a `while` loop with `false`, or `loop` with `while false`, so that the body of the loop never runs."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the loop runs at least once, and checks a result that depends on what the loop did."
+    }
+}
+
+impl LoopOperator for Skip {
+    fn replacement(&self, expr: &syn::Expr) -> Option<&'static str> {
+        match expr {
+            // `while let` has a pattern rather than a condition.
+            syn::Expr::While(expr_while) if !matches!(*expr_while.cond, syn::Expr::Let(_)) => {
+                Some("false")
+            }
+            // A `while` loop has no value, so a `loop` that breaks with one can't become one.
+            syn::Expr::Loop(expr_loop) if !breaks_with_value(&expr_loop.body) => {
+                Some("while false")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// True if there's a `break` with a value anywhere in `block`.
+///
+/// This is conservative: the `break` might be in a nested loop.
+fn breaks_with_value(block: &syn::Block) -> bool {
+    struct FindBreak(bool);
+
+    impl<'ast> Visit<'ast> for FindBreak {
+        fn visit_expr_break(&mut self, i: &'ast syn::ExprBreak) {
+            self.0 |= i.expr.is_some();
+            syn::visit::visit_expr_break(self, i);
+        }
+    }
+
+    let mut find = FindBreak(false);
+    find.visit_block(block);
+    find.0
+}
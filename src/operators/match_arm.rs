@@ -0,0 +1,100 @@
+// Copyright 2022 Martin Pool
+
+//! Delete arms from `match` expressions.
+
+use super::{Describe, MatchArmOperator};
+
+/// Delete one arm of a `match` at a time, when the match is still exhaustive without it.
+///
+/// Without type information, the only arms that can be known to be redundant for
+/// exhaustiveness are those with a guard, and those before a catch-all arm like `_`.
+/// Arms after a catch-all are unreachable, so deleting them would make no difference.
+pub struct DeleteArm;
+
+impl Describe for DeleteArm {
+    fn name(&self) -> &'static str {
+        "delete_match_arm"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Deletes an arm of a `match`, so that the values it matched fall through to a later arm."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of a value matched by that arm, that checks the result of the arm."
+    }
+}
+
+impl MatchArmOperator for DeleteArm {
+    fn arms_to_delete(&self, arms: &[syn::Arm]) -> Vec<usize> {
+        let catch_all = arms.iter().position(is_catch_all);
+        arms.iter()
+            .enumerate()
+            .take(catch_all.unwrap_or(arms.len()))
+            .filter(|(_, arm)| catch_all.is_some() || arm.guard.is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// True if an arm matches every value: it has no guard, and its pattern is `_` or binds a
+/// variable.
+///
+/// A lowercase name is taken to be a variable, since constants and enum variants are
+/// conventionally capitalized.
+fn is_catch_all(arm: &syn::Arm) -> bool {
+    arm.guard.is_none()
+        && match &arm.pat {
+            syn::Pat::Wild(_) => true,
+            syn::Pat::Ident(pat_ident) => {
+                pat_ident.subpat.is_none()
+                    && pat_ident
+                        .ident
+                        .to_string()
+                        .trim_start_matches("r#")
+                        .starts_with(|c: char| c.is_lowercase() || c == '_')
+            }
+            _ => false,
+        }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arms_to_delete(expr: syn::ExprMatch) -> Vec<usize> {
+        DeleteArm.arms_to_delete(&expr.arms)
+    }
+
+    #[test]
+    fn arms_before_a_catch_all_can_be_deleted() {
+        assert_eq!(
+            arms_to_delete(syn::parse_quote! {
+                match a { 1 => x, 2 => y, _ => z, 3 => w }
+            }),
+            [0, 1]
+        );
+        assert_eq!(
+            arms_to_delete(syn::parse_quote! {
+                match a { Some(1) => x, other => y }
+            }),
+            [0]
+        );
+    }
+
+    #[test]
+    fn only_guarded_arms_are_deleted_without_a_catch_all() {
+        assert_eq!(
+            arms_to_delete(syn::parse_quote! {
+                match a { Some(b) if b > 1 => x, Some(_) => y, None => z }
+            }),
+            [0]
+        );
+        assert_eq!(
+            arms_to_delete(syn::parse_quote! {
+                match a { b if b > 1 => x, Foo::Bar => y, Foo::Baz => z }
+            }),
+            [0]
+        );
+    }
+}
@@ -0,0 +1,2083 @@
+// Copyright 2022 Martin Pool
+
+//! Mutation operators: the kinds of change that can be applied to a function.
+//!
+//! Each operator is a self-contained type implementing [Operator], which decides whether it
+//! applies to a function and what code replaces the function body; [BinaryOperator], which
+//! replaces operators such as `+` within the body; [ConditionOperator], which changes the
+//! condition of an `if`; [MatchArmOperator], which deletes arms of a `match`;
+//! [LiteralOperator], which replaces literals such as `true`; [UnaryOperator], which deletes
+//! unary operators such as `-`; [RangeOperator], which changes the limits of ranges;
+//! [ClosureOperator], which replaces the bodies of closures; [StatementOperator], which deletes
+//! statements; [LoopControlOperator], which changes `break` and `continue`; [LoopOperator],
+//! which stops loops from running; [LetOperator], which skips the body of an `if let` or
+//! `while let`; or [ArgumentOperator], which swaps parameters of the same type. Every operator
+//! also implements [Describe], which gives its name and explains its mutants. The operators are
+//! collected in a registry, [Operators], from which they can be disabled by name.
+//!
+//! To add a new operator, implement [Operator] in a module here and add it to [ALL], or
+//! implement one of the other traits and add it to [BINARY], [CONDITION], [MATCH_ARM],
+//! [LITERAL], [UNARY], [RANGE], [CLOSURE], [STATEMENT], [LOOP_CONTROL], [LOOP], [LET], or
+//! [ARGUMENT].
+//!
+//! Domain-specific operators can also be provided without changing cargo-mutants, by external
+//! [Plugin] programs.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+mod argument;
+mod binary;
+mod boolean;
+mod character;
+mod closure;
+mod collection;
+mod condition;
+mod constructor;
+mod default;
+mod float;
+mod if_let;
+mod impl_trait;
+mod integer;
+mod loop_control;
+mod loops;
+mod match_arm;
+mod option;
+mod plugin;
+mod range;
+mod reference;
+mod result;
+mod statement;
+mod string;
+mod tuple;
+mod unary;
+mod unit;
+
+pub use plugin::{Plugin, PluginRequest};
+pub use result::ResultAlias;
+
+/// The name given to replacements from a `#[mutants::replace_with(...)]` attribute on a
+/// function.
+pub const REPLACE_WITH: &str = "replace_with";
+
+/// The name and explanation shared by every kind of operator.
+pub trait Describe: Sync {
+    /// A short, stable name for the operator, used to enable or disable it.
+    fn name(&self) -> &'static str;
+
+    /// Describe what the mutant does to the function, for people reading the results.
+    fn effect(&self) -> &'static str;
+
+    /// Describe what a test would need to assert to catch the mutant.
+    fn to_catch(&self) -> &'static str;
+}
+
+/// A kind of mutation that can be applied to a function.
+pub trait Operator: Describe {
+    /// True if this operator can mutate the given function.
+    fn applies_to(&self, function: &Function) -> bool;
+
+    /// Return the code that replaces the body of the function.
+    fn replacement(&self) -> &'static str;
+}
+
+/// A kind of mutation that replaces a binary operator, such as `+`, within a function body.
+pub trait BinaryOperator: Describe {
+    /// Return the operators that can replace `op`, or an empty list if this doesn't mutate it.
+    fn replacements(&self, op: &syn::BinOp) -> &'static [&'static str];
+}
+
+/// A kind of mutation that changes the condition of an `if` or `else if` within a function body.
+pub trait ConditionOperator: Describe {
+    /// Return the code that replaces a condition, given its original text.
+    fn replacement(&self, condition: &str) -> String;
+}
+
+/// A kind of mutation that deletes an arm of a `match` within a function body.
+pub trait MatchArmOperator: Describe {
+    /// Return the indexes of the arms that can be deleted, one at a time, while keeping the
+    /// match exhaustive.
+    fn arms_to_delete(&self, arms: &[syn::Arm]) -> Vec<usize>;
+}
+
+/// A kind of mutation that replaces a literal, such as `true`, within a function body.
+pub trait LiteralOperator: Describe {
+    /// Return the code that replaces `lit`, or None if this doesn't mutate it.
+    fn replacement(&self, lit: &syn::Lit) -> Option<&'static str>;
+}
+
+/// A kind of mutation that deletes a unary operator, such as `-`, within a function body.
+pub trait UnaryOperator: Describe {
+    /// True if this operator deletes `op`.
+    fn deletes(&self, op: &syn::UnOp) -> bool;
+}
+
+/// A kind of mutation that changes the limits of a range, such as `a..b`, in an expression or
+/// pattern within a function body.
+pub trait RangeOperator: Describe {
+    /// Return the limits that replace `limits`, or None if this doesn't mutate them.
+    fn replacement(&self, limits: &syn::RangeLimits) -> Option<&'static str>;
+}
+
+/// A kind of mutation that deletes statements from a block within a function body.
+pub trait StatementOperator: Describe {
+    /// Return the indexes of the statements in a block that can be deleted, one at a time.
+    fn statements_to_delete(&self, stmts: &[syn::Stmt]) -> Vec<usize>;
+}
+
+/// A kind of mutation that changes a `break` or `continue` within a loop in a function body.
+pub trait LoopControlOperator: Describe {
+    /// Return the keyword that replaces `expr`, which is a `break` or `continue` expression, or
+    /// None if this doesn't mutate it.
+    fn replacement(&self, expr: &syn::Expr) -> Option<&'static str>;
+}
+
+/// A kind of mutation that changes the head of a `while` or `loop` within a function body.
+pub trait LoopOperator: Describe {
+    /// Return the code that replaces the condition of `expr`, if it's a `while` loop, or the
+    /// `loop` keyword, if it's a `loop`, or None if this doesn't mutate it.
+    fn replacement(&self, expr: &syn::Expr) -> Option<&'static str>;
+}
+
+/// A kind of mutation that skips the body of an `if let` or `while let` within a function
+/// body, as if the pattern never matched.
+pub trait LetOperator: Describe {
+    /// True if this operator skips the body of `expr`, which is an `if` or `while` expression.
+    fn skips(&self, expr: &syn::Expr) -> bool;
+}
+
+/// A kind of mutation that swaps two parameters of a function, so that its body uses each
+/// argument where it used the other.
+pub trait ArgumentOperator: Describe {
+    /// Return the indexes, within the inputs of `sig`, of the pairs of parameters that can be
+    /// swapped, one pair at a time.
+    fn pairs_to_swap(&self, sig: &syn::Signature) -> Vec<(usize, usize)>;
+}
+
+/// A closure within a function body that might be mutated, as seen by a [ClosureOperator].
+pub struct Closure<'a> {
+    pub expr: &'a syn::ExprClosure,
+
+    /// The method that the closure is passed to as an argument, if any, such as `filter` in
+    /// `v.iter().filter(|x| ...)`.
+    pub method: Option<&'a syn::Ident>,
+}
+
+impl Closure<'_> {
+    /// True if the closure returns `bool`: either it's declared to, or it's passed to a method
+    /// that takes a predicate, like `filter` or `any`, or its body is obviously a `bool`, like a
+    /// comparison.
+    fn returns_bool(&self) -> bool {
+        match &self.expr.output {
+            syn::ReturnType::Type(_rarrow, typ) => {
+                matches!(&**typ, syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("bool"))
+            }
+            syn::ReturnType::Default => {
+                self.expr.asyncness.is_none()
+                    && (self
+                        .method
+                        .is_some_and(|method| PREDICATE_METHODS.iter().any(|name| method == name))
+                        || expr_is_bool(&self.expr.body))
+            }
+        }
+    }
+
+    /// True if the body of the closure is just the literal `value`, so that replacing it with
+    /// `value` would make no change.
+    fn body_is_bool_lit(&self, value: bool) -> bool {
+        matches!(&*self.expr.body, syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(lit), .. }) if lit.value == value)
+    }
+}
+
+/// Methods of the standard library whose closure argument returns `bool`.
+const PREDICATE_METHODS: &[&str] = &[
+    "all",
+    "any",
+    "dedup_by",
+    "filter",
+    "find",
+    "is_err_and",
+    "is_ok_and",
+    "is_some_and",
+    "partition",
+    "position",
+    "retain",
+    "retain_mut",
+    "rposition",
+    "skip_while",
+    "take_while",
+];
+
+/// True if the expression is obviously a `bool`: a comparison, `&&` or `||`, a `bool` literal,
+/// or the negation of one of those.
+fn expr_is_bool(expr: &syn::Expr) -> bool {
+    use syn::BinOp::*;
+    match expr {
+        syn::Expr::Binary(binary) => matches!(
+            binary.op,
+            Eq(_) | Ne(_) | Lt(_) | Le(_) | Gt(_) | Ge(_) | And(_) | Or(_)
+        ),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Bool(_),
+            ..
+        }) => true,
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Not(_),
+            expr,
+            ..
+        }) => expr_is_bool(expr),
+        syn::Expr::Paren(paren) => expr_is_bool(&paren.expr),
+        _ => false,
+    }
+}
+
+/// A kind of mutation that replaces the body of a closure within a function body.
+pub trait ClosureOperator: Describe {
+    /// Return the code that replaces the body of the closure, or None if this doesn't mutate
+    /// it.
+    fn replacement(&self, closure: &Closure) -> Option<&'static str>;
+}
+
+/// An explanation of a mutant, included in json output so that people new to mutation testing
+/// can understand what to do about a missed mutant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// The name of the operator that generated the mutant.
+    pub operator: String,
+
+    /// What the mutant does to the function.
+    pub effect: String,
+
+    /// What a test would need to assert to catch the mutant.
+    pub to_catch: String,
+}
+
+/// Explain the mutants generated by an operator or plugin, given its name.
+pub fn explain(operator: &str) -> Explanation {
+    let builtin = builtins().find(|(name, _, _)| *name == operator);
+    let (effect, to_catch) = match builtin {
+        Some((_name, effect, to_catch)) => (effect.to_owned(), to_catch.to_owned()),
+        None => (
+            format!(
+                "Replaces the function body with an expression suggested by the {} plugin.",
+                operator
+            ),
+            "A test of some input for which the replacement gives a different result from the original function.".to_owned(),
+        ),
+    };
+    Explanation {
+        operator: operator.to_owned(),
+        effect,
+        to_catch,
+    }
+}
+
+/// Return the name, effect, and what's needed to catch the mutants, of every built-in operator.
+fn builtins() -> impl Iterator<Item = (&'static str, &'static str, &'static str)> {
+    ALL.iter()
+        .map(|op| *op as &dyn Describe)
+        .chain([&reference::LeakDefault as &dyn Describe])
+        .chain(BINARY.iter().map(|op| *op as &dyn Describe))
+        .chain(CONDITION.iter().map(|op| *op as &dyn Describe))
+        .chain(MATCH_ARM.iter().map(|op| *op as &dyn Describe))
+        .chain(LITERAL.iter().map(|op| *op as &dyn Describe))
+        .chain(UNARY.iter().map(|op| *op as &dyn Describe))
+        .chain(RANGE.iter().map(|op| *op as &dyn Describe))
+        .chain(CLOSURE.iter().map(|op| *op as &dyn Describe))
+        .chain(STATEMENT.iter().map(|op| *op as &dyn Describe))
+        .chain(LOOP_CONTROL.iter().map(|op| *op as &dyn Describe))
+        .chain(LOOP.iter().map(|op| *op as &dyn Describe))
+        .chain(LET.iter().map(|op| *op as &dyn Describe))
+        .chain(ARGUMENT.iter().map(|op| *op as &dyn Describe))
+        .map(|op| (op.name(), op.effect(), op.to_catch()))
+        .chain(std::iter::once((
+            impl_trait::CONFIGURED,
+            "Replaces the function body with a value configured for its `impl Trait` return type.",
+            "A test of some input for which the configured value gives a different result from the original function.",
+        )))
+        .chain(std::iter::once((
+            tuple::ELEMENT,
+            "Makes the function return a tuple with one element replaced by a value for its type, and the others by their defaults.",
+            "A test of some input for which the function should return something else in that element, that checks every element of the tuple.",
+        )))
+        .chain(std::iter::once((
+            result::OK_VALUE,
+            "Makes the function always succeed, returning `Ok` with a value for its success type, such as `Ok(true)` or `Ok(\"xyzzy\".into())`.",
+            "A test of some input for which the function should return an error, or should succeed with a different value, that checks the value inside the `Ok`.",
+        )))
+        .chain(std::iter::once((
+            constructor::CONSTRUCTOR,
+            "Makes the function always return a value made by a constructor of its return type, such as `NonZeroUsize::new(1).unwrap()` or `Ipv4Addr::LOCALHOST`, for types that have no `Default`.",
+            "A test of some input for which the function should return a different value, that checks the value returned.",
+        )))
+        .chain(std::iter::once((
+            REPLACE_WITH,
+            "Replaces the function body with a value given by its `#[mutants::replace_with(...)]` attribute.",
+            "A test of some input for which the given value is a different result from the original function.",
+        )))
+}
+
+/// A function that might be mutated, as seen by an [Operator].
+pub struct Function<'a> {
+    /// The function's signature.
+    pub sig: &'a syn::Signature,
+
+    /// True if the function is in a `no_std` crate.
+    pub no_std: bool,
+
+    /// Aliases for `Result`, declared in the function's file or configured, that are mutated
+    /// like a `Result`.
+    pub result_aliases: &'a [ResultAlias],
+}
+
+impl<'a> Function<'a> {
+    /// Return the code that replaces the function body, given the replacement from an
+    /// operator.
+    ///
+    /// The operators choose a replacement of the [output type](Self::output_type), which is
+    /// then wrapped to match the declared return type: for example a function returning
+    /// `impl Future<Output = Box<T>>` is mutated to return `async { Box::new(replacement) }`.
+    pub fn replacement_body(&self, replacement: &str) -> String {
+        let wrappers = match &self.sig.output {
+            syn::ReturnType::Type(_rarrow, typ) => unwrap_type(typ).0,
+            syn::ReturnType::Default => Vec::new(),
+        };
+        wrappers
+            .iter()
+            .rev()
+            .fold(replacement.to_owned(), |body, wrapper| match wrapper {
+                Wrapper::Future => format!("async {{ {} }}", body),
+                Wrapper::Pointer(path) => format!(
+                    "{}{}::new({})",
+                    if path.leading_colon.is_some() {
+                        "::"
+                    } else {
+                        ""
+                    },
+                    path.segments
+                        .iter()
+                        .map(|segment| segment.ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                    body
+                ),
+            })
+    }
+
+    /// Return the type of the value that the function body produces, looking through any
+    /// [Wrapper]s, or None if the function has no declared return type.
+    ///
+    /// This is `T` for a function returning `impl Future<Output = T>` or `Box<T>`, as well as
+    /// for an `async fn` declared to return `T`.
+    fn output_type(&self) -> Option<&'a syn::Type> {
+        match &self.sig.output {
+            syn::ReturnType::Type(_rarrow, typ) => Some(unwrap_type(typ).1),
+            syn::ReturnType::Default => None,
+        }
+    }
+
+    /// Return the path of the output type, if it is a plain path like `bool` or
+    /// `io::Result<()>`.
+    fn return_type_path(&self) -> Option<&'a syn::Path> {
+        match self.output_type()? {
+            syn::Type::Path(syn::TypePath { path, .. }) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// True if the function is a `const fn`, so its body can only call other `const fn`s.
+    ///
+    /// Replacements such as `Default::default()` and `"".into()` aren't `const`, so would
+    /// never build.
+    fn is_const(&self) -> bool {
+        self.sig.constness.is_some()
+    }
+
+    /// True if the function has no declared return type, or its output is `()`.
+    fn returns_unit(&self) -> bool {
+        match self.output_type() {
+            None => true,
+            Some(syn::Type::Tuple(tuple)) => tuple.elems.is_empty(),
+            Some(_) => false,
+        }
+    }
+
+    /// Return the types of the elements of a tuple output type, or None if the output isn't a
+    /// tuple, or is `()`.
+    fn tuple_element_types(&self) -> Option<Vec<&'a syn::Type>> {
+        match self.output_type()? {
+            syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+                Some(tuple.elems.iter().collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the paths of the traits in an `impl Trait` output type, such as `Iterator` and
+    /// `Send` in `impl Iterator<Item = u8> + Send`, or of a trait object inside a smart pointer,
+    /// such as `Display` in `Box<dyn Display>`, or nothing for other types.
+    fn impl_trait_paths(&self) -> impl Iterator<Item = &'a syn::Path> {
+        let bounds = match self.output_type() {
+            Some(syn::Type::ImplTrait(impl_trait)) => Some(&impl_trait.bounds),
+            Some(syn::Type::TraitObject(trait_object)) => Some(&trait_object.bounds),
+            _ => None,
+        };
+        bounds
+            .into_iter()
+            .flatten()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(trait_bound) => Some(&trait_bound.path),
+                _ => None,
+            })
+    }
+
+    /// True if the function's output is an `impl Trait` type or a boxed trait object, which
+    /// can only be made by code that knows the trait.
+    fn returns_impl_trait(&self) -> bool {
+        matches!(
+            self.output_type(),
+            Some(syn::Type::ImplTrait(_) | syn::Type::TraitObject(_))
+        )
+    }
+
+    /// True if the function returns a reference that can't be made by `Default::default()`.
+    ///
+    /// References to `str` and slices implement `Default`, but references to other types
+    /// don't, and there's usually nothing they could point to that lives long enough.
+    fn returns_reference(&self) -> bool {
+        match self.output_type() {
+            Some(syn::Type::Reference(reference)) => {
+                !type_is_str(&reference.elem) && !matches!(*reference.elem, syn::Type::Slice(_))
+            }
+            _ => false,
+        }
+    }
+
+    /// True if the function returns `bool`.
+    fn returns_bool(&self) -> bool {
+        self.return_type_path()
+            .is_some_and(|path| path.is_ident("bool"))
+    }
+
+    /// True if the function returns the never type, `!`, so it has no value to return.
+    pub(crate) fn returns_never(&self) -> bool {
+        matches!(self.output_type(), Some(syn::Type::Never(_)))
+    }
+
+    /// True if the function returns `f32` or `f64`.
+    fn returns_float(&self) -> bool {
+        self.return_type_path()
+            .is_some_and(|path| path.is_ident("f32") || path.is_ident("f64"))
+    }
+
+    /// True if the function returns `u8`, `i8`, `u16`, or `i16`, for which the default value,
+    /// zero, is often a value the function legitimately returns.
+    fn returns_small_int(&self) -> bool {
+        self.return_type_path().is_some_and(|path| {
+            ["u8", "i8", "u16", "i16"]
+                .iter()
+                .any(|name| path.is_ident(name))
+        })
+    }
+
+    /// True if the function returns any primitive integer type.
+    fn returns_int(&self) -> bool {
+        self.return_type_path().is_some_and(|path| {
+            [
+                "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128", "i128", "usize",
+                "isize",
+            ]
+            .iter()
+            .any(|name| path.is_ident(name))
+        })
+    }
+
+    /// True if the function returns `u8`.
+    fn returns_u8(&self) -> bool {
+        self.return_type_path()
+            .is_some_and(|path| path.is_ident("u8"))
+    }
+
+    /// True if the function returns `char`.
+    fn returns_char(&self) -> bool {
+        self.return_type_path()
+            .is_some_and(|path| path.is_ident("char"))
+    }
+
+    /// True if the function returns `&str`, with any lifetime, which a string literal can be
+    /// returned as directly.
+    fn returns_str(&self) -> bool {
+        matches!(self.output_type(), Some(syn::Type::Reference(reference))
+            if reference.mutability.is_none() && type_is_str(&reference.elem))
+    }
+
+    /// True if the function returns text that can be made from a string literal with `.into()`:
+    /// a `String`, a `&str` with any lifetime, a `Cow<str>`, or a `Box<str>`, `Rc<str>`, or
+    /// `Arc<str>`.
+    ///
+    /// In a `no_std` crate, `String` isn't in the prelude, so it's either `alloc::string::String`
+    /// or some other string type, such as a fixed-capacity string, that may not be convertible
+    /// from arbitrary text.
+    fn returns_string(&self) -> bool {
+        match self.output_type() {
+            Some(syn::Type::Reference(reference)) => {
+                reference.mutability.is_none() && type_is_str(&reference.elem)
+            }
+            Some(syn::Type::Path(syn::TypePath { qself: None, path })) => {
+                (!self.no_std && path.is_ident("String"))
+                    || path_is_cow_str(path)
+                    || pointer_target(path).is_some_and(type_is_str)
+            }
+            _ => false,
+        }
+    }
+
+    /// True if the function returns a type named `Result`, or one of its
+    /// [aliases](Self::result_aliases).
+    fn returns_result(&self) -> bool {
+        self.return_type_path()
+            .is_some_and(|path| path_is_result(path, self.result_aliases))
+    }
+
+    /// Return the success type of a function returning a `Result`, including an alias like
+    /// `io::Result<T>`, or None if it doesn't return a `Result`.
+    fn result_ok_type(&self) -> Option<&'a syn::Type> {
+        let path = self
+            .return_type_path()
+            .filter(|path| path_is_result(path, self.result_aliases))?;
+        match &path.segments.last()?.arguments {
+            syn::PathArguments::AngleBracketed(args) => {
+                args.args.iter().find_map(|arg| match arg {
+                    syn::GenericArgument::Type(typ) => Some(typ),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the error type of a function returning `Result<T, E>`, or of one returning an
+    /// alias declared in its file that fixes the error type, or None if it doesn't return a
+    /// `Result`, or the error type is implied by an alias like `io::Result<T>`.
+    fn result_error_type(&self) -> Option<&'a syn::Type> {
+        let path = self
+            .return_type_path()
+            .filter(|path| path_is_result(path, self.result_aliases))?;
+        let segment = path.segments.last()?;
+        let explicit = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(typ) => Some(typ),
+                    _ => None,
+                })
+                .nth(1),
+            _ => None,
+        };
+        // Aliases are only looked up by their bare name, since `io::Result` isn't the same as
+        // a `Result` alias declared in this file.
+        explicit.or_else(|| {
+            self.result_aliases
+                .iter()
+                .filter(|_| path.segments.len() == 1)
+                .find(|alias| segment.ident == alias.name)
+                .and_then(|alias| alias.error_type.as_ref())
+        })
+    }
+
+    /// True if the function returns a `HashMap` or `BTreeMap`.
+    fn returns_map(&self) -> bool {
+        self.return_type_path().is_some_and(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == "HashMap" || segment.ident == "BTreeMap")
+        })
+    }
+
+    /// True if the function returns a `HashSet` or `BTreeSet`.
+    fn returns_set(&self) -> bool {
+        self.return_type_path().is_some_and(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == "HashSet" || segment.ident == "BTreeSet")
+        })
+    }
+
+    /// True if the function returns a type named `Option`.
+    fn returns_option(&self) -> bool {
+        self.return_type_path().is_some_and(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Option")
+        })
+    }
+}
+
+/// A type wrapped around the value that a function body produces, which replacements are
+/// wrapped in too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wrapper<'a> {
+    /// `impl Future<Output = T>`, made by an `async` block.
+    Future,
+    /// `Box<T>`, `Rc<T>`, or `Arc<T>`, made by `new`, with the path as it was written.
+    Pointer(&'a syn::Path),
+}
+
+/// Look through any [Wrapper]s around a type, and return them, outermost first, along with
+/// the type inside.
+///
+/// Pointers to `str` or slices aren't looked through, since they can't be made by `new`, but
+/// pointers to trait objects are, so that `Box<dyn Iterator<Item = T>>` can be mutated like
+/// `impl Iterator<Item = T>`.
+fn unwrap_type(mut typ: &syn::Type) -> (Vec<Wrapper<'_>>, &syn::Type) {
+    let mut wrappers = Vec::new();
+    loop {
+        if let Some(output) = future_output_type(typ) {
+            wrappers.push(Wrapper::Future);
+            typ = output;
+        } else if let Some((path, target)) = match typ {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => {
+                pointer_target(path).map(|target| (path, target))
+            }
+            _ => None,
+        }
+        .filter(|(_, target)| !type_is_str(target) && !matches!(target, syn::Type::Slice(_)))
+        {
+            wrappers.push(Wrapper::Pointer(path));
+            typ = target;
+        } else {
+            return (wrappers, typ);
+        }
+    }
+}
+
+/// If the path is `Box<T>`, `Rc<T>`, or `Arc<T>`, possibly with a module prefix, return `T`.
+fn pointer_target(path: &syn::Path) -> Option<&syn::Type> {
+    let segment = path.segments.last()?;
+    if !["Box", "Rc", "Arc"]
+        .iter()
+        .any(|name| segment.ident == name)
+    {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first()? {
+                syn::GenericArgument::Type(typ) => Some(typ),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If the type is `impl Future<Output = T>`, possibly with other bounds such as `Send`,
+/// return `T`.
+fn future_output_type(typ: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::ImplTrait(impl_trait) = typ else {
+        return None;
+    };
+    impl_trait.bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(trait_bound) => {
+            let segment = trait_bound.path.segments.last()?;
+            if segment.ident != "Future" {
+                return None;
+            }
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => {
+                    args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Binding(binding) if binding.ident == "Output" => {
+                            Some(&binding.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+fn type_is_str(typ: &syn::Type) -> bool {
+    matches!(typ, syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("str"))
+}
+
+/// True if the path is `Cow<str>`, possibly with a lifetime and a module prefix.
+fn path_is_cow_str(path: &syn::Path) -> bool {
+    path.segments.last().is_some_and(|segment| {
+        segment.ident == "Cow"
+            && match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .any(|arg| matches!(arg, syn::GenericArgument::Type(typ) if type_is_str(typ))),
+                _ => false,
+            }
+    })
+}
+
+/// True if the path names `Result`, like `Result<T, E>` or `io::Result<T>`, or one of the
+/// `aliases`.
+fn path_is_result(path: &syn::Path, aliases: &[ResultAlias]) -> bool {
+    path.segments.last().is_some_and(|segment| {
+        segment.ident == "Result" || aliases.iter().any(|alias| segment.ident == alias.name)
+    })
+}
+
+/// All the built-in operators, in the order their mutants are generated for each function.
+pub static ALL: &[&dyn Operator] = &[
+    &unit::Unit,
+    &boolean::True,
+    &boolean::False,
+    &float::Zero,
+    &float::One,
+    &float::MinusOne,
+    &integer::Zero,
+    &integer::One,
+    &integer::U8Max,
+    &character::Nul,
+    &character::X,
+    &string::EmptyString,
+    &string::Xyzzy,
+    &string::EmptyStr,
+    &string::XyzzyStr,
+    &result::OkDefault,
+    &result::ErrDefault,
+    &result::ErrAnyhow,
+    &option::None,
+    &option::SomeDefault,
+    &impl_trait::EmptyIter,
+    &collection::OneEntryMap,
+    &collection::OneEntrySet,
+    &default::Default,
+];
+
+/// All the built-in operators that replace binary operators, in the order their mutants are
+/// generated for each expression.
+pub static BINARY: &[&dyn BinaryOperator] =
+    &[&binary::Arithmetic, &binary::Comparison, &binary::Logical];
+
+/// All the built-in operators that change the condition of an `if`.
+pub static CONDITION: &[&dyn ConditionOperator] = &[&condition::Negate];
+
+/// All the built-in operators that delete arms of a `match`.
+pub static MATCH_ARM: &[&dyn MatchArmOperator] = &[&match_arm::DeleteArm];
+
+/// All the built-in operators that replace literals.
+pub static LITERAL: &[&dyn LiteralOperator] = &[&boolean::FlipLiteral];
+
+/// All the built-in operators that delete unary operators.
+pub static UNARY: &[&dyn UnaryOperator] = &[&unary::DeleteNeg, &unary::DeleteNot];
+
+/// All the built-in operators that change the limits of ranges.
+pub static RANGE: &[&dyn RangeOperator] = &[&range::Boundary];
+
+/// All the built-in operators that replace the bodies of closures.
+pub static CLOSURE: &[&dyn ClosureOperator] = &[&closure::True, &closure::False];
+
+/// All the built-in operators that delete statements.
+pub static STATEMENT: &[&dyn StatementOperator] = &[&statement::DeleteStatement];
+
+/// All the built-in operators that change `break` and `continue`.
+pub static LOOP_CONTROL: &[&dyn LoopControlOperator] = &[&loop_control::SwapBreakContinue];
+
+/// All the built-in operators that change the heads of loops.
+pub static LOOP: &[&dyn LoopOperator] = &[&loops::Skip];
+
+/// All the built-in operators that skip the bodies of `if let` and `while let`.
+pub static LET: &[&dyn LetOperator] = &[&if_let::SkipMatch];
+
+/// All the built-in operators that swap the parameters of functions.
+pub static ARGUMENT: &[&dyn ArgumentOperator] = &[&argument::Swap];
+
+/// A registry of the enabled operators, and any plugins.
+#[derive(Clone)]
+pub struct Operators {
+    ops: Vec<&'static dyn Operator>,
+    binary_ops: Vec<&'static dyn BinaryOperator>,
+    condition_ops: Vec<&'static dyn ConditionOperator>,
+    match_arm_ops: Vec<&'static dyn MatchArmOperator>,
+    literal_ops: Vec<&'static dyn LiteralOperator>,
+    unary_ops: Vec<&'static dyn UnaryOperator>,
+    range_ops: Vec<&'static dyn RangeOperator>,
+    closure_ops: Vec<&'static dyn ClosureOperator>,
+    statement_ops: Vec<&'static dyn StatementOperator>,
+    loop_control_ops: Vec<&'static dyn LoopControlOperator>,
+    loop_ops: Vec<&'static dyn LoopOperator>,
+    let_ops: Vec<&'static dyn LetOperator>,
+    argument_ops: Vec<&'static dyn ArgumentOperator>,
+    /// Replacements for functions returning `impl Trait`, by the path of the trait.
+    impl_trait_replacements: BTreeMap<String, Vec<String>>,
+    /// Make tuples with one element replaced by a value for its type.
+    tuple_elements: bool,
+    /// Make `Ok` values from the values for the success type of a `Result`.
+    ok_values: bool,
+    /// Make values of standard library types that have no `Default` by calling their
+    /// constructors.
+    constructors: bool,
+    /// Values of other types, by the path of the type.
+    configured_constructors: BTreeMap<String, Vec<String>>,
+    /// Use the replacements from `#[mutants::replace_with(...)]` attributes.
+    replace_with: bool,
+    /// Configured aliases for `Result`, which are mutated like a `Result`.
+    result_aliases: Vec<ResultAlias>,
+    plugins: Vec<Plugin>,
+}
+
+impl Operators {
+    /// All the built-in operators.
+    pub fn all() -> Operators {
+        Operators {
+            ops: ALL.to_vec(),
+            binary_ops: BINARY.to_vec(),
+            condition_ops: CONDITION.to_vec(),
+            match_arm_ops: MATCH_ARM.to_vec(),
+            literal_ops: LITERAL.to_vec(),
+            unary_ops: UNARY.to_vec(),
+            range_ops: RANGE.to_vec(),
+            closure_ops: CLOSURE.to_vec(),
+            statement_ops: STATEMENT.to_vec(),
+            loop_control_ops: LOOP_CONTROL.to_vec(),
+            loop_ops: LOOP.to_vec(),
+            let_ops: LET.to_vec(),
+            argument_ops: ARGUMENT.to_vec(),
+            impl_trait_replacements: BTreeMap::new(),
+            tuple_elements: true,
+            ok_values: true,
+            constructors: true,
+            configured_constructors: BTreeMap::new(),
+            replace_with: true,
+            result_aliases: Vec::new(),
+            plugins: Vec::new(),
+        }
+    }
+
+    /// No operators at all, for when the structure of the code is needed but not its mutants.
+    pub fn none() -> Operators {
+        Operators {
+            ops: Vec::new(),
+            binary_ops: Vec::new(),
+            condition_ops: Vec::new(),
+            match_arm_ops: Vec::new(),
+            literal_ops: Vec::new(),
+            unary_ops: Vec::new(),
+            range_ops: Vec::new(),
+            closure_ops: Vec::new(),
+            statement_ops: Vec::new(),
+            loop_control_ops: Vec::new(),
+            loop_ops: Vec::new(),
+            let_ops: Vec::new(),
+            argument_ops: Vec::new(),
+            impl_trait_replacements: BTreeMap::new(),
+            tuple_elements: false,
+            ok_values: false,
+            constructors: false,
+            configured_constructors: BTreeMap::new(),
+            replace_with: false,
+            result_aliases: Vec::new(),
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Return these operators, plus some plugins.
+    ///
+    /// A plugin named like a built-in operator, such as `default`, is renamed to
+    /// `plugin:default`, so that its mutants are explained and skipped separately.
+    pub fn with_plugins(mut self, plugins: Vec<Plugin>) -> Operators {
+        self.plugins.extend(plugins.into_iter().map(|plugin| {
+            if builtins().any(|(name, _, _)| name == plugin.name()) {
+                plugin.namespaced()
+            } else {
+                plugin
+            }
+        }));
+        self
+    }
+
+    /// Return these operators, plus, if `leak` is true, [reference::LeakDefault], which returns
+    /// `Box::leak(Box::new(Default::default()))` from functions returning references.
+    ///
+    /// This is off by default because the mutants leak memory, and often don't build, since
+    /// the referenced type may not implement `Default`.
+    pub fn leak_references(mut self, leak: bool) -> Operators {
+        if leak {
+            self.ops.push(&reference::LeakDefault);
+        }
+        self
+    }
+
+    /// Return these operators, plus replacements for functions returning `impl Trait`, keyed by
+    /// the path of the trait.
+    ///
+    /// The path can be abbreviated by leaving out leading modules, so `Display` or
+    /// `fmt::Display` match a function returning `impl std::fmt::Display`.
+    pub fn with_impl_trait_replacements(
+        mut self,
+        replacements: BTreeMap<String, Vec<String>>,
+    ) -> Operators {
+        self.impl_trait_replacements.extend(replacements);
+        self
+    }
+
+    /// Return these operators, plus values for functions returning other types that have no
+    /// `Default`, keyed by the path of the type, which are used in place of
+    /// `Default::default()` and any built-in values for the type.
+    ///
+    /// As for [Operators::with_impl_trait_replacements], the path can be abbreviated by leaving
+    /// out leading modules.
+    pub fn with_constructors(mut self, constructors: BTreeMap<String, Vec<String>>) -> Operators {
+        self.configured_constructors.extend(constructors);
+        self
+    }
+
+    /// Return these operators, treating types with the given names, such as `Fallible`, as
+    /// aliases for `Result`, so that functions returning them get `Ok` and `Err` values rather
+    /// than `Default::default()`.
+    ///
+    /// Aliases declared in the same file as the function are also recognized without being
+    /// configured.
+    pub fn with_result_aliases(mut self, names: &[String]) -> Operators {
+        self.result_aliases
+            .extend(names.iter().map(|name| ResultAlias::named(name)));
+        self
+    }
+
+    /// Return the configured aliases for `Result`.
+    pub fn result_aliases(&self) -> &[ResultAlias] {
+        &self.result_aliases
+    }
+
+    /// Return an error if the name isn't that of a built-in operator or of one of these
+    /// plugins.
+    pub fn check_name(&self, name: &str) -> Result<()> {
+        let known = || {
+            builtins()
+                .map(|(name, _, _)| name)
+                .chain(self.plugins.iter().map(|plugin| plugin.name()))
+        };
+        if known().any(|known| known == name) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "unknown mutation operator {:?}: expected one of {}",
+                name,
+                known().collect::<Vec<_>>().join(", ")
+            ))
+        }
+    }
+
+    /// Return these operators, without those with the given names.
+    ///
+    /// Returns an error if any name isn't a known operator.
+    pub fn without(&self, names: &[String]) -> Result<Operators> {
+        let known = || builtins().map(|(name, _, _)| name);
+        for name in names {
+            if !known().any(|known| known == name) {
+                return Err(anyhow!(
+                    "unknown mutation operator {:?}: expected one of {}",
+                    name,
+                    known().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+        let enabled = |op_name: &str| !names.iter().any(|name| name == op_name);
+        Ok(Operators {
+            ops: without_names(&self.ops, names),
+            binary_ops: without_names(&self.binary_ops, names),
+            condition_ops: without_names(&self.condition_ops, names),
+            match_arm_ops: without_names(&self.match_arm_ops, names),
+            literal_ops: without_names(&self.literal_ops, names),
+            unary_ops: without_names(&self.unary_ops, names),
+            range_ops: without_names(&self.range_ops, names),
+            closure_ops: without_names(&self.closure_ops, names),
+            statement_ops: without_names(&self.statement_ops, names),
+            loop_control_ops: without_names(&self.loop_control_ops, names),
+            loop_ops: without_names(&self.loop_ops, names),
+            let_ops: without_names(&self.let_ops, names),
+            argument_ops: without_names(&self.argument_ops, names),
+            impl_trait_replacements: if enabled(impl_trait::CONFIGURED) {
+                self.impl_trait_replacements.clone()
+            } else {
+                BTreeMap::new()
+            },
+            tuple_elements: self.tuple_elements && enabled(tuple::ELEMENT),
+            ok_values: self.ok_values && enabled(result::OK_VALUE),
+            constructors: self.constructors && enabled(constructor::CONSTRUCTOR),
+            configured_constructors: if enabled(constructor::CONSTRUCTOR) {
+                self.configured_constructors.clone()
+            } else {
+                BTreeMap::new()
+            },
+            replace_with: self.replace_with && enabled(REPLACE_WITH),
+            result_aliases: self.result_aliases.clone(),
+            plugins: self.plugins.clone(),
+        })
+    }
+
+    /// Return the enabled operators that apply to a function.
+    pub fn applicable<'s>(
+        &'s self,
+        function: &'s Function,
+    ) -> impl Iterator<Item = &'static dyn Operator> + 's {
+        self.ops
+            .iter()
+            .copied()
+            .filter(move |op| op.applies_to(function))
+    }
+
+    /// Return the names of the enabled operators that apply to a function, paired with the code
+    /// that replaces its body, followed by any replacements configured for its `impl Trait`
+    /// return type, values made by constructors of its return type, tuples with one element
+    /// replaced, and `Ok` values made from the values for the success type of a `Result`.
+    pub fn fn_replacements(&self, function: &Function) -> Vec<(String, String)> {
+        self.values(function)
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), function.replacement_body(&value)))
+            .collect()
+    }
+
+    /// Return the replacements for a function marked with `#[mutants::replace_with(...)]`,
+    /// whose values are used instead of those from the operators, wrapped to match the
+    /// declared return type as for [Operators::fn_replacements].
+    ///
+    /// If there are no values, or the `replace_with` operator is disabled, this is the same as
+    /// [Operators::fn_replacements].
+    pub fn fn_replacements_with(
+        &self,
+        function: &Function,
+        values: &[String],
+    ) -> Vec<(String, String)> {
+        if !self.replace_with || values.is_empty() {
+            return self.fn_replacements(function);
+        }
+        values
+            .iter()
+            .map(|value| (REPLACE_WITH.to_owned(), function.replacement_body(value)))
+            .collect()
+    }
+
+    /// Return the names of the operators that make values of the function's
+    /// [output type](Function::output_type), paired with the values, before they're wrapped to
+    /// match the declared return type.
+    fn values(&self, function: &Function) -> Vec<(&'static str, String)> {
+        let configured = function.impl_trait_paths().flat_map(|path| {
+            self.impl_trait_replacements
+                .iter()
+                .filter(move |(trait_path, _)| impl_trait::path_ends_with(path, trait_path))
+                .flat_map(|(_, replacements)| replacements)
+        });
+        let mut values = self
+            .applicable(function)
+            .map(|op| (op.name(), op.replacement().to_owned()))
+            .chain(configured.map(|replacement| (impl_trait::CONFIGURED, replacement.clone())))
+            .collect::<Vec<_>>();
+        if let Some(path) = function
+            .return_type_path()
+            .filter(|_| self.constructors && !function.is_const())
+        {
+            let configured = self
+                .configured_constructors
+                .iter()
+                .filter(|(type_path, _)| impl_trait::path_ends_with(path, type_path))
+                .flat_map(|(_, values)| values.iter().cloned())
+                .collect::<Vec<_>>();
+            let (constructed, has_default) = if !configured.is_empty() {
+                (configured, false)
+            } else if let Some(builtin) = constructor::builtin(path, function.no_std) {
+                (
+                    builtin
+                        .values
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect(),
+                    builtin.has_default,
+                )
+            } else {
+                (Vec::new(), true)
+            };
+            if !has_default {
+                // `Default::default()` wouldn't build.
+                values.retain(|(name, _)| *name != "default");
+            }
+            values.extend(
+                constructed
+                    .into_iter()
+                    .map(|value| (constructor::CONSTRUCTOR, value)),
+            );
+        }
+        if let Some(elements) = function
+            .tuple_element_types()
+            .filter(|_| self.tuple_elements && !function.is_const())
+        {
+            for (index, element) in elements.iter().enumerate() {
+                for (name, value) in self.type_values(element, function) {
+                    // These are the same as the default for the whole tuple.
+                    if name != "default" && name != "unit" {
+                        values.push((
+                            tuple::ELEMENT,
+                            tuple::with_element(elements.len(), index, &value),
+                        ));
+                    }
+                }
+            }
+        }
+        if let Some(ok_type) = function.result_ok_type().filter(|_| self.ok_values) {
+            let ok_values = self.type_values(ok_type, function);
+            let has_default = ok_values.iter().any(|(name, _)| *name == "default");
+            let ok_values = ok_values
+                .into_iter()
+                .filter(|(name, _)| *name != "default" && *name != "unit")
+                .map(|(_name, value)| (result::OK_VALUE, format!("Ok({})", value)))
+                .collect::<Vec<_>>();
+            // Types with their own values, other than the default, such as `bool`, usually have
+            // the default among them, so `Ok(Default::default())` would be the same as one of
+            // these, and is replaced by them.
+            let replace_default = !ok_values.is_empty() && !has_default;
+            let range = match values.iter().position(|(name, _)| *name == "ok_default") {
+                Some(index) if replace_default => index..index + 1,
+                Some(index) => index + 1..index + 1,
+                None => 0..0,
+            };
+            values.splice(range, ok_values);
+        }
+        values
+    }
+
+    /// Return the names of the operators that make values of a type, paired with the values,
+    /// as for a function returning that type, which is `const` if `outer` is.
+    fn type_values(&self, typ: &syn::Type, outer: &Function) -> Vec<(&'static str, String)> {
+        let constness = outer.sig.constness;
+        let sig: syn::Signature = syn::parse_quote! { #constness fn f() -> #typ };
+        let function = Function {
+            sig: &sig,
+            no_std: outer.no_std,
+            result_aliases: outer.result_aliases,
+        };
+        self.values(&function)
+            .into_iter()
+            .map(|(name, value)| (name, function.replacement_body(&value)))
+            .collect()
+    }
+
+    /// Return the names of the enabled operators that can replace a binary operator, paired
+    /// with each of their replacements.
+    pub fn binary_replacements<'s>(
+        &'s self,
+        op: &'s syn::BinOp,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.binary_ops.iter().flat_map(move |binary_op| {
+            binary_op
+                .replacements(op)
+                .iter()
+                .map(move |replacement| (binary_op.name(), *replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that can change the condition of an `if`,
+    /// paired with the code that replaces it.
+    pub fn condition_replacements<'s>(
+        &'s self,
+        condition: &'s str,
+    ) -> impl Iterator<Item = (&'static str, String)> + 's {
+        self.condition_ops
+            .iter()
+            .map(move |op| (op.name(), op.replacement(condition)))
+    }
+
+    /// Return the names of the enabled operators that can delete arms of a `match`, paired
+    /// with the index of each arm they delete.
+    pub fn match_arm_deletions<'s>(
+        &'s self,
+        arms: &'s [syn::Arm],
+    ) -> impl Iterator<Item = (&'static str, usize)> + 's {
+        self.match_arm_ops.iter().flat_map(move |op| {
+            op.arms_to_delete(arms)
+                .into_iter()
+                .map(move |index| (op.name(), index))
+        })
+    }
+
+    /// Return the names of the enabled operators that can delete statements from a block,
+    /// paired with the index of each statement they delete.
+    pub fn statement_deletions<'s>(
+        &'s self,
+        stmts: &'s [syn::Stmt],
+    ) -> impl Iterator<Item = (&'static str, usize)> + 's {
+        self.statement_ops.iter().flat_map(move |op| {
+            op.statements_to_delete(stmts)
+                .into_iter()
+                .map(move |index| (op.name(), index))
+        })
+    }
+
+    /// Return the names of the enabled operators that can replace a literal, paired with the
+    /// code that replaces it.
+    pub fn literal_replacements<'s>(
+        &'s self,
+        lit: &'s syn::Lit,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.literal_ops.iter().filter_map(move |op| {
+            op.replacement(lit)
+                .map(|replacement| (op.name(), replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that delete a unary operator.
+    pub fn unary_deletions<'s>(
+        &'s self,
+        op: &'s syn::UnOp,
+    ) -> impl Iterator<Item = &'static str> + 's {
+        self.unary_ops
+            .iter()
+            .filter(move |unary_op| unary_op.deletes(op))
+            .map(|unary_op| unary_op.name())
+    }
+
+    /// Return the names of the enabled operators that can change the limits of a range, paired
+    /// with the limits that replace them.
+    pub fn range_replacements<'s>(
+        &'s self,
+        limits: &'s syn::RangeLimits,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.range_ops.iter().filter_map(move |op| {
+            op.replacement(limits)
+                .map(|replacement| (op.name(), replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that can replace the body of a closure, paired
+    /// with the code that replaces it.
+    pub fn closure_replacements<'s>(
+        &'s self,
+        closure: &'s Closure,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.closure_ops.iter().filter_map(move |op| {
+            op.replacement(closure)
+                .map(|replacement| (op.name(), replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that can change a `break` or `continue`,
+    /// paired with the keyword that replaces it.
+    pub fn loop_control_replacements<'s>(
+        &'s self,
+        expr: &'s syn::Expr,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.loop_control_ops.iter().filter_map(move |op| {
+            op.replacement(expr)
+                .map(|replacement| (op.name(), replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that can change the head of a `while` or
+    /// `loop`, paired with the code that replaces it.
+    pub fn loop_replacements<'s>(
+        &'s self,
+        expr: &'s syn::Expr,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 's {
+        self.loop_ops.iter().filter_map(move |op| {
+            op.replacement(expr)
+                .map(|replacement| (op.name(), replacement))
+        })
+    }
+
+    /// Return the names of the enabled operators that skip the body of an `if let` or
+    /// `while let`.
+    pub fn let_skips<'s>(&'s self, expr: &'s syn::Expr) -> impl Iterator<Item = &'static str> + 's {
+        self.let_ops
+            .iter()
+            .filter(move |op| op.skips(expr))
+            .map(|op| op.name())
+    }
+
+    /// Return the names of the enabled operators that swap parameters of a function, paired
+    /// with the indexes of the parameters in its signature.
+    pub fn argument_swaps<'s>(
+        &'s self,
+        sig: &'s syn::Signature,
+    ) -> impl Iterator<Item = (&'static str, (usize, usize))> + 's {
+        self.argument_ops.iter().flat_map(move |op| {
+            op.pairs_to_swap(sig)
+                .into_iter()
+                .map(move |pair| (op.name(), pair))
+        })
+    }
+
+    /// Return the plugins, which are run on every function.
+    pub fn plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    /// Return the names of the enabled operators and plugins.
+    pub fn names(&self) -> Vec<&str> {
+        self.ops
+            .iter()
+            .map(|op| op.name())
+            .chain(self.binary_ops.iter().map(|op| op.name()))
+            .chain(self.condition_ops.iter().map(|op| op.name()))
+            .chain(self.match_arm_ops.iter().map(|op| op.name()))
+            .chain(self.literal_ops.iter().map(|op| op.name()))
+            .chain(self.unary_ops.iter().map(|op| op.name()))
+            .chain(self.range_ops.iter().map(|op| op.name()))
+            .chain(self.closure_ops.iter().map(|op| op.name()))
+            .chain(self.statement_ops.iter().map(|op| op.name()))
+            .chain(self.loop_control_ops.iter().map(|op| op.name()))
+            .chain(self.loop_ops.iter().map(|op| op.name()))
+            .chain(self.let_ops.iter().map(|op| op.name()))
+            .chain(self.argument_ops.iter().map(|op| op.name()))
+            .chain((!self.impl_trait_replacements.is_empty()).then_some(impl_trait::CONFIGURED))
+            .chain(self.tuple_elements.then_some(tuple::ELEMENT))
+            .chain(self.ok_values.then_some(result::OK_VALUE))
+            .chain(self.constructors.then_some(constructor::CONSTRUCTOR))
+            .chain(self.replace_with.then_some(REPLACE_WITH))
+            .chain(self.plugins.iter().map(|plugin| plugin.name()))
+            .collect()
+    }
+}
+
+/// Return the operators whose names aren't among `names`.
+fn without_names<T: Describe + ?Sized>(ops: &[&'static T], names: &[String]) -> Vec<&'static T> {
+    ops.iter()
+        .copied()
+        .filter(|op| !names.iter().any(|name| name == op.name()))
+        .collect()
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        Operators::all()
+    }
+}
+
+impl fmt::Debug for Operators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// A function with this signature, in a crate that uses `std` and declares no `Result`
+    /// aliases.
+    fn function(sig: &syn::Signature) -> Function<'_> {
+        Function {
+            sig,
+            no_std: false,
+            result_aliases: &[],
+        }
+    }
+
+    fn applicable_names(operators: &Operators, item: syn::ItemFn) -> Vec<&'static str> {
+        let function = function(&item.sig);
+        operators
+            .applicable(&function)
+            .map(|op| op.name())
+            .collect_vec()
+    }
+
+    #[test]
+    fn path_is_result() {
+        let path: syn::Path = syn::parse_quote! { Result<(), ()> };
+        assert!(super::path_is_result(&path, &[]));
+        let path: syn::Path = syn::parse_quote! { Fallible<()> };
+        assert!(!super::path_is_result(&path, &[]));
+        assert!(super::path_is_result(
+            &path,
+            &[ResultAlias::named("Fallible")]
+        ));
+    }
+
+    #[test]
+    fn operators_are_explained() {
+        for (name, _, _) in builtins() {
+            let explanation = explain(name);
+            assert_eq!(explanation.operator, name);
+            assert!(explanation.effect.ends_with('.'), "{}", name);
+            assert!(explanation.to_catch.starts_with("A test"), "{}", name);
+        }
+        assert!(explain("rounding").effect.contains("the rounding plugin"));
+    }
+
+    #[test]
+    fn operator_names_are_unique() {
+        let operators = Operators::all();
+        let names = operators.names();
+        assert_eq!(names.iter().unique().count(), names.len());
+    }
+
+    #[test]
+    fn builtin_names_are_unique() {
+        let names = builtins().map(|(name, _, _)| name).collect_vec();
+        assert_eq!(names.iter().unique().count(), names.len());
+    }
+
+    #[test]
+    fn plugins_named_like_builtins_are_namespaced() {
+        let operators = Operators::all().with_plugins(vec![
+            Plugin::new(Path::new("tools/default.sh")),
+            Plugin::new(Path::new("tools/rounding.sh")),
+        ]);
+        assert_eq!(
+            operators
+                .plugins()
+                .iter()
+                .map(|plugin| plugin.name())
+                .collect_vec(),
+            ["plugin:default", "rounding"]
+        );
+        assert!(explain("plugin:default")
+            .effect
+            .contains("the plugin:default plugin"));
+    }
+
+    #[test]
+    fn operators_are_chosen_by_return_type() {
+        let all = Operators::all();
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() {} }),
+            ["unit"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> bool { true } }),
+            ["true", "false"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> String { todo!() } }),
+            ["empty_string", "xyzzy"]
+        );
+        for item in [
+            syn::parse_quote! { fn f(&self) -> &str { todo!() } },
+            syn::parse_quote! { fn f() -> &'static str { todo!() } },
+            syn::parse_quote! { fn f<'a>() -> Cow<'a, str> { todo!() } },
+            syn::parse_quote! { fn f() -> std::borrow::Cow<'static, str> { todo!() } },
+        ] {
+            assert_eq!(applicable_names(&all, item), ["empty_string", "xyzzy"]);
+        }
+        for item in [
+            syn::parse_quote! { fn f(&mut self) -> &mut str { todo!() } },
+            syn::parse_quote! { fn f() -> Cow<'static, [u8]> { todo!() } },
+        ] {
+            assert_eq!(applicable_names(&all, item), ["default"]);
+        }
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> io::Result<()> { todo!() } }
+            ),
+            ["ok_default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> Result<u8, MyError> { todo!() } }
+            ),
+            ["ok_default", "err_default"]
+        );
+        for item in [
+            syn::parse_quote! { fn f() -> anyhow::Result<()> { todo!() } },
+            syn::parse_quote! { fn f() -> Result<(), anyhow::Error> { todo!() } },
+        ] {
+            assert_eq!(applicable_names(&all, item), ["ok_default", "err_anyhow"]);
+        }
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> std::option::Option<u8> { todo!() } }
+            ),
+            ["none", "some_default"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> &[u8] { todo!() } }),
+            ["default"]
+        );
+        for item in [
+            syn::parse_quote! { fn f() -> f32 { todo!() } },
+            syn::parse_quote! { const fn f(x: f64) -> f64 { x } },
+        ] {
+            assert_eq!(
+                applicable_names(&all, item),
+                ["float_zero", "float_one", "float_minus_one"]
+            );
+        }
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> u8 { todo!() } }),
+            ["int_zero", "int_one", "u8_max"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> i16 { todo!() } }),
+            ["int_zero", "int_one"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> char { todo!() } }),
+            ["char_nul", "char_x"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> u32 { todo!() } }),
+            ["default"]
+        );
+    }
+
+    #[test]
+    fn async_functions_are_mutated_by_their_output_type() {
+        let all = Operators::all();
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { async fn f() -> bool { true } }),
+            ["true", "false"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { async fn f() {} }),
+            ["unit"]
+        );
+        let item: syn::ItemFn = syn::parse_quote! {
+            fn f(name: &str) -> impl Future<Output = String> + Send + '_ {
+                async move { name.to_owned() }
+            }
+        };
+        assert_eq!(
+            applicable_names(&all, item.clone()),
+            ["empty_string", "xyzzy"]
+        );
+        assert_eq!(
+            function(&item.sig).replacement_body("\"\".into()"),
+            "async { \"\".into() }"
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> impl std::future::Future<Output = ()> { async {} } }
+            ),
+            ["unit"]
+        );
+        let item: syn::ItemFn = syn::parse_quote! { async fn f() -> u32 { 1 } };
+        assert_eq!(
+            function(&item.sig).replacement_body("Default::default()"),
+            "Default::default()"
+        );
+    }
+
+    #[test]
+    fn impl_trait_returns_get_known_or_configured_values() {
+        let all = Operators::all();
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f(a: &[u8]) -> impl Iterator<Item = &u8> + '_ { a.iter() } }
+            ),
+            ["empty_iter"]
+        );
+        let display: syn::ItemFn = syn::parse_quote! { fn f() -> impl std::fmt::Display { 1 } };
+        assert_eq!(applicable_names(&all, display.clone()), [] as [&str; 0]);
+        let display = function(&display.sig);
+        let configured = Operators::all().with_impl_trait_replacements(
+            [
+                ("fmt::Display".to_owned(), vec!["0".to_owned()]),
+                ("Display".to_owned(), vec!["\"\"".to_owned()]),
+                ("Debug".to_owned(), vec!["()".to_owned()]),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            configured.fn_replacements(&display),
+            [
+                ("impl_trait".to_owned(), "\"\"".to_owned()),
+                ("impl_trait".to_owned(), "0".to_owned()),
+            ]
+        );
+        assert!(configured
+            .without(&["impl_trait".to_owned()])
+            .unwrap()
+            .fn_replacements(&display)
+            .is_empty());
+        let future: syn::ItemFn = syn::parse_quote! {
+            fn f() -> impl Future<Output = impl Iterator<Item = u8>> { async { [1].into_iter() } }
+        };
+        assert_eq!(
+            all.fn_replacements(&function(&future.sig)),
+            [(
+                "empty_iter".to_owned(),
+                "async { ::core::iter::empty() }".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn smart_pointer_returns_wrap_the_replacement() {
+        let replacements = |item: syn::ItemFn| {
+            Operators::all()
+                .fn_replacements(&function(&item.sig))
+                .into_iter()
+                .map(|(_name, replacement)| replacement)
+                .collect_vec()
+        };
+        assert_eq!(
+            replacements(syn::parse_quote! { fn f() -> Box<Option<u32>> { todo!() } }),
+            ["Box::new(None)", "Box::new(Some(Default::default()))"]
+        );
+        assert_eq!(
+            replacements(syn::parse_quote! { fn f() -> std::sync::Arc<Vec<u8>> { todo!() } }),
+            ["std::sync::Arc::new(Default::default())"]
+        );
+        assert_eq!(
+            replacements(syn::parse_quote! { fn f() -> Rc<str> { todo!() } }),
+            ["\"\".into()", "\"xyzzy\".into()"]
+        );
+        assert_eq!(
+            replacements(syn::parse_quote! { fn f() -> Box<[u8]> { todo!() } }),
+            ["Default::default()"]
+        );
+        assert_eq!(
+            replacements(
+                syn::parse_quote! { fn f() -> Box<dyn Iterator<Item = u8> + Send> { todo!() } }
+            ),
+            ["Box::new(::core::iter::empty())"]
+        );
+        assert!(
+            replacements(syn::parse_quote! { fn f() -> Arc<dyn Display> { todo!() } }).is_empty()
+        );
+        assert_eq!(
+            replacements(
+                syn::parse_quote! { fn f() -> impl Future<Output = Rc<bool>> { todo!() } }
+            ),
+            ["async { Rc::new(true) }", "async { Rc::new(false) }"]
+        );
+    }
+
+    #[test]
+    fn tuples_get_one_element_replaced_at_a_time() {
+        let replacements = |operators: &Operators, item: syn::ItemFn| {
+            operators.fn_replacements(&function(&item.sig))
+        };
+        let all = Operators::all();
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> (bool, Box<u8>, u32) { todo!() } }
+            )
+            .iter()
+            .map(|(name, replacement)| format!("{name}: {replacement}"))
+            .collect_vec(),
+            [
+                "default: Default::default()",
+                "tuple_element: (true, Default::default(), Default::default())",
+                "tuple_element: (false, Default::default(), Default::default())",
+                "tuple_element: (Default::default(), Box::new(0), Default::default())",
+                "tuple_element: (Default::default(), Box::new(1), Default::default())",
+                "tuple_element: (Default::default(), Box::new(255), Default::default())",
+            ]
+        );
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> impl Future<Output = (String,)> { todo!() } }
+            ),
+            [
+                ("default", "async { Default::default() }"),
+                ("tuple_element", "async { (\"\".into(),) }"),
+                ("tuple_element", "async { (\"xyzzy\".into(),) }"),
+            ]
+            .map(|(name, replacement)| (name.to_owned(), replacement.to_owned()))
+        );
+        assert_eq!(
+            replacements(
+                &all.without(&["tuple_element".to_owned()]).unwrap(),
+                syn::parse_quote! { fn f() -> (bool, bool) { todo!() } }
+            ),
+            [("default".to_owned(), "Default::default()".to_owned())]
+        );
+        assert!(replacements(
+            &all,
+            syn::parse_quote! { const fn f() -> (bool, bool) { (true, true) } }
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn results_get_ok_values_for_their_success_type() {
+        let replacements = |operators: &Operators, item: syn::ItemFn| {
+            operators
+                .fn_replacements(&function(&item.sig))
+                .iter()
+                .map(|(name, replacement)| format!("{name}: {replacement}"))
+                .collect_vec()
+        };
+        let all = Operators::all();
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> Result<bool, String> { todo!() } }
+            ),
+            [
+                "ok_value: Ok(true)",
+                "ok_value: Ok(false)",
+                "err_default: Err(Default::default())",
+            ]
+        );
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> anyhow::Result<Box<str>> { todo!() } }
+            ),
+            [
+                "ok_value: Ok(\"\".into())",
+                "ok_value: Ok(\"xyzzy\".into())",
+                "err_anyhow: Err(::anyhow::anyhow!(\"mutated!\"))",
+            ]
+        );
+        // The default of a map isn't among its other values, so it's kept.
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> io::Result<HashMap<u32, u32>> { todo!() } }
+            ),
+            [
+                "ok_default: Ok(Default::default())",
+                "ok_value: Ok(::core::iter::once((Default::default(), Default::default())).collect())",
+            ]
+        );
+        for item in [
+            syn::parse_quote! { fn f() -> io::Result<()> { todo!() } },
+            syn::parse_quote! { fn f() -> io::Result<Vec<u8>> { todo!() } },
+        ] {
+            assert_eq!(
+                replacements(&all, item),
+                ["ok_default: Ok(Default::default())"]
+            );
+        }
+        assert_eq!(
+            replacements(
+                &all.without(&["ok_value".to_owned()]).unwrap(),
+                syn::parse_quote! { fn f() -> io::Result<bool> { todo!() } }
+            ),
+            ["ok_default: Ok(Default::default())"]
+        );
+    }
+
+    #[test]
+    fn types_without_default_get_values_from_their_constructors() {
+        let replacements = |operators: &Operators, item: syn::ItemFn, no_std: bool| {
+            operators
+                .fn_replacements(&Function {
+                    no_std,
+                    ..function(&item.sig)
+                })
+                .iter()
+                .map(|(name, replacement)| format!("{name}: {replacement}"))
+                .collect_vec()
+        };
+        let all = Operators::all();
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> NonZeroUsize { todo!() } },
+                false
+            ),
+            ["constructor: ::core::num::NonZeroUsize::new(1).unwrap()"]
+        );
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> std::net::Ipv4Addr { todo!() } },
+                false
+            ),
+            [
+                "constructor: ::std::net::Ipv4Addr::UNSPECIFIED",
+                "constructor: ::std::net::Ipv4Addr::LOCALHOST",
+            ]
+        );
+        // `Duration` has a default, zero, which is kept.
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> Box<time::Duration> { todo!() } },
+                true
+            ),
+            [
+                "default: Box::new(Default::default())",
+                "constructor: Box::new(::core::time::Duration::from_secs(1))",
+            ]
+        );
+        assert_eq!(
+            replacements(
+                &all,
+                syn::parse_quote! { fn f() -> io::Result<NonZeroU8> { todo!() } },
+                false
+            ),
+            ["ok_value: Ok(::core::num::NonZeroU8::new(1).unwrap())"]
+        );
+        // Types only in `std` can't be made in a `no_std` crate, and types from other crates
+        // with the same name aren't made at all.
+        for (item, no_std) in [
+            (syn::parse_quote! { fn f() -> Instant { todo!() } }, true),
+            (
+                syn::parse_quote! { fn f() -> chrono::Duration { todo!() } },
+                false,
+            ),
+        ] {
+            assert_eq!(
+                replacements(&all, item, no_std),
+                ["default: Default::default()"]
+            );
+        }
+        let configured = all.clone().with_constructors(
+            [
+                (
+                    "Ipv4Addr".to_owned(),
+                    vec!["[10, 0, 0, 1].into()".to_owned()],
+                ),
+                (
+                    "chrono::NaiveDate".to_owned(),
+                    vec!["NaiveDate::MIN".to_owned()],
+                ),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            replacements(
+                &configured,
+                syn::parse_quote! { fn f() -> Ipv4Addr { todo!() } },
+                false
+            ),
+            ["constructor: [10, 0, 0, 1].into()"]
+        );
+        assert_eq!(
+            replacements(
+                &configured,
+                syn::parse_quote! { fn f() -> chrono::NaiveDate { todo!() } },
+                false
+            ),
+            ["constructor: NaiveDate::MIN"]
+        );
+        assert!(configured
+            .without(&["constructor".to_owned()])
+            .unwrap()
+            .fn_replacements(&function(&syn::parse_quote! { fn f() -> NonZeroU32 }))
+            .iter()
+            .all(|(name, _)| name == "default"));
+    }
+
+    #[test]
+    fn maps_and_sets_get_one_entry_and_empty_collections() {
+        let all = Operators::all();
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> HashMap<String, u32> { todo!() } }
+            ),
+            ["one_entry_map", "default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> std::collections::BTreeMap<u8, u8> { todo!() } }
+            ),
+            ["one_entry_map", "default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> HashSet<u32> { todo!() } }
+            ),
+            ["one_entry_set", "default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { fn f() -> BTreeSet<&str> { todo!() } }
+            ),
+            ["one_entry_set", "default"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> Vec<u32> { todo!() } }),
+            ["default"]
+        );
+    }
+
+    #[test]
+    fn references_are_only_mutated_when_leaking_is_enabled() {
+        let item: syn::ItemFn = syn::parse_quote! { fn f(&self) -> &Config { &self.config } };
+        assert_eq!(
+            applicable_names(&Operators::all(), item.clone()),
+            [] as [&str; 0]
+        );
+        assert_eq!(
+            applicable_names(&Operators::all().leak_references(true), item),
+            ["leak_default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &Operators::all().leak_references(true),
+                syn::parse_quote! { fn f(&mut self) -> &mut Vec<u8> { &mut self.buf } }
+            ),
+            ["leak_default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &Operators::all().leak_references(true),
+                syn::parse_quote! { fn f(&self) -> &[u8] { &self.buf } }
+            ),
+            ["default"]
+        );
+        assert_eq!(
+            applicable_names(
+                &Operators::all().leak_references(true),
+                syn::parse_quote! { const fn f() -> &'static u8 { &1 } }
+            ),
+            [] as [&str; 0]
+        );
+    }
+
+    #[test]
+    fn const_fns_only_get_const_replacements() {
+        let all = Operators::all();
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { const fn f() -> bool { true } }),
+            ["true", "false"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { const fn f() {} }),
+            ["unit"]
+        );
+        assert_eq!(
+            applicable_names(
+                &all,
+                syn::parse_quote! { const fn f() -> Option<u8> { None } }
+            ),
+            ["none"]
+        );
+        for item in [
+            syn::parse_quote! { const fn f() -> String { String::new() } },
+            syn::parse_quote! { const fn f() -> Result<(), ()> { Ok(()) } },
+            syn::parse_quote! { const fn f() -> Vec<u8> { Vec::new() } },
+        ] {
+            assert!(applicable_names(&all, item).is_empty());
+        }
+    }
+
+    #[test]
+    fn const_fns_get_literals_instead_of_defaults() {
+        let all = Operators::all();
+        let values = |item: syn::ItemFn| {
+            let function = function(&item.sig);
+            all.values(&function)
+        };
+        assert_eq!(
+            values(syn::parse_quote! { const fn f() -> u32 { 1 } }),
+            [("int_zero", "0".to_owned()), ("int_one", "1".to_owned())]
+        );
+        assert_eq!(
+            values(syn::parse_quote! { const fn f() -> &'static str { "a" } }),
+            [
+                ("empty_str", "\"\"".to_owned()),
+                ("xyzzy_str", "\"xyzzy\"".to_owned())
+            ]
+        );
+        assert_eq!(
+            values(syn::parse_quote! { const fn f() -> Result<bool, ()> { Ok(true) } }),
+            [
+                (result::OK_VALUE, "Ok(true)".to_owned()),
+                (result::OK_VALUE, "Ok(false)".to_owned())
+            ]
+        );
+        // Outside a const fn, larger integers and strings still get their usual values.
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> u32 { 1 } }),
+            ["default"]
+        );
+        assert_eq!(
+            applicable_names(&all, syn::parse_quote! { fn f() -> &'static str { "a" } }),
+            ["empty_string", "xyzzy"]
+        );
+    }
+
+    #[test]
+    fn functions_returning_never_have_no_replacements() {
+        let all = Operators::all();
+        assert!(applicable_names(&all, syn::parse_quote! { fn f() -> ! { panic!() } }).is_empty());
+        assert!(
+            applicable_names(&all, syn::parse_quote! { async fn f() -> ! { loop {} } }).is_empty()
+        );
+    }
+
+    #[test]
+    fn operators_can_be_disabled_by_name() {
+        let ops = Operators::all()
+            .without(&["xyzzy".to_owned(), "false".to_owned()])
+            .unwrap();
+        assert_eq!(
+            applicable_names(&ops, syn::parse_quote! { fn f() -> bool { true } }),
+            ["true"]
+        );
+        assert_eq!(
+            applicable_names(&ops, syn::parse_quote! { fn f() -> String { todo!() } }),
+            ["empty_string"]
+        );
+        let err = Operators::all()
+            .without(&["wibble".to_owned()])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown mutation operator \"wibble\""));
+    }
+
+    #[test]
+    fn comparisons_are_replaced_at_their_boundary() {
+        let replacements = |operators: &Operators, op: syn::BinOp| {
+            operators
+                .binary_replacements(&op)
+                .map(|(name, replacement)| format!("{} {}", name, replacement))
+                .collect::<Vec<_>>()
+        };
+        let all = Operators::all();
+        assert_eq!(
+            replacements(&all, syn::parse_quote! { < }),
+            ["comparison <="]
+        );
+        assert_eq!(
+            replacements(&all, syn::parse_quote! { >= }),
+            ["comparison >"]
+        );
+        assert_eq!(
+            replacements(&all, syn::parse_quote! { == }),
+            ["comparison !="]
+        );
+        assert_eq!(
+            replacements(&all, syn::parse_quote! { != }),
+            ["comparison =="]
+        );
+        assert_eq!(
+            replacements(&all, syn::parse_quote! { + }),
+            ["arithmetic -", "arithmetic *"]
+        );
+        let ops = all.without(&["comparison".to_owned()]).unwrap();
+        assert!(replacements(&ops, syn::parse_quote! { < }).is_empty());
+    }
+
+    #[test]
+    fn logical_connectives_are_swapped() {
+        let all = Operators::all();
+        assert_eq!(
+            all.binary_replacements(&syn::parse_quote! { && })
+                .collect::<Vec<_>>(),
+            [("logical", "||")]
+        );
+        assert_eq!(
+            all.binary_replacements(&syn::parse_quote! { || })
+                .collect::<Vec<_>>(),
+            [("logical", "&&")]
+        );
+        // Bitwise operators aren't changed.
+        assert_eq!(all.binary_replacements(&syn::parse_quote! { & }).count(), 0);
+    }
+}
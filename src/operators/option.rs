@@ -0,0 +1,60 @@
+// Copyright 2022 Martin Pool
+
+//! Return `None` or `Some` from functions that return an `Option`.
+
+use super::{Describe, Function, Operator};
+
+/// Return `None`.
+pub struct None;
+
+impl Describe for None {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `None`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return `Some`, that checks it does."
+    }
+}
+
+impl Operator for None {
+    fn applies_to(&self, function: &Function) -> bool {
+        // `None` can be evaluated at compile time, so this also applies to `const fn`s.
+        function.returns_option()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "None"
+    }
+}
+
+/// Return `Some(Default::default())`.
+pub struct SomeDefault;
+
+impl Describe for SomeDefault {
+    fn name(&self) -> &'static str {
+        "some_default"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return `Some` with the default value of its inner type, such as zero or an empty collection."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return `None`, or a test that checks the value inside the `Some`."
+    }
+}
+
+impl Operator for SomeDefault {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_option()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Some(Default::default())"
+    }
+}
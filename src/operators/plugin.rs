@@ -0,0 +1,192 @@
+// Copyright 2022 Martin Pool
+
+//! External programs that suggest domain-specific replacements for function bodies.
+//!
+//! A plugin is run once for each function that might be mutated. It's given a JSON
+//! [PluginRequest] describing the function on stdin, and should print a JSON object with a
+//! `replacements` list of Rust expressions, each of which becomes a mutant that replaces the
+//! function body. A plugin that has nothing to suggest for a function prints
+//! `{"replacements": []}`.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use path_slash::PathExt;
+use serde::{Deserialize, Serialize};
+
+use crate::textedit::LineColumn;
+
+/// An external program that suggests replacements for function bodies.
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    /// The path of the plugin executable.
+    path: PathBuf,
+
+    /// The name of the operator, from the executable's file name.
+    name: String,
+}
+
+/// A function sent to a plugin.
+#[derive(Debug, Serialize)]
+pub struct PluginRequest<'a> {
+    /// The path of the source file, relative to the tree root, with forward slashes.
+    pub file: &'a str,
+
+    /// The name of the function, including the names of enclosing modules and types.
+    pub function: &'a str,
+
+    /// The function's signature, as Rust syntax.
+    pub signature: &'a str,
+
+    /// The return type of the function, as Rust syntax, or empty if it returns `()`.
+    pub return_type: &'a str,
+
+    /// The current text of the function body, including the braces.
+    pub body: &'a str,
+
+    /// The position of the start of the body.
+    pub start: LineColumn,
+
+    /// The position of the end of the body.
+    pub end: LineColumn,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginResponse {
+    replacements: Vec<String>,
+}
+
+impl Plugin {
+    pub fn new(path: &Path) -> Plugin {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_slash_lossy());
+        Plugin {
+            path: path.to_owned(),
+            name,
+        }
+    }
+
+    /// The name of the plugin, which is used as the name of the operator for its mutants.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return this plugin with its name prefixed by `plugin:`, so that its mutants can't be
+    /// mistaken for those of a built-in operator with the same name.
+    pub(super) fn namespaced(self) -> Plugin {
+        Plugin {
+            name: format!("plugin:{}", self.name),
+            ..self
+        }
+    }
+
+    /// Run the plugin to get the replacements it suggests for one function.
+    pub fn replacements(&self, request: &PluginRequest) -> Result<Vec<String>> {
+        let path = self.path.to_slash_lossy();
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("run plugin {}", path))?;
+        let input = serde_json::to_vec(request)?;
+        match child
+            .stdin
+            .take()
+            .expect("child has stdin")
+            .write_all(&input)
+        {
+            // A plugin that doesn't need to read the request may exit without reading it.
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => (),
+            r => r.with_context(|| format!("write request to plugin {}", path))?,
+        }
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("wait for plugin {}", path))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "plugin {} failed on {} in {} ({}): {}",
+                path,
+                request.function,
+                request.file,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            ));
+        }
+        let response: PluginResponse = serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("parse response from plugin {}", path))?;
+        Ok(response.replacements)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn write_script(dir: &Path, name: &str, script: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn request() -> PluginRequest<'static> {
+        PluginRequest {
+            file: "src/money.rs",
+            function: "Money::round",
+            signature: "fn round (& self) -> Decimal",
+            return_type: "-> Decimal",
+            body: "{ self.0.round_dp(2) }",
+            start: LineColumn {
+                line: 3,
+                column: 35,
+            },
+            end: LineColumn {
+                line: 3,
+                column: 56,
+            },
+        }
+    }
+
+    #[test]
+    fn plugin_gets_request_and_returns_replacements() {
+        let tmp = tempfile::tempdir().unwrap();
+        let request_copy = tmp.path().join("request.json");
+        let path = write_script(
+            tmp.path(),
+            "rounding.sh",
+            &format!(
+                "#!/bin/sh\ncat >{}\necho '{{\"replacements\": [\"self.0.trunc()\"]}}'\n",
+                request_copy.display()
+            ),
+        );
+        let plugin = Plugin::new(&path);
+        assert_eq!(plugin.name(), "rounding");
+        assert_eq!(plugin.replacements(&request()).unwrap(), ["self.0.trunc()"]);
+        let sent: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(request_copy).unwrap()).unwrap();
+        assert_eq!(sent["function"], "Money::round");
+        assert_eq!(sent["body"], "{ self.0.round_dp(2) }");
+        assert_eq!(sent["start"]["line"], 3);
+    }
+
+    #[test]
+    fn failing_plugin_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_script(tmp.path(), "broken", "#!/bin/sh\necho oops >&2\nexit 1\n");
+        let err = Plugin::new(&path).replacements(&request()).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("failed on Money::round in src/money.rs"));
+        assert!(message.ends_with("oops"), "{}", message);
+    }
+}
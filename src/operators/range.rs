@@ -0,0 +1,31 @@
+// Copyright 2022 Martin Pool
+
+//! Change the limits of ranges within function bodies.
+
+use super::{Describe, RangeOperator};
+
+/// Swap an exclusive range, `a..b`, for an inclusive range, `a..=b`, and vice versa.
+pub struct Boundary;
+
+impl Describe for Boundary {
+    fn name(&self) -> &'static str {
+        "range_boundary"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces `..` with `..=`, or `..=` with `..`, so that the range includes or excludes its end."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test where the end of the range matters, such as the last iteration of a loop, or a value equal to the upper bound of a pattern."
+    }
+}
+
+impl RangeOperator for Boundary {
+    fn replacement(&self, limits: &syn::RangeLimits) -> Option<&'static str> {
+        match limits {
+            syn::RangeLimits::HalfOpen(_) => Some("..="),
+            syn::RangeLimits::Closed(_) => Some(".."),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright 2022 Martin Pool
+
+//! Return a leaked reference from functions that return a reference.
+//!
+//! This isn't enabled by default: see
+//! [Operators::leak_references](super::Operators::leak_references).
+
+use super::{Describe, Function, Operator};
+
+/// Return a reference to a default value that's leaked so that it lives long enough.
+pub struct LeakDefault;
+
+impl Describe for LeakDefault {
+    fn name(&self) -> &'static str {
+        "leak_default"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return a reference to the default value of the referenced type, leaking the memory."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return a reference to something other than the default value, that checks the value referred to."
+    }
+}
+
+impl Operator for LeakDefault {
+    fn applies_to(&self, function: &Function) -> bool {
+        // `Box` isn't in the prelude of a `no_std` crate.
+        !function.is_const() && !function.no_std && function.returns_reference()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Box::leak(Box::new(Default::default()))"
+    }
+}
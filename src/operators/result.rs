@@ -0,0 +1,213 @@
+// Copyright 2022 Martin Pool
+
+//! Return success or an error from functions that return a `Result`.
+
+use quote::ToTokens;
+
+use super::{Describe, Function, Operator};
+
+/// The name given to `Ok` values made from the replacements for the success type, such as
+/// `Ok(true)` for a `Result<bool>`: see [Operators::fn_replacements](super::Operators::fn_replacements).
+pub const OK_VALUE: &str = "ok_value";
+
+/// Return `Ok(Default::default())`.
+pub struct OkDefault;
+
+impl Describe for OkDefault {
+    fn name(&self) -> &'static str {
+        "ok_default"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always succeed, returning `Ok` with the default value of its success type, such as `()`, zero, or an empty collection."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should return an error, or a test that checks the value inside the `Ok`."
+    }
+}
+
+impl Operator for OkDefault {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_result()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Ok(Default::default())"
+    }
+}
+
+/// Return `Err(Default::default())`, for `Result`s with an explicit error type, which will
+/// build if the error type implements `Default`, as `()` and `String` do.
+pub struct ErrDefault;
+
+impl Describe for ErrDefault {
+    fn name(&self) -> &'static str {
+        "err_default"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always fail, returning `Err` with the default value of its error type."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should succeed, that checks it returns `Ok`."
+    }
+}
+
+impl Operator for ErrDefault {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const()
+            && function
+                .result_error_type()
+                .is_some_and(|error_type| !type_is_anyhow_error(error_type))
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Err(Default::default())"
+    }
+}
+
+/// Return an `anyhow::Error`, for functions returning `anyhow::Result` or
+/// `Result<T, anyhow::Error>`.
+pub struct ErrAnyhow;
+
+impl Describe for ErrAnyhow {
+    fn name(&self) -> &'static str {
+        "err_anyhow"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always fail, returning an `anyhow` error."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the function should succeed, that checks it returns `Ok`."
+    }
+}
+
+impl Operator for ErrAnyhow {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const()
+            && function.returns_result()
+            && match function.result_error_type() {
+                Some(error_type) => type_is_anyhow_error(error_type),
+                None => function.return_type_path().is_some_and(|path| {
+                    path.segments.len() > 1 && path.segments[0].ident == "anyhow"
+                }),
+            }
+    }
+
+    fn replacement(&self) -> &'static str {
+        "Err(::anyhow::anyhow!(\"mutated!\"))"
+    }
+}
+
+/// True if the type is `anyhow::Error`, written with its crate name.
+fn type_is_anyhow_error(typ: &syn::Type) -> bool {
+    match typ {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let names = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>();
+            names == ["anyhow", "Error"]
+        }
+        _ => false,
+    }
+}
+
+/// A type alias for `Result`, such as `Fallible<T>`, whose functions are mutated like those
+/// returning a `Result`, taking the success type from its first type argument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultAlias {
+    /// The name of the alias, such as `Fallible`.
+    pub name: String,
+
+    /// The error type fixed by the alias, like `MyError` in
+    /// `type Res<T> = Result<T, MyError>`, if it's known.
+    pub error_type: Option<syn::Type>,
+}
+
+impl ResultAlias {
+    /// An alias configured by name, whose error type isn't known.
+    pub fn named(name: &str) -> ResultAlias {
+        ResultAlias {
+            name: name.to_owned(),
+            error_type: None,
+        }
+    }
+
+    /// Resolve a type alias declared in the source, given the aliases that are already known,
+    /// returning None if it's not an alias for `Result`.
+    ///
+    /// Aliases whose first type parameter isn't the success type, like
+    /// `type Flag<E> = Result<bool, E>`, aren't resolved, since the success type couldn't be
+    /// found from where they're used.
+    pub fn resolve(item: &syn::ItemType, known: &[ResultAlias]) -> Option<ResultAlias> {
+        let syn::Type::Path(syn::TypePath { qself: None, path }) = &*item.ty else {
+            return None;
+        };
+        let segment = path.segments.last()?;
+        let target = known.iter().find(|alias| segment.ident == alias.name);
+        if segment.ident != "Result" && target.is_none() {
+            return None;
+        }
+        let args = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(typ) => Some(typ),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let params = item.generics.type_params().collect::<Vec<_>>();
+        if let Some(first_param) = params.first() {
+            if !matches!(args.first(), Some(syn::Type::Path(syn::TypePath { qself: None, path }))
+                if path.is_ident(&first_param.ident))
+            {
+                return None;
+            }
+        }
+        let error_type = match args.get(1) {
+            // A parameter of the alias is known only from its default.
+            Some(syn::Type::Path(syn::TypePath { qself: None, path }))
+                if params.iter().any(|param| path.is_ident(&param.ident)) =>
+            {
+                params
+                    .iter()
+                    .find(|param| path.is_ident(&param.ident))
+                    .and_then(|param| param.default.clone())
+            }
+            Some(typ) if mentions_any(typ, &params) => None,
+            Some(typ) => Some((*typ).clone()),
+            None => match target {
+                Some(target) => target.error_type.clone(),
+                None if path.segments.len() > 1 && path.segments[0].ident == "anyhow" => {
+                    Some(syn::parse_quote! { anyhow::Error })
+                }
+                None => None,
+            },
+        };
+        Some(ResultAlias {
+            name: item.ident.to_string(),
+            error_type,
+        })
+    }
+}
+
+/// True if any of the type parameters are named in `typ`.
+fn mentions_any(typ: &syn::Type, params: &[&syn::TypeParam]) -> bool {
+    fn tokens_mention(tokens: proc_macro2::TokenStream, params: &[&syn::TypeParam]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => params.iter().any(|param| ident == param.ident),
+            proc_macro2::TokenTree::Group(group) => tokens_mention(group.stream(), params),
+            _ => false,
+        })
+    }
+    tokens_mention(typ.to_token_stream(), params)
+}
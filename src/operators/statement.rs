@@ -0,0 +1,114 @@
+// Copyright 2022 Martin Pool
+
+//! Delete statements from blocks within function bodies.
+
+use super::{Describe, StatementOperator};
+
+/// Delete one statement at a time, when the block still makes sense without it.
+///
+/// Expression statements, such as assignments, calls, and loops, can be deleted, except for
+/// the final expression that gives the block its value. `let` bindings are kept, since the
+/// names they bind are almost always used later, unless they bind nothing, as in
+/// `let _ = f();`. `return`, `break`, and `continue` are kept, since deleting them usually
+/// leaves code that doesn't build.
+pub struct DeleteStatement;
+
+impl Describe for DeleteStatement {
+    fn name(&self) -> &'static str {
+        "delete_statement"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Deletes a statement, such as an assignment, a call, or a loop, from a block."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the effect of the statement, such as the value it assigns or the side effect of the call."
+    }
+}
+
+impl StatementOperator for DeleteStatement {
+    fn statements_to_delete(&self, stmts: &[syn::Stmt]) -> Vec<usize> {
+        stmts
+            .iter()
+            .enumerate()
+            .filter(|(i, stmt)| match stmt {
+                syn::Stmt::Local(local) => {
+                    matches!(local.pat, syn::Pat::Wild(_)) && local.init.is_some()
+                }
+                syn::Stmt::Item(syn::Item::Macro(item_macro)) => {
+                    item_macro.ident.is_none() && item_macro.semi_token.is_some()
+                }
+                syn::Stmt::Item(_) => false,
+                syn::Stmt::Expr(expr) => *i + 1 < stmts.len() && !diverges(expr),
+                syn::Stmt::Semi(expr, _semi) => !diverges(expr),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// True if the expression leaves the block by `return`, `break`, or `continue`.
+fn diverges(expr: &syn::Expr) -> bool {
+    matches!(
+        expr,
+        syn::Expr::Return(_) | syn::Expr::Break(_) | syn::Expr::Continue(_)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn deleted(block: syn::Block) -> Vec<String> {
+        DeleteStatement
+            .statements_to_delete(&block.stmts)
+            .into_iter()
+            .map(|i| {
+                let stmt = &block.stmts[i];
+                quote::quote!(#stmt).to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expression_statements_are_deleted() {
+        assert_eq!(
+            deleted(syn::parse_quote! {{
+                let mut total = 0;
+                let _ = check();
+                total += 1;
+                println!("{}", total);
+                for i in 0..3 { total += i; }
+                if total > 10 { return 0; }
+                log(total);
+                total
+            }}),
+            [
+                "let _ = check () ;",
+                "total += 1 ;",
+                "println ! (\"{}\" , total) ;",
+                "for i in 0 .. 3 { total += i ; }",
+                "if total > 10 { return 0 ; }",
+                "log (total) ;",
+            ]
+        );
+    }
+
+    #[test]
+    fn control_flow_and_items_are_kept() {
+        assert_eq!(
+            deleted(syn::parse_quote! {{
+                fn helper() {}
+                macro_rules! m { () => {} }
+                let x = 1;
+                if x > 0 { continue; }
+                break;
+                return;
+            }}),
+            ["if x > 0 { continue ; }"]
+        );
+    }
+}
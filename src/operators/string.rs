@@ -0,0 +1,116 @@
+// Copyright 2022 Martin Pool
+
+//! Return constant text from functions that return `String`, `&str`, or `Cow<str>`.
+//!
+//! `.into()` can't be called in a `const fn`, so `const fn`s returning `&str` get the string
+//! literals themselves.
+
+use super::{Describe, Function, Operator};
+
+/// Return an empty string.
+pub struct EmptyString;
+
+impl Describe for EmptyString {
+    fn name(&self) -> &'static str {
+        "empty_string"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return an empty string."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the function returns the expected non-empty string."
+    }
+}
+
+impl Operator for EmptyString {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_string()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "\"\".into()"
+    }
+}
+
+/// Return `"xyzzy"`.
+pub struct Xyzzy;
+
+impl Describe for Xyzzy {
+    fn name(&self) -> &'static str {
+        "xyzzy"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the function always return the string `\"xyzzy\"`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the exact string the function returns, not only that it's non-empty."
+    }
+}
+
+impl Operator for Xyzzy {
+    fn applies_to(&self, function: &Function) -> bool {
+        !function.is_const() && function.returns_string()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "\"xyzzy\".into()"
+    }
+}
+
+/// Return an empty string literal from a `const fn` returning `&str`.
+pub struct EmptyStr;
+
+impl Describe for EmptyStr {
+    fn name(&self) -> &'static str {
+        "empty_str"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the `const fn` always return an empty string."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the function returns the expected non-empty string."
+    }
+}
+
+impl Operator for EmptyStr {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.is_const() && function.returns_str()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "\"\""
+    }
+}
+
+/// Return `"xyzzy"` from a `const fn` returning `&str`.
+pub struct XyzzyStr;
+
+impl Describe for XyzzyStr {
+    fn name(&self) -> &'static str {
+        "xyzzy_str"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Makes the `const fn` always return the string `\"xyzzy\"`."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that checks the exact string the function returns, not only that it's non-empty."
+    }
+}
+
+impl Operator for XyzzyStr {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.is_const() && function.returns_str()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "\"xyzzy\""
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright 2022 Martin Pool
+
+//! Return tuples from functions that return them, with one element at a time replaced by a
+//! value for its type, and the others by `Default::default()`.
+//!
+//! The values for each element are the replacements that would be made in a function returning
+//! the element's type, so a function returning `(bool, String)` gets mutants returning
+//! `(true, Default::default())` and `(Default::default(), "xyzzy".into())`, among others. This
+//! catches tests that check only one element of the tuple, which a single
+//! `Default::default()` for the whole tuple doesn't.
+
+/// The name given to tuples with one element replaced.
+pub const ELEMENT: &str = "tuple_element";
+
+/// Return the tuple with `value` as the element at `index`, among `len` elements, and
+/// `Default::default()` for the others.
+pub(crate) fn with_element(len: usize, index: usize, value: &str) -> String {
+    let elements = (0..len)
+        .map(|i| {
+            if i == index {
+                value
+            } else {
+                "Default::default()"
+            }
+        })
+        .collect::<Vec<_>>();
+    if len == 1 {
+        format!("({},)", elements[0])
+    } else {
+        format!("({})", elements.join(", "))
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2022 Martin Pool
+
+//! Delete unary operators within function bodies.
+
+use super::{Describe, UnaryOperator};
+
+/// Delete a unary minus, so that `-x` becomes `x`.
+pub struct DeleteNeg;
+
+impl Describe for DeleteNeg {
+    fn name(&self) -> &'static str {
+        "delete_neg"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Deletes a unary `-`, so that the sign of the value is flipped."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the negated value is not zero, and checks its sign."
+    }
+}
+
+impl UnaryOperator for DeleteNeg {
+    fn deletes(&self, op: &syn::UnOp) -> bool {
+        matches!(op, syn::UnOp::Neg(_))
+    }
+}
+
+/// Delete a `!`, so that `!x` becomes `x`.
+pub struct DeleteNot;
+
+impl Describe for DeleteNot {
+    fn name(&self) -> &'static str {
+        "delete_not"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Deletes a `!`, so that a boolean is inverted, or the bits of an integer are not."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test of some input for which the expression with and without the `!` gives different results."
+    }
+}
+
+impl UnaryOperator for DeleteNot {
+    fn deletes(&self, op: &syn::UnOp) -> bool {
+        matches!(op, syn::UnOp::Not(_))
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright 2022 Martin Pool
+
+//! Replace the body of a function that returns `()` with nothing.
+
+use super::{Describe, Function, Operator};
+
+/// Replace the function body with `()`, for functions that return `()`.
+///
+/// We use `()` rather than just nothing because it's clearer in messages about the mutation.
+pub struct Unit;
+
+impl Describe for Unit {
+    fn name(&self) -> &'static str {
+        "unit"
+    }
+
+    fn effect(&self) -> &'static str {
+        "Replaces the body of a function that returns `()`, so that it does nothing."
+    }
+
+    fn to_catch(&self) -> &'static str {
+        "A test that calls the function and then checks one of its side effects, such as a change to `self`, to an argument, or to some external state."
+    }
+}
+
+impl Operator for Unit {
+    fn applies_to(&self, function: &Function) -> bool {
+        function.returns_unit()
+    }
+
+    fn replacement(&self) -> &'static str {
+        "()"
+    }
+}
@@ -6,14 +6,15 @@
 //! processes access the same directory they'll tread on each other...
 
 use std::fs::{File, OpenOptions};
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 use anyhow::{Context, Result};
+use path_slash::PathExt;
 
-const OUTDIR_NAME: &str = "mutants.out";
-const ROTATED_NAME: &str = "mutants.out.old";
+pub const OUTDIR_NAME: &str = "mutants.out";
+pub const ROTATED_NAME: &str = "mutants.out.old";
 
 /// A `mutants.out` directory holding logs and other output information.
 #[derive(Debug)]
@@ -32,14 +33,22 @@ impl OutputDir {
         if path.exists() {
             let rotated = in_dir.as_ref().join(ROTATED_NAME);
             if rotated.exists() {
-                fs::remove_dir_all(&rotated).with_context(|| format!("remove {:?}", &rotated))?;
+                fs::remove_dir_all(&rotated)
+                    .with_context(|| format!("remove {}", rotated.to_slash_lossy()))?;
             }
-            fs::rename(&path, &rotated)
-                .with_context(|| format!("move {:?} to {:?}", &path, &rotated))?;
+            fs::rename(&path, &rotated).with_context(|| {
+                format!(
+                    "move {} to {}",
+                    path.to_slash_lossy(),
+                    rotated.to_slash_lossy()
+                )
+            })?;
         }
-        fs::create_dir(&path).with_context(|| format!("create output directory {:?}", &path))?;
+        fs::create_dir(&path)
+            .with_context(|| format!("create output directory {}", path.to_slash_lossy()))?;
         let log_dir = path.join("log");
-        fs::create_dir(&log_dir).with_context(|| format!("create log directory {:?}", &log_dir))?;
+        fs::create_dir(&log_dir)
+            .with_context(|| format!("create log directory {}", log_dir.to_slash_lossy()))?;
         Ok(OutputDir { path, log_dir })
     }
 
@@ -70,8 +79,9 @@ impl OutputDir {
             }
         }
         unreachable!(
-            "couldn't create any test log in {:?} for {:?}",
-            self, scenario_name,
+            "couldn't create any test log in {} for {:?}",
+            self.log_dir.to_slash_lossy(),
+            scenario_name,
         );
     }
 
@@ -82,6 +92,8 @@ impl OutputDir {
     }
 }
 
+/// Make a scenario name into a file name that's valid, and the same, on every platform, by
+/// replacing path separators and characters that aren't allowed in Windows file names.
 fn clean_filename(s: &str) -> String {
     s.chars()
         .map(|c| match c {
@@ -103,7 +115,23 @@ impl LogFile {
         let mut buf: Vec<u8> = Vec::new();
         File::open(&self.path)
             .and_then(|mut f| f.read_to_end(&mut buf))
-            .with_context(|| format!("read log file {}", self.path.display()))?;
+            .with_context(|| format!("read log file {}", self.path.to_slash_lossy()))?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    /// Return up to the last `max_bytes` of the log, which may start part way through a line.
+    ///
+    /// This is cheap even if the log is large, so it can be called often to show the latest
+    /// output.
+    pub fn tail(&self, max_bytes: u64) -> Result<String> {
+        let mut buf: Vec<u8> = Vec::new();
+        File::open(&self.path)
+            .and_then(|mut f| {
+                let len = f.metadata()?.len();
+                f.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))?;
+                f.read_to_end(&mut buf)
+            })
+            .with_context(|| format!("read log file {}", self.path.to_slash_lossy()))?;
         Ok(String::from_utf8_lossy(&buf).into_owned())
     }
 
@@ -112,7 +140,7 @@ impl LogFile {
         OpenOptions::new()
             .append(true)
             .open(&self.path)
-            .with_context(|| format!("open {} for append", self.path.display()))
+            .with_context(|| format!("open {} for append", self.path.to_slash_lossy()))
     }
 }
 
@@ -160,6 +188,23 @@ mod test {
         assert_eq!(output_dir.log_dir, tmp.path().join("mutants.out/log"));
     }
 
+    #[test]
+    fn log_names_are_the_same_on_every_platform() {
+        let temp_dir = minimal_source_tree();
+        let output_dir = OutputDir::new(&temp_dir).unwrap();
+        let (_file, log_file) = output_dir
+            .create_log("replace <impl Debug for Foo>::fmt with Ok(Default::default()) in src\\fmt/debug.rs:4")
+            .unwrap();
+        assert_eq!(
+            log_file
+                .path
+                .strip_prefix(output_dir.path())
+                .unwrap()
+                .to_slash_lossy(),
+            "log/replace__impl_Debug_for_Foo___fmt_with_Ok(Default__default())_in_src_fmt_debug.rs_4.log"
+        );
+    }
+
     #[test]
     fn rotate() {
         let temp_dir = tempfile::TempDir::new().unwrap();
@@ -194,4 +239,17 @@ mod test {
             .join("mutants.out.old/log/one.log")
             .is_file());
     }
+
+    #[test]
+    fn log_tail_reads_only_the_end() {
+        let temp_dir = minimal_source_tree();
+        let output_dir = OutputDir::new(&temp_dir).unwrap();
+        let (mut file, log_file) = output_dir.create_log("tail").unwrap();
+        io::Write::write_all(&mut file, b"first line\nsecond line\n").unwrap();
+        assert_eq!(log_file.tail(12).unwrap(), "second line\n");
+        assert_eq!(
+            log_file.tail(1000).unwrap(),
+            log_file.log_content().unwrap()
+        );
+    }
 }
@@ -0,0 +1,191 @@
+// Copyright 2022 Martin Pool
+
+//! Plans: lists of mutants saved to a file, so that exactly the same mutants can be found again
+//! later, perhaps on another machine.
+//!
+//! A plan records each mutant's stable id, and finds the mutants again by rediscovering the
+//! mutants in the tree and checking that each planned id is still there. Since ids don't depend
+//! on line numbers, a plan still applies after unrelated edits move code around, but if a planned
+//! function has changed, loading the plan fails, rather than silently testing something
+//! different.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use path_slash::PathExt;
+use serde::{Deserialize, Serialize};
+
+use crate::mutate::Mutation;
+use crate::textedit::LineColumn;
+
+/// A list of mutants to test.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    pub mutants: Vec<PlannedMutant>,
+}
+
+/// One mutant in a [Plan].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlannedMutant {
+    /// The stable id of the mutant, from [Mutation::id].
+    pub id: String,
+
+    /// The path of the source file, relative to the tree root, with forward slashes.
+    pub file: String,
+
+    /// The name of the function that's mutated.
+    pub function: String,
+
+    /// The name of the operator that generated the mutant.
+    pub op: String,
+
+    /// The text that replaces the function body.
+    pub replacement: String,
+
+    /// The start of the replaced span.
+    pub start: LineColumn,
+
+    /// The end of the replaced span.
+    pub end: LineColumn,
+}
+
+impl PlannedMutant {
+    fn new(mutation: &Mutation) -> PlannedMutant {
+        let span = mutation.span();
+        PlannedMutant {
+            id: mutation.id().to_owned(),
+            file: mutation.source_file.tree_relative_slashes(),
+            function: mutation.function_name().to_owned(),
+            op: mutation.op.clone(),
+            replacement: mutation.replacement_text().to_owned(),
+            start: span.start,
+            end: span.end,
+        }
+    }
+
+    /// True if this describes the given mutation.
+    ///
+    /// The other fields are informative, for people reading the plan: only the id is matched,
+    /// so that the plan still applies if the function has moved.
+    fn matches(&self, mutation: &Mutation) -> bool {
+        self.id == mutation.id()
+    }
+}
+
+impl Plan {
+    /// Make a plan to test the given mutations.
+    pub fn new(mutations: &[Mutation]) -> Plan {
+        Plan {
+            mutants: mutations.iter().map(PlannedMutant::new).collect(),
+        }
+    }
+
+    /// Read a plan from a json file.
+    pub fn read(path: &Path) -> Result<Plan> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("read {}", path.to_slash_lossy()))?;
+        serde_json::from_str(&json).with_context(|| format!("parse plan {}", path.to_slash_lossy()))
+    }
+
+    /// Write the plan to a json file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        serde_json::to_writer_pretty(
+            BufWriter::new(
+                File::create(path).with_context(|| format!("create {}", path.to_slash_lossy()))?,
+            ),
+            self,
+        )
+        .with_context(|| format!("write {}", path.to_slash_lossy()))
+    }
+
+    /// Select the planned mutants from all those discovered in the tree, returning each with
+    /// its planned id, in the order of the plan.
+    ///
+    /// It's an error if any planned mutant isn't among those discovered, which probably means
+    /// the source changed after the plan was made.
+    pub fn select(&self, mutations: Vec<Mutation>) -> Result<Vec<(String, Mutation)>> {
+        let mut available: Vec<Option<Mutation>> = mutations.into_iter().map(Some).collect();
+        let mut selected = Vec::with_capacity(self.mutants.len());
+        for planned in &self.mutants {
+            let found = available
+                .iter_mut()
+                .find(|m| m.as_ref().is_some_and(|m| planned.matches(m)))
+                .and_then(Option::take)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "planned mutant {} (replace {} with {} in {}:{}) is not in the source tree: has the source changed since the plan was made?",
+                        planned.id,
+                        planned.function,
+                        planned.replacement,
+                        planned.file,
+                        planned.start.line,
+                    )
+                })?;
+            selected.push((planned.id.clone(), found));
+        }
+        Ok(selected)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::operators::Operators;
+    use crate::source::SourceFile;
+
+    fn factorial_mutations() -> Vec<Mutation> {
+        SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap()
+    }
+
+    #[test]
+    fn plan_round_trips_through_a_file() {
+        let mutations = factorial_mutations();
+        let plan = Plan::new(&mutations);
+        assert_eq!(
+            plan.mutants
+                .iter()
+                .map(|m| m.id.as_str())
+                .collect::<Vec<_>>(),
+            mutations.iter().map(Mutation::id).collect::<Vec<_>>()
+        );
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("plan.json");
+        plan.write(&path).unwrap();
+        let loaded = Plan::read(&path).unwrap();
+        assert_eq!(loaded, plan);
+
+        let selected = loaded.select(factorial_mutations()).unwrap();
+        assert_eq!(selected.len(), mutations.len());
+        for ((id, selected), original) in selected.iter().zip(&mutations) {
+            assert_eq!(id, original.id());
+            assert_eq!(selected.to_string(), original.to_string());
+        }
+    }
+
+    #[test]
+    fn missing_planned_mutant_is_an_error() {
+        let mut plan = Plan::new(&factorial_mutations());
+        plan.mutants[3].id = "0123456789abcdef".to_owned();
+        let err = plan.select(factorial_mutations()).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("planned mutant 0123456789abcdef (replace factorial with Default::default() in src/bin/main.rs:7)"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2022 Martin Pool
+
+//! Observers that write the results of a run into files in `mutants.out`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use path_slash::PathExt;
+use serde::Serialize;
+
+use crate::lab::{Outcome, Status};
+use crate::mutate::Mutation;
+use crate::observer::{Observer, Scenario};
+use crate::output::OutputDir;
+
+/// Writes `mutants.json`, listing all the mutants, and `outcomes.json`, recording the outcome
+/// of each scenario as it finishes.
+pub struct JsonReporter {
+    output_path: PathBuf,
+    outcomes: Vec<serde_json::Value>,
+}
+
+/// One entry in `outcomes.json`.
+#[derive(Serialize)]
+struct OutcomeRecord<'a> {
+    scenario: &'a Scenario<'a>,
+    status: Status,
+    /// The path of the log file, relative to `mutants.out`.
+    log_path: String,
+    /// How long the scenario took, in seconds.
+    duration: f64,
+}
+
+impl JsonReporter {
+    pub fn new(output_dir: &OutputDir) -> JsonReporter {
+        JsonReporter {
+            output_path: output_dir.path().to_owned(),
+            outcomes: Vec::new(),
+        }
+    }
+
+    fn write_json<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        let path = self.output_path.join(name);
+        serde_json::to_writer_pretty(
+            BufWriter::new(
+                File::create(&path).with_context(|| format!("create {}", path.to_slash_lossy()))?,
+            ),
+            value,
+        )
+        .with_context(|| format!("write {}", path.to_slash_lossy()))
+    }
+}
+
+impl Observer for JsonReporter {
+    fn mutants_discovered(&mut self, mutations: &[Mutation]) {
+        report_error(self.write_json("mutants.json", &mutations));
+    }
+
+    fn scenario_finished(&mut self, scenario: &Scenario, outcome: &Outcome) {
+        let record = OutcomeRecord {
+            scenario,
+            status: outcome.status,
+            log_path: relative_log_path(&self.output_path, &outcome.log_file.path),
+            duration: outcome.duration.as_secs_f64(),
+        };
+        match serde_json::to_value(record) {
+            Ok(value) => self.outcomes.push(value),
+            Err(err) => report_error(Err(err.into())),
+        }
+        // Rewrite the whole file each time, so that it's complete even if the run is interrupted.
+        report_error(self.write_json(
+            "outcomes.json",
+            &serde_json::json!({ "outcomes": self.outcomes }),
+        ));
+    }
+}
+
+/// Appends the name of each mutant to a text file for its outcome: `caught.txt`, `missed.txt`,
+/// `timeout.txt`, `unviable.txt` (for mutants that failed to build), or `bad_patch.txt` (for
+/// mutants whose code doesn't parse). Mutants that matched the ignore list, or that the verdict
+/// command ignored, are listed in `ignored.txt`.
+///
+/// These are convenient to read, and to process with line-oriented tools.
+pub struct ListFilesReporter {
+    output_path: PathBuf,
+}
+
+impl ListFilesReporter {
+    pub fn new(output_dir: &OutputDir) -> ListFilesReporter {
+        ListFilesReporter {
+            output_path: output_dir.path().to_owned(),
+        }
+    }
+
+    fn append_line(&self, name: &str, line: &str) -> Result<()> {
+        let path = self.output_path.join(name);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.to_slash_lossy()))?;
+        writeln!(file, "{}", line).with_context(|| format!("write {}", path.to_slash_lossy()))
+    }
+}
+
+impl Observer for ListFilesReporter {
+    fn mutants_discovered(&mut self, _mutations: &[Mutation]) {
+        // Create all the files, so that an empty file means there were no such mutants.
+        for name in [
+            "caught.txt",
+            "missed.txt",
+            "timeout.txt",
+            "unviable.txt",
+            "bad_patch.txt",
+            "ignored.txt",
+        ] {
+            let path = self.output_path.join(name);
+            report_error(
+                fs::write(&path, b"").with_context(|| format!("create {}", path.to_slash_lossy())),
+            );
+        }
+    }
+
+    fn mutants_ignored(&mut self, mutations: &[Mutation]) {
+        for mutation in mutations {
+            report_error(self.append_line("ignored.txt", &mutation.to_string()));
+        }
+    }
+
+    fn scenario_finished(&mut self, scenario: &Scenario, outcome: &Outcome) {
+        let mutation = match scenario.mutation() {
+            Some(mutation) => mutation,
+            None => return,
+        };
+        let name = match outcome.status {
+            Status::MutantCaught => "caught.txt",
+            Status::MutantMissed => "missed.txt",
+            Status::Timeout => "timeout.txt",
+            Status::CheckFailed | Status::BuildFailed => "unviable.txt",
+            Status::BadPatch => "bad_patch.txt",
+            Status::Ignored => "ignored.txt",
+            _ => return,
+        };
+        report_error(self.append_line(name, &mutation.to_string()));
+    }
+}
+
+/// Return the path of a log file relative to the output directory, with forward slashes.
+fn relative_log_path(output_path: &Path, log_path: &Path) -> String {
+    log_path
+        .strip_prefix(output_path)
+        .unwrap_or(log_path)
+        .to_slash_lossy()
+}
+
+/// Observers can't return errors, so print them: failing to write a report shouldn't stop the
+/// run.
+fn report_error(result: Result<()>) {
+    if let Err(err) = result {
+        eprintln!("error writing report: {:#}", err);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+    use std::time::Instant;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::operators::Operators;
+    use crate::source::discover_mutations;
+
+    #[test]
+    fn bad_patches_are_listed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let output_dir = OutputDir::new(tmp.path()).unwrap();
+        let mutations = discover_mutations(
+            Path::new("src/lib.rs"),
+            "pub fn one() -> u32 {\n    1\n}\n",
+            &Operators::all(),
+        )
+        .unwrap();
+        let mut reporter = ListFilesReporter::new(&output_dir);
+        reporter.mutants_discovered(&mutations);
+        let (_file, log_file) = output_dir.create_log("mutant").unwrap();
+        let outcome = Outcome::new(&log_file, &Instant::now(), Status::BadPatch);
+        reporter.scenario_finished(&Scenario::Mutant(&mutations[0]), &outcome);
+        let read = |name: &str| fs::read_to_string(output_dir.path().join(name)).unwrap();
+        assert_eq!(read("bad_patch.txt"), format!("{}\n", mutations[0]));
+        assert_eq!(read("unviable.txt"), "");
+    }
+}
@@ -4,8 +4,9 @@
 
 use std::borrow::Cow;
 use std::env;
+use std::ffi::OsString;
 
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::Path;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
@@ -13,18 +14,15 @@ use std::time::{Duration, Instant};
 use anyhow::{Context, Result};
 use subprocess::{Popen, PopenConfig, Redirection};
 
-use crate::console::Activity;
 use crate::lab::LOG_MARKER;
+use crate::observer::Observer;
 use crate::output::LogFile;
 
-// Until we can reliably stop the grandchild test binaries, by killing a process
-// group, timeouts are disabled.
-const TEST_TIMEOUT: Duration = Duration::MAX; // Duration::from_secs(1);
-
 /// How frequently to check if cargo finished.
 const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// The result of running a single Cargo command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CargoResult {
     // Note: This is not, for now, a Result, because it seems like there is
     // no clear "normal" success: sometimes a non-zero exit is what we want, etc.
@@ -42,10 +40,19 @@ impl CargoResult {
     }
 }
 
+/// Run cargo with the given arguments in a directory, logging its output.
+///
+/// `cargo_env` are environment variables set for cargo in addition to the inherited
+/// environment.
+///
+/// If cargo runs for longer than `timeout`, it's killed, along with the tests it's running,
+/// and the result is [CargoResult::Timeout].
 pub fn run_cargo(
     cargo_args: &[&str],
     in_dir: &Path,
-    activity: &mut Activity,
+    cargo_env: &[(String, String)],
+    timeout: Option<Duration>,
+    observer: &mut dyn Observer,
     log_file: &LogFile,
 ) -> Result<CargoResult> {
     let start = Instant::now();
@@ -74,19 +81,20 @@ pub fn run_cargo(
             stdout: Redirection::File(out_file.try_clone()?),
             stderr: Redirection::Merge,
             cwd: Some(in_dir.as_os_str().to_owned()),
+            env: child_env(cargo_env),
             ..setpgid_on_unix()
         },
     )
     .with_context(|| format!("failed to spawn {} {}", cargo_bin, cargo_args.join(" ")))?;
     let exit_status = loop {
-        if start.elapsed() > TEST_TIMEOUT {
+        if timeout.is_some_and(|timeout| start.elapsed() > timeout) {
             writeln!(
                 out_file,
                 "\n{} timeout after {}s, killing cargo process...",
                 LOG_MARKER,
                 start.elapsed().as_secs_f32()
             )?;
-            if let Err(e) = child.kill() {
+            if let Err(e) = kill_child(&mut child) {
                 // most likely we raced and it's already gone
                 writeln!(
                     out_file,
@@ -103,7 +111,7 @@ pub fn run_cargo(
         if let Some(status) = child.wait_timeout(WAIT_POLL_INTERVAL)? {
             break status;
         }
-        activity.tick();
+        observer.tick();
     };
     let duration = start.elapsed();
     writeln!(
@@ -118,6 +126,41 @@ pub fn run_cargo(
     }
 }
 
+/// Return the environment for a child process: the inherited environment plus some additions,
+/// or None to just inherit it.
+fn child_env(cargo_env: &[(String, String)]) -> Option<Vec<(OsString, OsString)>> {
+    if cargo_env.is_empty() {
+        return None;
+    }
+    let mut child_env: Vec<(OsString, OsString)> = env::vars_os()
+        .filter(|(name, _)| {
+            !cargo_env
+                .iter()
+                .any(|(set_name, _)| name == set_name.as_str())
+        })
+        .collect();
+    child_env.extend(
+        cargo_env
+            .iter()
+            .map(|(name, value)| (name.into(), value.into())),
+    );
+    Some(child_env)
+}
+
+/// Kill cargo and, on Unix, everything in its process group, including the test binaries it's
+/// running.
+#[cfg(unix)]
+fn kill_child(child: &mut Popen) -> io::Result<()> {
+    use subprocess::unix::PopenExt;
+    const SIGKILL: i32 = 9;
+    child.send_signal_group(SIGKILL)
+}
+
+#[cfg(not(unix))]
+fn kill_child(child: &mut Popen) -> io::Result<()> {
+    child.kill()
+}
+
 #[cfg(unix)]
 fn setpgid_on_unix() -> PopenConfig {
     PopenConfig {
@@ -2,15 +2,29 @@
 
 //! Access to a Rust source tree and files.
 
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
 use anyhow::{anyhow, Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use path_slash::PathExt;
+use serde::Serialize;
 use syn::visit::Visit;
 
-use crate::mutate::Mutation;
-use crate::visit::DiscoveryVisitor;
+use crate::manifest;
+use crate::mutate::{disambiguate_ids, sort_mutations, Mutation};
+use crate::operators::Operators;
+use crate::textedit::{Edit, LineIndex, Span};
+use crate::visit::{DiscoveryVisitor, IncludePath, IncludeReference, ModReference};
+pub use crate::visit::{SkipReason, Skipped, VisitOptions};
+
+/// A gitignore-style file in the root of the tree listing files that should not be mutated.
+const MUTANTS_IGNORE: &str = ".mutants-ignore";
+
+/// The UTF-8 encoding of a byte order mark, which some editors put at the start of files.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
 
 /// A Rust source file within a source tree.
 ///
@@ -26,34 +40,149 @@ pub struct SourceFile {
 
     /// Full copy of the source.
     pub code: Rc<String>,
+
+    /// Where each line of `code` starts, made once and shared by all the mutants in the file.
+    line_index: Rc<LineIndex>,
+
+    /// The name of the package that this file is part of, or empty if it's not known.
+    pub package: String,
+
+    /// The Cargo target that this file is part of.
+    pub target: Target,
+
+    /// True if the target this file is part of is a `no_std` crate.
+    pub no_std: bool,
+
+    /// The path of the module in this file from the crate root, such as `["util"]`, or empty
+    /// for the top file of a target.
+    pub namespace: Vec<String>,
+}
+
+/// A Cargo target, such as the library or a binary, that source files are built into.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    Lib,
+    /// The library of a proc-macro package.
+    ProcMacro,
+    Bin(String),
+    Example(String),
+    Bench(String),
+    Test(String),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Lib => write!(f, "lib"),
+            Target::ProcMacro => write!(f, "proc-macro"),
+            Target::Bin(name) => write!(f, "bin {}", name),
+            Target::Example(name) => write!(f, "example {}", name),
+            Target::Bench(name) => write!(f, "bench {}", name),
+            Target::Test(name) => write!(f, "test {}", name),
+        }
+    }
 }
 
 impl SourceFile {
     /// Construct a SourceFile representing a file within a tree.
     ///
     /// This eagerly loads the text of the file.
+    ///
+    /// The file is assumed to be part of the library target; discovery updates this when
+    /// it's found from some other target.
     pub fn new(tree_path: &Path, tree_relative: &Path) -> Result<SourceFile> {
         let full_path = tree_path.join(tree_relative);
-        let code = std::fs::read_to_string(&full_path)
-            .with_context(|| format!("failed to read source of {:?}", full_path))?
+        let bytes = std::fs::read(&full_path)
+            .with_context(|| format!("failed to read source of {}", full_path.to_slash_lossy()))?;
+        SourceFile::from_bytes(tree_relative, &bytes)
+            .map_err(|reason| anyhow!("{}: {}", full_path.to_slash_lossy(), reason))
+    }
+
+    /// Construct a SourceFile from the contents of a file.
+    ///
+    /// The path is normalized, so that however the file was found, it's reported the same way.
+    ///
+    /// If the file can't be mutated, because it's not valid UTF-8 or it starts with a
+    /// byte order mark, returns the reason why.
+    pub fn from_bytes(tree_relative: &Path, bytes: &[u8]) -> Result<SourceFile, &'static str> {
+        if bytes.starts_with(UTF8_BOM) {
+            return Err("starts with a UTF-8 byte order mark");
+        }
+        let code = std::str::from_utf8(bytes)
+            .map_err(|_| "not valid UTF-8")?
             .replace("\r\n", "\n");
         Ok(SourceFile {
-            tree_relative: tree_relative.to_owned(),
+            tree_relative: normalize_relative_path(tree_relative)
+                .unwrap_or_else(|| tree_relative.to_owned()),
+            line_index: Rc::new(LineIndex::new(&code)),
             code: Rc::new(code),
+            package: String::new(),
+            target: Target::Lib,
+            no_std: false,
+            namespace: Vec::new(),
         })
     }
 
+    /// Return the text of the file within a span.
+    pub fn span_text(&self, span: &Span) -> &str {
+        self.line_index.extract(&self.code, span)
+    }
+
+    /// Return the text of the whole file with the text in a span replaced.
+    pub fn replace_span(&self, span: &Span, replacement: &str) -> String {
+        self.line_index.replace(&self.code, span, replacement)
+    }
+
+    /// Return the text of the whole file with several edits applied at once, or an error if
+    /// any of them overlap.
+    pub fn apply_edits(&self, edits: &[Edit]) -> Result<String> {
+        self.line_index.apply_edits(&self.code, edits)
+    }
+
     /// Return the path of this file relative to the tree root, with forward slashes.
     pub fn tree_relative_slashes(&self) -> String {
         self.tree_relative.to_slash_lossy()
     }
 
-    /// Generate a list of all mutation possibilities within this file.
-    pub fn mutations(&self) -> Result<Vec<Mutation>> {
+    /// Generate a list of all mutation possibilities within this file, using the given operators.
+    ///
+    /// `unsafe fn`s are not mutated.
+    pub fn mutations(&self, operators: &Operators) -> Result<Vec<Mutation>> {
+        self.discover(operators, &VisitOptions::default())
+            .map(|(mutations, _skipped)| mutations)
+    }
+
+    /// Find the mutations within this file, and the functions and other items that are
+    /// skipped because of the options.
+    pub fn discover(
+        &self,
+        operators: &Operators,
+        options: &VisitOptions,
+    ) -> Result<(Vec<Mutation>, Vec<Skipped>)> {
+        let visitor = self.visit(operators, options)?;
+        match visitor.error {
+            Some(err) => Err(err),
+            None => {
+                let mut mutations = visitor.mutations;
+                sort_mutations(&mut mutations, operators);
+                disambiguate_ids(&mut mutations);
+                let mut skipped = visitor.skipped;
+                skipped.sort_by_key(|skipped| skipped.line);
+                Ok((mutations, skipped))
+            }
+        }
+    }
+
+    /// Parse the file and visit its syntax tree.
+    fn visit<'s>(
+        &'s self,
+        operators: &'s Operators,
+        options: &'s VisitOptions,
+    ) -> Result<DiscoveryVisitor<'s>> {
         let syn_file = syn::parse_str::<syn::File>(&self.code)?;
-        let mut v = DiscoveryVisitor::new(self);
+        let mut v = DiscoveryVisitor::new(self, operators, options);
         v.visit_file(&syn_file);
-        Ok(v.mutations)
+        Ok(v)
     }
 
     /// Return the path of this file relative to a given directory.
@@ -63,11 +192,126 @@ impl SourceFile {
     }
 }
 
+/// Options controlling which source files and functions are mutated.
+#[derive(Debug, Default, Clone)]
+pub struct DiscoveryOptions {
+    /// Also mutate code in `examples/`.
+    pub include_examples: bool,
+    /// Also mutate code in `benches/`.
+    pub include_benches: bool,
+    /// Also mutate non-test helper code in integration tests under `tests/`.
+    pub include_test_helpers: bool,
+    /// Gitignore-style patterns for files that should not be mutated, in addition to those in
+    /// `.mutants-ignore`.
+    pub exclude_globs: Vec<String>,
+    /// Only mutate these targets, if any are selected.
+    pub targets: TargetSelection,
+    /// Also mutate `unsafe fn`s.
+    pub mutate_unsafe: bool,
+    /// Cfg predicates, such as `fuzzing`, that are false when the tests run, so that code
+    /// only compiled when they're true isn't mutated.
+    pub exclude_cfgs: Vec<String>,
+    /// Don't mutate trivial accessors, whose body only reads or assigns a field.
+    pub skip_trivial: bool,
+    /// Don't mutate functions containing `unsafe` blocks.
+    pub skip_unsafe_blocks: bool,
+    /// The mutation operators to apply.
+    pub operators: Operators,
+}
+
+impl DiscoveryOptions {
+    /// The options for visiting each file.
+    pub fn visit_options(&self) -> VisitOptions {
+        VisitOptions {
+            mutate_unsafe: self.mutate_unsafe,
+            exclude_cfgs: self.exclude_cfgs.clone(),
+            skip_trivial: self.skip_trivial,
+            skip_unsafe_blocks: self.skip_unsafe_blocks,
+        }
+    }
+}
+
+/// A selection of targets to mutate, like Cargo's `--lib`, `--bin`, `--bins`, `--example`, and
+/// `--examples` options.
+///
+/// If nothing is selected, all targets are mutated, subject to the other discovery options.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TargetSelection {
+    /// Select the library.
+    pub lib: bool,
+    /// Select binaries by name.
+    pub bins: Vec<String>,
+    /// Select all binaries.
+    pub all_bins: bool,
+    /// Select examples by name.
+    pub examples: Vec<String>,
+    /// Select all examples.
+    pub all_examples: bool,
+}
+
+impl TargetSelection {
+    /// True if no targets are specifically selected, so all should be mutated.
+    pub fn is_empty(&self) -> bool {
+        *self == TargetSelection::default()
+    }
+
+    /// True if any examples are selected.
+    fn has_examples(&self) -> bool {
+        self.all_examples || !self.examples.is_empty()
+    }
+
+    /// True if the given target should be mutated.
+    pub fn includes(&self, target: &Target) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match target {
+            Target::Lib | Target::ProcMacro => self.lib,
+            Target::Bin(name) => self.all_bins || self.bins.contains(name),
+            Target::Example(name) => self.all_examples || self.examples.contains(name),
+            Target::Bench(_) | Target::Test(_) => false,
+        }
+    }
+}
+
+/// A file, or other code, that is deliberately not mutated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExcludedFile {
+    /// The path of the file, or a description of where the code comes from.
+    pub name: String,
+
+    /// Why the file is excluded.
+    pub reason: &'static str,
+}
+
+/// The source files found in a tree, and those that were found but excluded.
+#[derive(Default)]
+pub struct DiscoveredFiles {
+    pub files: Vec<SourceFile>,
+    pub excluded: Vec<ExcludedFile>,
+
+    /// Names of binary targets that contain no `#[test]` functions or `#[cfg(test)]` code.
+    pub bins_without_unit_tests: Vec<String>,
+
+    /// True if the package has any integration test targets in `tests/`.
+    pub has_integration_tests: bool,
+
+    /// The mutation operators to apply to the files.
+    pub operators: Operators,
+
+    /// Which functions and items in the files are mutated.
+    pub visit_options: VisitOptions,
+}
+
 #[derive(Debug)]
 pub struct SourceTree {
     root: PathBuf,
 }
 
+/// A directory where Cargo discovers targets automatically, such as `examples`, and the
+/// kind of target made from each one, given its name.
+type TargetDir = (&'static str, fn(String) -> Target);
+
 impl SourceTree {
     pub fn new(root: &Path) -> Result<SourceTree> {
         if !root.join("Cargo.toml").is_file() {
@@ -82,41 +326,421 @@ impl SourceTree {
     }
 
     /// Return all the mutations that could possibly be applied to this tree.
-    pub fn mutations(&self) -> Result<Vec<Mutation>> {
-        let mut r = Vec::new();
-        for sf in self.source_files() {
-            r.extend(Rc::new(sf).mutations()?);
-        }
-        Ok(r)
+    pub fn mutations(&self, options: &DiscoveryOptions) -> Result<Vec<Mutation>> {
+        self.discover_files(options).mutations()
     }
 
-    /// Return an iterator of `src/**/*.rs` paths relative to the root.
-    pub fn source_files(&self) -> impl Iterator<Item = SourceFile> + '_ {
-        walkdir::WalkDir::new(self.root.join("src"))
-            .sort_by_file_name()
-            .into_iter()
-            .filter_map(|r| {
-                r.map_err(|err| eprintln!("error walking source tree: {:?}", err))
-                    .ok()
-            })
-            .filter(|entry| entry.file_type().is_file())
-            .map(|entry| entry.into_path())
-            .filter(|path| {
-                path.extension()
-                    .map_or(false, |p| p.eq_ignore_ascii_case("rs"))
-            })
-            .filter_map(move |full_path| {
-                let tree_relative = full_path.strip_prefix(&self.root).unwrap();
-                SourceFile::new(&self.root, tree_relative)
-                    .map_err(|err| {
-                        eprintln!(
-                            "error reading source {}: {}",
-                            full_path.to_slash_lossy(),
-                            err
-                        );
-                    })
-                    .ok()
+    /// Return all the source files in the crate, in order by their path.
+    pub fn source_files(&self, options: &DiscoveryOptions) -> Vec<SourceFile> {
+        self.discover_files(options).files
+    }
+
+    /// Find all the source files in the crate, and any that are excluded from mutation.
+    ///
+    /// Files are found by starting from the crate's top-level source files (`src/lib.rs`,
+    /// `src/main.rs`, and `src/bin/*`, plus optionally examples, benches, and integration
+    /// tests) and following `mod` declarations, including those with `#[path]` attributes.
+    ///
+    /// If the tree's top-level manifest is a virtual workspace, with a `[workspace]` but no
+    /// `[package]`, the source of each member package is found instead.
+    ///
+    /// Files matching patterns in `.mutants-ignore`, or the exclude globs in the options, are
+    /// excluded, although `mod` declarations within them are still followed.
+    ///
+    /// The build script, and code included from the build script's `OUT_DIR`, are never
+    /// mutated, because they're not part of the code under test and, in the case of generated
+    /// code, changes to it could not be acted upon.
+    pub fn discover_files(&self, options: &DiscoveryOptions) -> DiscoveredFiles {
+        let mut discovered = DiscoveredFiles {
+            operators: options.operators.clone(),
+            visit_options: options.visit_options(),
+            ..Default::default()
+        };
+        let root_manifest = match manifest::read_manifest(&self.root) {
+            Ok(manifest) => Some(manifest),
+            Err(err) => {
+                eprintln!("{:?}", err);
+                None
+            }
+        };
+        let package_dirs = match &root_manifest {
+            Some(manifest)
+                if manifest.get("package").is_none() && manifest.get("workspace").is_some() =>
+            {
+                manifest::workspace_members(manifest, &self.root)
+            }
+            _ => vec![PathBuf::new()],
+        };
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut package_names: HashMap<PathBuf, String> = HashMap::new();
+        // Files to visit, whether they're `mod.rs` files, and the target and package they're in.
+        let mut queue: Vec<(PathBuf, bool, Target, PathBuf, Vec<String>)> = Vec::new();
+        for package_dir in package_dirs {
+            let manifest = if package_dir.as_os_str().is_empty() {
+                root_manifest.clone()
+            } else {
+                match manifest::read_manifest(&self.root.join(&package_dir)) {
+                    Ok(manifest) => Some(manifest),
+                    Err(err) => {
+                        eprintln!("{:?}", err);
+                        None
+                    }
+                }
+            };
+            package_names.insert(
+                package_dir.clone(),
+                manifest
+                    .as_ref()
+                    .and_then(manifest::package_name)
+                    .unwrap_or_else(|| package_dir.to_slash_lossy()),
+            );
+            let build_script = manifest.as_ref().and_then(|manifest| {
+                manifest::build_script_path(manifest, &self.root.join(&package_dir))
+                    .and_then(|path| normalize_relative_path(&package_dir.join(path)))
+            });
+            let main_bin_name = manifest.as_ref().and_then(|manifest| {
+                manifest::main_bin_name(manifest, &self.root.join(&package_dir))
+            });
+            let lib_target = if matches!(&manifest, Some(manifest) if manifest::is_proc_macro(manifest))
+            {
+                Target::ProcMacro
+            } else {
+                Target::Lib
+            };
+            discovered.has_integration_tests |= !self
+                .auto_target_paths(&package_dir.join("tests"))
+                .is_empty()
+                || matches!(
+                    manifest.as_ref().and_then(|manifest| manifest.get("test")),
+                    Some(toml::Value::Array(tests)) if !tests.is_empty()
+                );
+            if let Some(build_script) = build_script {
+                discovered.excluded.push(ExcludedFile {
+                    name: build_script.to_slash_lossy(),
+                    reason: "build script",
+                });
+                seen.insert(build_script);
+            }
+            queue.extend(
+                self.top_source_paths(&package_dir, options, lib_target, main_bin_name.as_deref())
+                    .into_iter()
+                    .filter(|(_path, target)| options.targets.includes(target))
+                    .map(|(path, target)| (path, true, target, package_dir.clone(), Vec::new())),
+            );
+        }
+        let mut targets_with_tests: HashSet<Target> = HashSet::new();
+        let mut no_std_targets: HashSet<Target> = HashSet::new();
+        let exclude_matcher = self.exclude_matcher(options);
+        let structure_options = VisitOptions {
+            exclude_cfgs: options.exclude_cfgs.clone(),
+            ..Default::default()
+        };
+        // Visit each library, and all its modules, before the binaries, so that a module
+        // declared by both is mutated as part of the library, and so tested by all the tests
+        // in the package, rather than only those of one binary.
+        queue.reverse();
+        while let Some((tree_relative, is_mod_rs, target, package_dir, namespace)) = queue.pop() {
+            if !seen.insert(tree_relative.clone()) {
+                continue;
+            }
+            let bytes = match std::fs::read(self.root.join(&tree_relative)) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    eprintln!(
+                        "error reading source {}: {}",
+                        tree_relative.to_slash_lossy(),
+                        err
+                    );
+                    continue;
+                }
+            };
+            let mut source_file = match SourceFile::from_bytes(&tree_relative, &bytes) {
+                Ok(source_file) => source_file,
+                Err(reason) => {
+                    discovered.exclude_unmutable(&tree_relative, reason);
+                    continue;
+                }
+            };
+            // Module declarations and `include!`s in the file are found within its namespace.
+            source_file.namespace = namespace;
+            // Discovery only needs the structure of the file, not its mutants.
+            match source_file.visit(&Operators::none(), &structure_options) {
+                Ok(visitor) => {
+                    if visitor.has_tests {
+                        targets_with_tests.insert(target.clone());
+                    }
+                    // `#![no_std]` can only be on the top file of a target, which is visited
+                    // before any of its modules.
+                    if visitor.no_std {
+                        no_std_targets.insert(target.clone());
+                    }
+                    for mod_reference in &visitor.mod_references {
+                        if let Some((path, is_mod_rs)) =
+                            self.find_mod_file(&tree_relative, is_mod_rs, mod_reference)
+                        {
+                            queue.push((
+                                path,
+                                is_mod_rs,
+                                target.clone(),
+                                package_dir.clone(),
+                                mod_reference.namespace.clone(),
+                            ));
+                        }
+                    }
+                    for include_reference in &visitor.include_references {
+                        let reason = if include_reference.is_out_dir() {
+                            "generated code in OUT_DIR"
+                        } else if !include_reference.in_module {
+                            "included code isn't a module's items"
+                        } else {
+                            match self.find_include_file(
+                                &tree_relative,
+                                &package_dir,
+                                include_reference,
+                            ) {
+                                Ok(path) => {
+                                    queue.push((
+                                        path,
+                                        is_mod_rs,
+                                        target.clone(),
+                                        package_dir.clone(),
+                                        include_reference.namespace.clone(),
+                                    ));
+                                    continue;
+                                }
+                                Err(reason) => reason,
+                            }
+                        };
+                        discovered.excluded.push(ExcludedFile {
+                            name: format!(
+                                "{}:{}: include!({})",
+                                tree_relative.to_slash_lossy(),
+                                include_reference.line,
+                                include_reference.arg
+                            ),
+                            reason,
+                        });
+                    }
+                }
+                Err(err) => {
+                    eprintln!(
+                        "error parsing source {}: {}",
+                        tree_relative.to_slash_lossy(),
+                        err
+                    );
+                    discovered.exclude_unmutable(&tree_relative, "syntax error");
+                    continue;
+                }
+            }
+            if exclude_matcher
+                .matched_path_or_any_parents(&tree_relative, false)
+                .is_ignore()
+            {
+                // Still followed to find its children, which may not be excluded.
+                discovered.excluded.push(ExcludedFile {
+                    name: tree_relative.to_slash_lossy(),
+                    reason: "matches an exclude pattern",
+                });
+                continue;
+            }
+            source_file.no_std = no_std_targets.contains(&target);
+            source_file.package = package_names[&package_dir].clone();
+            source_file.target = target;
+            discovered.files.push(source_file);
+        }
+        discovered
+            .files
+            .sort_by(|a, b| a.tree_relative.cmp(&b.tree_relative));
+        discovered.bins_without_unit_tests = discovered
+            .files
+            .iter()
+            .filter_map(|file| match &file.target {
+                Target::Bin(name) if !targets_with_tests.contains(&file.target) => {
+                    Some(name.clone())
+                }
+                _ => None,
             })
+            .collect();
+        discovered.bins_without_unit_tests.sort();
+        discovered.bins_without_unit_tests.dedup();
+        discovered.excluded.sort_by(|a, b| a.name.cmp(&b.name));
+        discovered
+    }
+
+    /// Build a matcher for files that should never be mutated, from the `.mutants-ignore` file
+    /// in the tree and any exclude globs in the options.
+    fn exclude_matcher(&self, options: &DiscoveryOptions) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(&self.root);
+        let ignore_path = self.root.join(MUTANTS_IGNORE);
+        if ignore_path.is_file() {
+            if let Some(err) = builder.add(&ignore_path) {
+                eprintln!("error reading {}: {}", ignore_path.to_slash_lossy(), err);
+            }
+        }
+        for glob in &options.exclude_globs {
+            if let Err(err) = builder.add_line(None, glob) {
+                eprintln!("invalid exclude pattern {:?}: {}", glob, err);
+            }
+        }
+        builder.build().unwrap_or_else(|err| {
+            eprintln!("error building exclude patterns: {}", err);
+            Gitignore::empty()
+        })
+    }
+
+    /// Return the tree-relative paths of the top-level source files of the targets of the
+    /// package in `package_dir`, and the target each one is the root of.
+    ///
+    /// `lib_target` is the target built from `src/lib.rs`, and `main_bin_name` is the name of
+    /// the binary built from `src/main.rs`.
+    fn top_source_paths(
+        &self,
+        package_dir: &Path,
+        options: &DiscoveryOptions,
+        lib_target: Target,
+        main_bin_name: Option<&str>,
+    ) -> Vec<(PathBuf, Target)> {
+        let mut paths = Vec::new();
+        let lib_path = package_dir.join("src/lib.rs");
+        if self.root.join(&lib_path).is_file() {
+            paths.push((lib_path, lib_target));
+        }
+        let main_path = package_dir.join("src/main.rs");
+        if self.root.join(&main_path).is_file() {
+            let name = main_bin_name.unwrap_or("main").to_owned();
+            paths.push((main_path, Target::Bin(name)));
+        }
+        let mut target_dirs: Vec<TargetDir> = vec![("src/bin", Target::Bin)];
+        if options.include_examples || options.targets.has_examples() {
+            target_dirs.push(("examples", Target::Example));
+        }
+        if options.include_benches {
+            target_dirs.push(("benches", Target::Bench));
+        }
+        if options.include_test_helpers {
+            target_dirs.push(("tests", Target::Test));
+        }
+        for (target_dir, make_target) in target_dirs {
+            for (path, name) in self.auto_target_paths(&package_dir.join(target_dir)) {
+                paths.push((path, make_target(name)));
+            }
+        }
+        paths
+    }
+
+    /// Return the top-level files of targets that Cargo automatically discovers in a directory
+    /// such as `src/bin` or `examples`: either `*.rs` or `*/main.rs`, along with the names of
+    /// the targets.
+    fn auto_target_paths(&self, target_dir: &Path) -> Vec<(PathBuf, String)> {
+        let mut paths = Vec::new();
+        if let Ok(read_dir) = self.root.join(target_dir).read_dir() {
+            for entry in read_dir.filter_map(|r| r.ok()) {
+                let file_name = PathBuf::from(entry.file_name());
+                let tree_relative = target_dir.join(&file_name);
+                if matches!(file_name.extension(), Some(ext) if ext.eq_ignore_ascii_case("rs"))
+                    && self.root.join(&tree_relative).is_file()
+                {
+                    let name = file_name
+                        .file_stem()
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned();
+                    paths.push((tree_relative, name));
+                } else if self.root.join(&tree_relative).join("main.rs").is_file() {
+                    let name = file_name.to_string_lossy().into_owned();
+                    paths.push((tree_relative.join("main.rs"), name));
+                }
+            }
+        }
+        // Directories are listed in a different order on different platforms and filesystems,
+        // but which target owns a shared module, and so the order of the mutants, mustn't be.
+        paths.sort();
+        paths
+    }
+
+    /// Find the tree-relative path of the file included by an `include!` macro in `parent`,
+    /// or return the reason it can't be mutated.
+    fn find_include_file(
+        &self,
+        parent: &Path,
+        package_dir: &Path,
+        include_reference: &IncludeReference,
+    ) -> Result<PathBuf, &'static str> {
+        let path = match include_reference.path() {
+            Some(IncludePath::RelativeToFile(path)) => {
+                parent.parent().unwrap_or_else(|| Path::new("")).join(path)
+            }
+            Some(IncludePath::RelativeToPackage(path)) => package_dir.join(path),
+            None => return Err("include! path can't be determined"),
+        };
+        match normalize_relative_path(&path) {
+            Some(path) if self.root.join(&path).is_file() => Ok(path),
+            Some(_) => Err("included file not found"),
+            None => Err("included file is outside the source tree"),
+        }
+    }
+
+    /// Find the file containing the body of a `mod` declared in `parent`.
+    ///
+    /// `parent_is_mod_rs` is true if the parent is a crate root, a `mod.rs`, or was itself
+    /// loaded by a `#[path]` attribute: in those cases child modules are found in the same
+    /// directory as the parent, rather than in a subdirectory named after it.
+    ///
+    /// Returns the tree-relative path of the file, and whether it is itself "mod-rs".
+    fn find_mod_file(
+        &self,
+        parent: &Path,
+        parent_is_mod_rs: bool,
+        mod_reference: &ModReference,
+    ) -> Option<(PathBuf, bool)> {
+        let parent_dir = parent.parent().unwrap_or_else(|| Path::new(""));
+        let candidates = match &mod_reference.path_attr {
+            Some(path_attr) if mod_reference.inline_dirs.is_empty() => {
+                // A path attribute outside of any inline module is relative to the directory
+                // containing the declaring file.
+                vec![(parent_dir.join(path_attr), true)]
+            }
+            _ => {
+                let mut dir = parent_dir.to_owned();
+                if !parent_is_mod_rs {
+                    dir.push(parent.file_stem().expect("source file has a stem"));
+                }
+                dir.extend(&mod_reference.inline_dirs);
+                match &mod_reference.path_attr {
+                    Some(path_attr) => vec![(dir.join(path_attr), true)],
+                    None => vec![
+                        (dir.join(format!("{}.rs", mod_reference.name)), false),
+                        (dir.join(&mod_reference.name).join("mod.rs"), true),
+                    ],
+                }
+            }
+        };
+        for (path, is_mod_rs) in &candidates {
+            match normalize_relative_path(path) {
+                Some(path) if self.root.join(&path).is_file() => return Some((path, *is_mod_rs)),
+                Some(_) => (),
+                None => {
+                    eprintln!(
+                        "module {} in {} is outside the source tree: {}",
+                        mod_reference.name,
+                        parent.to_slash_lossy(),
+                        path.to_slash_lossy()
+                    );
+                    return None;
+                }
+            }
+        }
+        eprintln!(
+            "module {} in {} not found: tried {}",
+            mod_reference.name,
+            parent.to_slash_lossy(),
+            candidates
+                .iter()
+                .map(|(path, _)| path.to_slash_lossy())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        None
     }
 
     /// Return the path (possibly relative) to the root of the source tree.
@@ -125,6 +749,90 @@ impl SourceTree {
     }
 }
 
+impl DiscoveredFiles {
+    /// Record that a source file can't be mutated, and warn about it.
+    fn exclude_unmutable(&mut self, tree_relative: &Path, reason: &'static str) {
+        let name = tree_relative.to_slash_lossy();
+        eprintln!("warning: skipping {}: {}", name, reason);
+        self.excluded.push(ExcludedFile { name, reason });
+    }
+
+    /// True if some test could catch a mutant in the target.
+    ///
+    /// Mutants in a binary can only be caught by its own unit tests or by integration tests, so
+    /// if it has neither, they can't be caught.
+    pub fn can_be_caught(&self, target: &Target) -> bool {
+        match target {
+            Target::Bin(name) => {
+                self.has_integration_tests || !self.bins_without_unit_tests.contains(name)
+            }
+            _ => true,
+        }
+    }
+
+    /// Return all the mutations that could possibly be applied to the discovered files.
+    pub fn mutations(&self) -> Result<Vec<Mutation>> {
+        self.discover().map(|(mutations, _skipped)| mutations)
+    }
+
+    /// Return all the mutations in the discovered files, and everything that's skipped: the
+    /// excluded files, and the functions and items within the other files.
+    pub fn discover(&self) -> Result<(Vec<Mutation>, Vec<Skipped>)> {
+        let mut mutations = Vec::new();
+        let mut skipped: Vec<Skipped> = self
+            .excluded
+            .iter()
+            .map(|excluded| Skipped {
+                file: excluded.name.clone(),
+                name: None,
+                line: None,
+                reason: SkipReason::ExcludedFile,
+                detail: Some(excluded.reason.to_owned()),
+            })
+            .collect();
+        for sf in &self.files {
+            let (file_mutations, file_skipped) =
+                sf.discover(&self.operators, &self.visit_options)?;
+            mutations.extend(file_mutations);
+            skipped.extend(file_skipped);
+        }
+        Ok((mutations, skipped))
+    }
+}
+
+/// Find the mutants in some Rust source text, without reading a source tree or running cargo.
+///
+/// This is for tools, such as editors and review bots, that want to show what would be mutated
+/// in one file. `path` is only used to describe the mutants, and is normally relative to the
+/// tree root. The file is treated as part of a library, and its `mod` statements aren't
+/// followed.
+pub fn discover_mutations(path: &Path, code: &str, operators: &Operators) -> Result<Vec<Mutation>> {
+    SourceFile::from_bytes(path, code.as_bytes())
+        .map_err(|reason| anyhow!("{}: {}", path.to_slash_lossy(), reason))?
+        .mutations(operators)
+        .with_context(|| format!("discover mutations in {}", path.to_slash_lossy()))
+}
+
+/// Lexically remove `.` and `..` components from a relative path.
+///
+/// Returns None if the path would escape above its starting directory.
+pub fn normalize_relative_path(path: &Path) -> Option<PathBuf> {
+    let mut r = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if !r.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(name) => r.push(name),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(r)
+}
+
 #[cfg(test)]
 mod test {
     use std::fs::File;
@@ -133,13 +841,35 @@ mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::test_util::write_tree;
+
+    #[test]
+    fn discover_mutations_in_text() {
+        let mutations = discover_mutations(
+            Path::new("./src/lib.rs"),
+            "mod inner {\n    pub fn ok() -> bool {\n        true\n    }\n}\n",
+            &Operators::all(),
+        )
+        .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace inner::ok with true in src/lib.rs:2",
+                "replace inner::ok with false in src/lib.rs:2",
+                "replace true with false in inner::ok in src/lib.rs:3",
+            ]
+        );
+
+        let err =
+            discover_mutations(Path::new("src/bad.rs"), "fn (", &Operators::all()).unwrap_err();
+        assert_eq!(err.to_string(), "discover mutations in src/bad.rs");
+    }
 
     #[test]
     fn source_files_in_testdata_factorial() {
         let source_paths = SourceTree::new(Path::new("testdata/tree/factorial"))
             .unwrap()
-            .source_files()
-            .collect::<Vec<SourceFile>>();
+            .source_files(&DiscoveryOptions::default());
         assert_eq!(source_paths.len(), 1);
         assert_eq!(
             source_paths[0].tree_relative,
@@ -147,6 +877,474 @@ mod test {
         );
     }
 
+    #[test]
+    fn source_files_follow_path_attributes() {
+        let source_paths = SourceTree::new(Path::new("testdata/tree/path_attr"))
+            .unwrap()
+            .source_files(&DiscoveryOptions::default())
+            .iter()
+            .map(SourceFile::tree_relative_slashes)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            source_paths,
+            [
+                "shared/util.rs",
+                "src/inline/renamed_file.rs",
+                "src/lib.rs",
+                "src/plain/child.rs",
+                "src/plain.rs",
+                "src/sys/other.rs",
+                "src/sys/unix.rs",
+            ]
+        );
+        let mut function_names = SourceTree::new(Path::new("testdata/tree/path_attr"))
+            .unwrap()
+            .mutations(&DiscoveryOptions {
+                operators: Operators::all(),
+                ..Default::default()
+            })
+            .unwrap()
+            .iter()
+            .map(|mutation| mutation.function_name().to_owned())
+            .collect::<Vec<String>>();
+        function_names.dedup();
+        assert_eq!(
+            function_names,
+            [
+                "util::double",
+                "inline::renamed::double",
+                "sum_of_doubles",
+                "plain::child::is_even",
+                "sys::path_separator",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_script_and_out_dir_includes_are_excluded() {
+        let discovered = SourceTree::new(Path::new("testdata/tree/build_script"))
+            .unwrap()
+            .discover_files(&DiscoveryOptions::default());
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<String>>(),
+            ["src/lib.rs"]
+        );
+        assert_eq!(
+            discovered.excluded,
+            [
+                ExcludedFile {
+                    name: "build.rs".to_owned(),
+                    reason: "build script",
+                },
+                ExcludedFile {
+                    name:
+                        r#"src/lib.rs:3: include!(concat ! (env ! ("OUT_DIR") , "/generated.rs"))"#
+                            .to_owned(),
+                    reason: "generated code in OUT_DIR",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn examples_benches_and_tests_are_optional() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", ""),
+            ("src/lib.rs", ""),
+            ("examples/demo.rs", "fn main() {}"),
+            ("examples/multi/main.rs", "fn main() {}"),
+            ("benches/speed.rs", "fn main() {}"),
+            ("tests/api.rs", "mod common;"),
+            ("tests/common/mod.rs", "pub fn setup() {}"),
+        ]);
+        let source_tree = SourceTree::new(tmp.path()).unwrap();
+        let list_files = |options: &DiscoveryOptions| {
+            source_tree
+                .source_files(options)
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(list_files(&DiscoveryOptions::default()), ["src/lib.rs"]);
+        assert_eq!(
+            list_files(&DiscoveryOptions {
+                include_examples: true,
+                include_benches: true,
+                include_test_helpers: true,
+                ..DiscoveryOptions::default()
+            }),
+            [
+                "benches/speed.rs",
+                "examples/demo.rs",
+                "examples/multi/main.rs",
+                "src/lib.rs",
+                "tests/api.rs",
+                "tests/common/mod.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_identifier_modules_are_found_without_the_prefix() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            (
+                "src/lib.rs",
+                "mod r#type;\nmod r#impl {\n    mod r#where;\n}\n",
+            ),
+            ("src/type.rs", "pub fn r#loop() -> bool { true }\n"),
+            ("src/impl/where.rs", "pub fn f() {}\n"),
+        ]);
+        let root = tmp.path();
+        let source_files = SourceTree::new(root)
+            .unwrap()
+            .source_files(&DiscoveryOptions::default())
+            .iter()
+            .map(SourceFile::tree_relative_slashes)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            source_files,
+            ["src/impl/where.rs", "src/lib.rs", "src/type.rs"]
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn tree_relative_paths_use_forward_slashes() {
+        let source_file =
+            SourceFile::from_bytes(Path::new("src\\inner\\mod.rs"), b"pub fn f() {}\n").unwrap();
+        assert_eq!(source_file.tree_relative_slashes(), "src/inner/mod.rs");
+        let mutations = source_file.mutations(&Operators::all()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&mutations[0]).unwrap()["file"],
+            "src/inner/mod.rs"
+        );
+        assert!(mutations[0].diff().starts_with("--- src/inner/mod.rs\n"));
+    }
+
+    #[test]
+    fn files_are_assigned_to_targets() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            ("src/lib.rs", "pub fn f() {}\n"),
+            ("src/main.rs", "mod cli;\nfn main() {}\n"),
+            ("src/cli.rs", "#[test]\nfn t() {}\n"),
+            ("src/bin/tool.rs", "fn main() {}\n"),
+            ("src/bin/main.rs", "fn main() {}\n"),
+        ]);
+        let root = tmp.path();
+        let discovered = SourceTree::new(root)
+            .unwrap()
+            .discover_files(&DiscoveryOptions::default());
+        let targets = discovered
+            .files
+            .iter()
+            .map(|file| (file.tree_relative_slashes(), file.target.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            [
+                ("src/bin/main.rs".to_owned(), Target::Bin("main".to_owned())),
+                ("src/bin/tool.rs".to_owned(), Target::Bin("tool".to_owned())),
+                ("src/cli.rs".to_owned(), Target::Bin("pkg".to_owned())),
+                ("src/lib.rs".to_owned(), Target::Lib),
+                ("src/main.rs".to_owned(), Target::Bin("pkg".to_owned())),
+            ]
+        );
+        assert_eq!(discovered.bins_without_unit_tests, ["main", "tool"]);
+        assert!(!discovered.has_integration_tests);
+        assert!(discovered.can_be_caught(&Target::Lib));
+        assert!(discovered.can_be_caught(&Target::Bin("pkg".to_owned())));
+        assert!(!discovered.can_be_caught(&Target::Bin("tool".to_owned())));
+    }
+
+    #[test]
+    fn modules_shared_by_library_and_binary_belong_to_the_library() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            ("src/lib.rs", "pub mod shared;\n"),
+            ("src/main.rs", "mod shared;\nmod cli;\nfn main() {}\n"),
+            (
+                "src/bin/tool.rs",
+                "#[path = \"../shared.rs\"]\nmod shared;\nfn main() {}\n",
+            ),
+            ("src/shared.rs", "pub fn f() {}\n"),
+            ("src/cli.rs", "pub fn run() {}\n"),
+        ]);
+        let root = tmp.path();
+        let targets = SourceTree::new(root)
+            .unwrap()
+            .source_files(&DiscoveryOptions::default())
+            .iter()
+            .map(|file| (file.tree_relative_slashes(), file.target.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            [
+                ("src/bin/tool.rs".to_owned(), Target::Bin("tool".to_owned())),
+                ("src/cli.rs".to_owned(), Target::Bin("pkg".to_owned())),
+                ("src/lib.rs".to_owned(), Target::Lib),
+                ("src/main.rs".to_owned(), Target::Bin("pkg".to_owned())),
+                ("src/shared.rs".to_owned(), Target::Lib),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_std_crates_get_only_core_replacements() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            (
+                "src/lib.rs",
+                "#![no_std]\nextern crate alloc;\nuse alloc::string::String;\nmod names;\n",
+            ),
+            (
+                "src/names.rs",
+                "use alloc::string::String;\npub fn name() -> String {\n    String::new()\n}\n\
+                 pub fn title() -> &'static str {\n    \"\"\n}\n",
+            ),
+        ]);
+        let root = tmp.path();
+        let discovered = SourceTree::new(root)
+            .unwrap()
+            .discover_files(&DiscoveryOptions::default());
+        assert!(discovered.files.iter().all(|file| file.no_std));
+        let mutations = discovered.mutations().unwrap();
+        let replacements = mutations
+            .iter()
+            .map(|mutation| mutation.replacement_text())
+            .collect::<Vec<_>>();
+        // String literals are in core, so string slices can still be replaced.
+        assert_eq!(
+            replacements,
+            ["Default::default()", "\"\".into()", "\"xyzzy\".into()"]
+        );
+    }
+
+    #[test]
+    fn targets_can_be_selected() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            ("src/lib.rs", "fn f() {}\n"),
+            ("src/main.rs", "fn f() {}\n"),
+            ("src/bin/tool.rs", "fn f() {}\n"),
+            ("examples/demo.rs", "fn f() {}\n"),
+            ("examples/other.rs", "fn f() {}\n"),
+        ]);
+        let root = tmp.path();
+        let source_tree = SourceTree::new(root).unwrap();
+        let selected_files = |targets: TargetSelection| {
+            source_tree
+                .discover_files(&DiscoveryOptions {
+                    targets,
+                    ..DiscoveryOptions::default()
+                })
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            selected_files(TargetSelection::default()),
+            ["src/bin/tool.rs", "src/lib.rs", "src/main.rs"]
+        );
+        assert_eq!(
+            selected_files(TargetSelection {
+                lib: true,
+                ..TargetSelection::default()
+            }),
+            ["src/lib.rs"]
+        );
+        assert_eq!(
+            selected_files(TargetSelection {
+                bins: vec!["tool".to_owned()],
+                examples: vec!["demo".to_owned()],
+                ..TargetSelection::default()
+            }),
+            ["examples/demo.rs", "src/bin/tool.rs"]
+        );
+        assert_eq!(
+            selected_files(TargetSelection {
+                all_bins: true,
+                all_examples: true,
+                ..TargetSelection::default()
+            }),
+            [
+                "examples/demo.rs",
+                "examples/other.rs",
+                "src/bin/tool.rs",
+                "src/main.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn included_files_are_found() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            (
+                "src/lib.rs",
+                r#"include!("tables.rs");
+include!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/more.rs"));
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+include!(path_of!());
+include!("missing.rs");
+std::include!("split.rs",);
+pub fn table_len() -> usize {
+    include!("len.rs")
+}
+"#,
+            ),
+            ("src/split.rs", "pub fn split() -> u32 {\n    3\n}\n"),
+            ("src/len.rs", "3\n"),
+            ("src/tables.rs", "pub fn table() -> u32 {\n    1\n}\n"),
+            ("data/more.rs", "pub fn more() -> u32 {\n    2\n}\n"),
+        ]);
+        let root = tmp.path();
+        let discovered = SourceTree::new(root)
+            .unwrap()
+            .discover_files(&DiscoveryOptions::default());
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<_>>(),
+            [
+                "data/more.rs",
+                "src/lib.rs",
+                "src/split.rs",
+                "src/tables.rs"
+            ]
+        );
+        assert_eq!(
+            discovered
+                .excluded
+                .iter()
+                .map(|excluded| (excluded.name.as_str(), excluded.reason))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    r#"src/lib.rs:3: include!(concat ! (env ! ("OUT_DIR") , "/generated.rs"))"#,
+                    "generated code in OUT_DIR"
+                ),
+                (
+                    r#"src/lib.rs:4: include!(path_of ! ())"#,
+                    "include! path can't be determined"
+                ),
+                (
+                    r#"src/lib.rs:5: include!("missing.rs")"#,
+                    "included file not found"
+                ),
+                (
+                    r#"src/lib.rs:8: include!("len.rs")"#,
+                    "included code isn't a module's items"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmutable_files_are_excluded() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", &b"[package]\nname = 'pkg'\n"[..]),
+            (
+                "src/lib.rs",
+                b"mod bom;\nmod latin1;\nmod broken;\nmod good;\n",
+            ),
+            ("src/bom.rs", b"\xef\xbb\xbfpub fn f() {}\n"),
+            ("src/latin1.rs", b"// caf\xe9\npub fn f() {}\n"),
+            ("src/broken.rs", b"pub fn f( {}\n"),
+            ("src/good.rs", b"pub fn f() {}\n"),
+        ]);
+        let root = tmp.path();
+        let discovered = SourceTree::new(root)
+            .unwrap()
+            .discover_files(&DiscoveryOptions::default());
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<_>>(),
+            ["src/good.rs", "src/lib.rs"]
+        );
+        assert_eq!(
+            discovered.excluded,
+            [
+                ExcludedFile {
+                    name: "src/bom.rs".into(),
+                    reason: "starts with a UTF-8 byte order mark",
+                },
+                ExcludedFile {
+                    name: "src/broken.rs".into(),
+                    reason: "syntax error",
+                },
+                ExcludedFile {
+                    name: "src/latin1.rs".into(),
+                    reason: "not valid UTF-8",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mutants_ignore_and_exclude_globs() {
+        let tmp = write_tree(&[
+            ("Cargo.toml", "[package]\nname = 'pkg'\n"),
+            (".mutants-ignore", "# Generated code\nsrc/generated/\n"),
+            ("src/lib.rs", "mod generated;\nmod legacy;\nmod util;\n"),
+            ("src/generated/mod.rs", "pub mod inner;\n"),
+            ("src/generated/inner.rs", "pub fn f() {}\n"),
+            ("src/legacy.rs", "pub mod child;\n"),
+            ("src/legacy/child.rs", "pub fn f() {}\n"),
+            ("src/util.rs", "pub fn f() {}\n"),
+        ]);
+        let root = tmp.path();
+        let discovered = SourceTree::new(root)
+            .unwrap()
+            .discover_files(&DiscoveryOptions {
+                exclude_globs: vec!["legacy.rs".to_owned()],
+                ..DiscoveryOptions::default()
+            });
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect::<Vec<_>>(),
+            ["src/legacy/child.rs", "src/lib.rs", "src/util.rs"]
+        );
+        assert_eq!(
+            discovered
+                .excluded
+                .iter()
+                .map(|excluded| excluded.name.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "src/generated/inner.rs",
+                "src/generated/mod.rs",
+                "src/legacy.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_relative_path() {
+        assert_eq!(
+            super::normalize_relative_path(Path::new("src/../shared/./util.rs")),
+            Some(PathBuf::from("shared/util.rs"))
+        );
+        assert_eq!(
+            super::normalize_relative_path(Path::new("src/../../outside.rs")),
+            None
+        );
+    }
+
     #[test]
     fn error_opening_subdirectory_of_crate() {
         let result = SourceTree::new(Path::new("testdata/tree/factorial/src"));
@@ -159,6 +1357,17 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn source_file_path_is_normalized() {
+        let source_file =
+            SourceFile::from_bytes(Path::new("./src/bin/../lib.rs"), b"fn f() {}\n").unwrap();
+        assert_eq!(source_file.tree_relative_slashes(), "src/lib.rs");
+        assert_eq!(
+            source_file.within_dir(Path::new("/scratch")),
+            Path::new("/scratch").join("src").join("lib.rs")
+        );
+    }
+
     #[test]
     fn source_file_normalizes_crlf() {
         let temp = tempfile::tempdir().unwrap();
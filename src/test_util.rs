@@ -0,0 +1,19 @@
+// Copyright 2022 Martin Pool
+
+//! Helpers for unit tests.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+/// Make a temporary directory containing files with the given paths and contents, creating
+/// their parent directories as needed.
+pub fn write_tree<C: AsRef<[u8]>>(files: &[(&str, C)]) -> TempDir {
+    let tmp = tempfile::tempdir().unwrap();
+    for (path, content) in files {
+        let path = tmp.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    tmp
+}
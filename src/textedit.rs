@@ -1,11 +1,43 @@
 // Copyright 2021 Martin Pool
 
 //! Edit source code.
+//!
+//! Positions are (line, column) pairs, as reported by `syn` and `proc_macro2`, and spans include
+//! both their start and end positions. These functions map spans to byte ranges in the text and
+//! replace them, so that programs using cargo-mutants as a library can apply or preview mutants
+//! themselves, perhaps several at once:
+//!
+//! ```
+//! use cargo_mutants::textedit::{apply_edits, Edit, LineColumn, Span};
+//!
+//! let code = "fn one() -> u32 {\n    1\n}\nfn two() -> u32 {\n    2\n}\n";
+//! let edits = [
+//!     Edit::new(Span::new(LineColumn::new(1, 17), LineColumn::new(3, 1)), "{ 0 }"),
+//!     Edit::new(Span::new(LineColumn::new(4, 17), LineColumn::new(6, 1)), "{ 0 }"),
+//! ];
+//! assert_eq!(
+//!     apply_edits(code, &edits)?,
+//!     "fn one() -> u32 { 0 }\nfn two() -> u32 { 0 }\n"
+//! );
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Columns are counted in chars, and a `\r` before a newline counts as part of the last column
+//! of its line, so CRLF line endings are kept or replaced together with the text before them.
+//!
+//! Finding a position means counting chars along its line, so to find many positions in one
+//! text, such as every mutant in a large file, make a [LineIndex] once and use its methods.
 
-use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 
 /// A (line, column) position in a source file.
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize)]
+///
+/// Positions are ordered by line and then by column.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub struct LineColumn {
     /// 1-based line number.
     pub line: usize,
@@ -14,6 +46,18 @@ pub struct LineColumn {
     pub column: usize,
 }
 
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> LineColumn {
+        LineColumn { line, column }
+    }
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 impl From<proc_macro2::LineColumn> for LineColumn {
     fn from(l: proc_macro2::LineColumn) -> Self {
         LineColumn {
@@ -26,7 +70,7 @@ impl From<proc_macro2::LineColumn> for LineColumn {
 /// A contiguous text span in a file.
 ///
 /// TODO: Perhaps a semi-open range that can represent an empty span would be more general?
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Span {
     /// The inclusive position where the span starts.
     pub start: LineColumn,
@@ -34,6 +78,28 @@ pub struct Span {
     pub end: LineColumn,
 }
 
+impl Span {
+    pub fn new(start: LineColumn, end: LineColumn) -> Span {
+        Span { start, end }
+    }
+
+    /// True if the position is within the span.
+    pub fn contains(&self, pos: LineColumn) -> bool {
+        self.start <= pos && pos <= self.end
+    }
+
+    /// True if the two spans have any position in common.
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
 impl From<proc_macro2::Span> for Span {
     fn from(s: proc_macro2::Span) -> Self {
         Span {
@@ -52,40 +118,150 @@ impl From<&proc_macro2::Span> for Span {
     }
 }
 
-/// Replace a subregion of text.
+/// A replacement of the text in a span.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Edit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn new<S: Into<String>>(span: Span, replacement: S) -> Edit {
+        Edit {
+            span,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+/// The byte offset where each line of a text starts, so that positions in the text can be
+/// found without scanning it from the start.
 ///
-/// Returns a copy of `s` with the region between `start` and `end` inclusive replaced by
-/// `replacement`.
-pub(crate) fn replace_region(
-    s: &str,
-    start: &LineColumn,
-    end: &LineColumn,
-    replacement: &str,
-) -> String {
-    // dbg!(start, end);
-    let mut r = String::with_capacity(s.len() + replacement.len());
-    let mut line_no = 1;
-    let mut col_no = 1;
-    for c in s.chars() {
-        if line_no < start.line
-            || line_no > end.line
-            || (line_no == start.line && col_no < start.column)
-            || (line_no == end.line && col_no > end.column)
-        {
-            r.push(c);
-        } else if line_no == start.line && col_no == start.column {
-            r.push_str(replacement);
+/// The index doesn't keep the text: each method must be given the same text that the index
+/// was made from.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LineIndex {
+    /// The offset of the first byte of each line; the first line starts at 0.
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(s: &str) -> LineIndex {
+        let line_starts = std::iter::once(0)
+            .chain(s.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        LineIndex { line_starts }
+    }
+
+    /// Return the byte offset of the first char at or after a position, or the length of the
+    /// text if there's none.
+    pub fn offset(&self, s: &str, pos: LineColumn) -> usize {
+        let line_start = match pos.line.checked_sub(1).map(|i| self.line_starts.get(i)) {
+            None => return 0, // line 0 is before the text
+            Some(None) => return s.len(),
+            Some(Some(&line_start)) => line_start,
+        };
+        let mut column = 1;
+        for (offset, c) in s[line_start..].char_indices() {
+            if column >= pos.column || c == '\n' {
+                // The position is on this line, or past its end and so at the start of the
+                // next line, which is just after the newline.
+                return line_start + offset + usize::from(column < pos.column);
+            }
+            if c != '\r' {
+                column += 1;
+            }
+        }
+        s.len()
+    }
+
+    /// Return the byte range of `s` covered by a span, or None if no text is in the span.
+    pub fn byte_range(&self, s: &str, span: &Span) -> Option<Range<usize>> {
+        let start = self.offset(s, span.start);
+        let end = self.offset(s, LineColumn::new(span.end.line, span.end.column + 1));
+        (start < end).then_some(start..end)
+    }
+
+    /// Return the text in a span, which is exactly the text that [LineIndex::replace] would
+    /// replace, or an empty string if no text is in the span.
+    pub fn extract<'s>(&self, s: &'s str, span: &Span) -> &'s str {
+        self.byte_range(s, span).map_or("", |range| &s[range])
+    }
+
+    /// Return a copy of `s` with the text in a span replaced, or unchanged if no text is in the
+    /// span.
+    pub fn replace(&self, s: &str, span: &Span, replacement: &str) -> String {
+        match self.byte_range(s, span) {
+            Some(range) => [&s[..range.start], replacement, &s[range.end..]].concat(),
+            None => s.to_owned(),
         }
-        if c == '\n' {
-            line_no += 1;
-            col_no = 1;
-        } else if c == '\r' {
-            // counts as part of the last column, not a separate column
-        } else {
-            col_no += 1;
+    }
+
+    /// Apply several edits to `s` at once: see [apply_edits].
+    pub fn apply_edits(&self, s: &str, edits: &[Edit]) -> Result<String> {
+        let spans: Vec<Span> = edits.iter().map(|edit| edit.span).collect();
+        if let Some((a, b)) = find_overlap(&spans) {
+            return Err(anyhow!("edits at {} and {} overlap", spans[a], spans[b]));
         }
+        let mut ranges = edits
+            .iter()
+            .map(|edit| {
+                self.byte_range(s, &edit.span)
+                    .map(|range| (range, edit.replacement.as_str()))
+                    .ok_or_else(|| anyhow!("edit at {} is outside the text", edit.span))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ranges.sort_by_key(|(range, _)| range.start);
+        let mut r = String::with_capacity(s.len());
+        let mut copied = 0;
+        for (range, replacement) in ranges {
+            r.push_str(&s[copied..range.start]);
+            r.push_str(replacement);
+            copied = range.end;
+        }
+        r.push_str(&s[copied..]);
+        Ok(r)
     }
-    r
+}
+
+/// Return the byte range of `s` covered by a span, or None if no text is in the span.
+pub fn byte_range(s: &str, span: &Span) -> Option<Range<usize>> {
+    LineIndex::new(s).byte_range(s, span)
+}
+
+/// Replace a subregion of text.
+///
+/// Returns a copy of `s` with the region between `start` and `end` inclusive replaced by
+/// `replacement`. If there's no text in the region, `s` is returned unchanged.
+pub fn replace_region(s: &str, start: &LineColumn, end: &LineColumn, replacement: &str) -> String {
+    LineIndex::new(s).replace(s, &Span::new(*start, *end), replacement)
+}
+
+/// Return the text of a subregion, between `start` and `end` inclusive.
+///
+/// This selects exactly the text that [replace_region] would replace.
+pub fn extract_region(s: &str, start: &LineColumn, end: &LineColumn) -> String {
+    LineIndex::new(s)
+        .extract(s, &Span::new(*start, *end))
+        .to_owned()
+}
+
+/// Return the indexes of the first pair of spans that overlap, if any do.
+pub fn find_overlap(spans: &[Span]) -> Option<(usize, usize)> {
+    let mut order: Vec<usize> = (0..spans.len()).collect();
+    order.sort_by_key(|&i| spans[i].start);
+    order
+        .windows(2)
+        .find(|pair| spans[pair[0]].overlaps(&spans[pair[1]]))
+        .map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+}
+
+/// Apply several edits to `s` at once.
+///
+/// The spans are all positions in the original text, and can be given in any order. It's an
+/// error if any of them overlap, or if a span contains no text.
+pub fn apply_edits(s: &str, edits: &[Edit]) -> Result<String> {
+    LineIndex::new(s).apply_edits(s, edits)
 }
 
 #[cfg(test)]
@@ -111,6 +287,113 @@ mod test {
         );
     }
 
+    #[test]
+    fn extract_region_matches_replaced_region() {
+        let source = "fn foo() {\n    some();\n}\nconst BAR: u32 = 32;\n";
+        let start = LineColumn {
+            line: 1,
+            column: 10,
+        };
+        let end = LineColumn { line: 3, column: 1 };
+        assert_eq!(extract_region(source, &start, &end), "{\n    some();\n}");
+        assert_eq!(
+            replace_region(source, &start, &end, &extract_region(source, &start, &end)),
+            source
+        );
+    }
+
+    #[test]
+    fn byte_range_counts_columns_in_chars() {
+        let source = "fn é() {\r\n    1\r\n}\r\n";
+        let span = Span::new(LineColumn::new(1, 8), LineColumn::new(3, 1));
+        assert_eq!(byte_range(source, &span), Some(8..19));
+        assert_eq!(&source[8..19], "{\r\n    1\r\n}");
+        assert_eq!(
+            byte_range(
+                source,
+                &Span::new(LineColumn::new(9, 1), LineColumn::new(9, 2))
+            ),
+            None
+        );
+    }
+
+    /// Find the byte range of a span the slow way, by working out the position of every char.
+    fn scan_byte_range(s: &str, span: &Span) -> Option<Range<usize>> {
+        let mut pos = LineColumn::new(1, 1);
+        let mut range: Option<Range<usize>> = None;
+        for (offset, c) in s.char_indices() {
+            if span.contains(pos) {
+                range = Some(range.map_or(offset, |range| range.start)..offset + c.len_utf8());
+            }
+            if c == '\n' {
+                pos = LineColumn::new(pos.line + 1, 1);
+            } else if c != '\r' {
+                pos.column += 1;
+            }
+        }
+        range
+    }
+
+    #[test]
+    fn line_index_finds_the_same_ranges_as_scanning() {
+        for source in [
+            "",
+            "x",
+            "fn é() {\r\n    1\r\n}\r\n",
+            "a\n\nbcd\nef",
+            "const Ü: &str = \"日本\";\n\n",
+        ] {
+            let index = LineIndex::new(source);
+            let positions =
+                (0..=5).flat_map(|line| (0..=9).map(move |column| LineColumn::new(line, column)));
+            for start in positions.clone() {
+                for end in positions.clone() {
+                    let span = Span::new(start, end);
+                    assert_eq!(
+                        index.byte_range(source, &span),
+                        scan_byte_range(source, &span),
+                        "{:?} in {:?}",
+                        span,
+                        source
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_edits_in_any_order_and_reject_overlaps() {
+        let source = "let a = 1;\nlet b = 2;\n";
+        let one = Edit::new(
+            Span::new(LineColumn::new(1, 9), LineColumn::new(1, 9)),
+            "10",
+        );
+        let two = Edit::new(
+            Span::new(LineColumn::new(2, 9), LineColumn::new(2, 9)),
+            "20",
+        );
+        assert_eq!(
+            apply_edits(source, &[two.clone(), one.clone()]).unwrap(),
+            "let a = 10;\nlet b = 20;\n"
+        );
+        assert_eq!(apply_edits(source, &[]).unwrap(), source);
+
+        let whole = Edit::new(Span::new(LineColumn::new(1, 1), LineColumn::new(2, 10)), "");
+        assert_eq!(
+            find_overlap(&[one.span, two.span, whole.span]),
+            Some((0, 2))
+        );
+        assert_eq!(
+            apply_edits(source, &[one, whole]).unwrap_err().to_string(),
+            "edits at 1:9-1:9 and 1:1-2:10 overlap"
+        );
+        let outside = Edit::new(Span::new(LineColumn::new(5, 1), LineColumn::new(5, 2)), "");
+        assert_eq!(
+            apply_edits(source, &[outside]).unwrap_err().to_string(),
+            "edit at 5:1-5:2 is outside the text"
+        );
+    }
+
     #[test]
     fn test_replace_region() {
         let source = "
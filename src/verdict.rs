@@ -0,0 +1,223 @@
+// Copyright 2022 Martin Pool
+
+//! A command, configured by the project, that reviews the outcome of each mutant and can
+//! reclassify it, so that teams can apply their own policies without changing how cargo-mutants
+//! scores mutants.
+//!
+//! The command is run in the source tree after each mutant is tested, and is given a JSON
+//! [VerdictRequest] on stdin. Its exit code decides what happens to the outcome:
+//!
+//! * [KEEP] (0): the outcome stands.
+//! * [IGNORE] (10): the mutant is counted as ignored, as if it were in `mutants-ignore.toml`.
+//! * [ESCALATE] (11): the mutant is counted as missed, so that the run fails.
+//!
+//! Any other exit code means the command itself failed, and stops the run.
+//!
+//! Anything the command prints is appended to the mutant's log.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use path_slash::PathExt;
+use serde::Serialize;
+
+use crate::lab::{Outcome, Status, LOG_MARKER};
+use crate::mutate::Mutation;
+
+/// The exit code from the verdict command to keep the outcome unchanged.
+pub const KEEP: i32 = 0;
+
+/// The exit code from the verdict command to ignore the mutant.
+pub const IGNORE: i32 = 10;
+
+/// The exit code from the verdict command to count the mutant as missed.
+pub const ESCALATE: i32 = 11;
+
+/// A command that reviews the outcome of each mutant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerdictCommand {
+    /// The program and its arguments.
+    argv: Vec<String>,
+
+    /// The directory to run it in: the root of the source tree.
+    dir: PathBuf,
+}
+
+/// The outcome of one mutant, sent to the verdict command.
+#[derive(Debug, Serialize)]
+pub struct VerdictRequest<'a> {
+    pub mutant: &'a Mutation,
+
+    pub status: Status,
+
+    /// The absolute path of the mutant's log file.
+    pub log_path: String,
+
+    /// How long the mutant took to test, in seconds.
+    pub duration: f64,
+}
+
+impl VerdictCommand {
+    /// Make a verdict command from a program and its arguments, run in `dir`.
+    ///
+    /// If the program is a relative path with more than one component, it's relative to `dir`;
+    /// otherwise it's found on the `PATH`.
+    pub fn new(argv: &[String], dir: &Path) -> Result<VerdictCommand> {
+        let mut argv = argv.to_vec();
+        let program = argv
+            .first_mut()
+            .ok_or_else(|| anyhow!("verdict command is empty"))?;
+        let program_path = Path::new(program.as_str());
+        if program_path.is_relative() && program_path.components().count() > 1 {
+            *program = dir.join(program_path).to_slash_lossy();
+        }
+        Ok(VerdictCommand {
+            argv,
+            dir: dir.to_owned(),
+        })
+    }
+
+    /// Run the command on the outcome of a mutant, and return its status, which may have been
+    /// changed by the command.
+    pub fn review(&self, mutation: &Mutation, outcome: &Outcome) -> Result<Status> {
+        let name = self.argv.join(" ");
+        let request = VerdictRequest {
+            mutant: mutation,
+            status: outcome.status,
+            log_path: outcome.log_file.path.to_slash_lossy(),
+            duration: outcome.duration.as_secs_f64(),
+        };
+        let mut child = Command::new(&self.argv[0])
+            .args(&self.argv[1..])
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("run verdict command {}", name))?;
+        match child
+            .stdin
+            .take()
+            .expect("child has stdin")
+            .write_all(&serde_json::to_vec(&request)?)
+        {
+            // A command that decides without reading the request may exit without reading it.
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => (),
+            r => r.with_context(|| format!("write request to verdict command {}", name))?,
+        }
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("wait for verdict command {}", name))?;
+        let status = match output.status.code() {
+            Some(KEEP) => outcome.status,
+            Some(IGNORE) => Status::Ignored,
+            Some(ESCALATE) => Status::MutantMissed,
+            _ => {
+                return Err(anyhow!(
+                    "verdict command {} failed on {} ({}): {}",
+                    name,
+                    mutation,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                ))
+            }
+        };
+        let mut log = outcome.log_file.open_append()?;
+        writeln!(log, "\n{} run verdict command {}", LOG_MARKER, name)?;
+        log.write_all(&output.stdout)?;
+        log.write_all(&output.stderr)?;
+        writeln!(
+            log,
+            "{} verdict command exited with {}: {:?}",
+            LOG_MARKER, output.status, status
+        )?;
+        Ok(status)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Instant;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::operators::Operators;
+    use crate::output::OutputDir;
+    use crate::source::SourceFile;
+
+    #[test]
+    fn verdict_command_exit_code_reclassifies_outcome() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script = tmp.path().join("verdict.sh");
+        // Ignore mutants of main, escalate timeouts, and keep everything else.
+        fs::write(
+            &script,
+            "#!/bin/sh\nread request\necho \"$request\" >request.json\n\
+             case \"$request\" in\n\
+             *'\"function\":\"main\"'*) exit 10;;\n\
+             *'\"status\":\"Timeout\"'*) exit 11;;\n\
+             *'\"status\":\"BuildFailed\"'*) echo 'policy error' >&2; exit 1;;\n\
+             esac\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let command = VerdictCommand::new(&["./verdict.sh".to_owned()], tmp.path()).unwrap();
+
+        let mutations = SourceFile::new(
+            Path::new("testdata/tree/factorial"),
+            Path::new("src/bin/main.rs"),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        let output_dir = OutputDir::new(tmp.path()).unwrap();
+        let outcome = |mutation: &Mutation, status| {
+            let (_file, log_file) = output_dir.create_log(&mutation.to_string()).unwrap();
+            Outcome::new(&log_file, &Instant::now(), status)
+        };
+
+        let (main, factorial) = (&mutations[0], &mutations[3]);
+        assert_eq!(
+            command
+                .review(main, &outcome(main, Status::MutantMissed))
+                .unwrap(),
+            Status::Ignored
+        );
+        let request: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(tmp.path().join("request.json")).unwrap())
+                .unwrap();
+        assert_eq!(request["mutant"]["id"], main.id());
+        assert_eq!(request["status"], "MutantMissed");
+
+        let caught = outcome(factorial, Status::MutantCaught);
+        assert_eq!(
+            command.review(factorial, &caught).unwrap(),
+            Status::MutantCaught
+        );
+        assert!(caught
+            .log_file
+            .log_content()
+            .unwrap()
+            .contains("*** verdict command exited with exit status: 0: MutantCaught"));
+        assert_eq!(
+            command
+                .review(factorial, &outcome(factorial, Status::Timeout))
+                .unwrap(),
+            Status::MutantMissed
+        );
+        let err = command
+            .review(factorial, &outcome(factorial, Status::BuildFailed))
+            .unwrap_err();
+        assert!(err.to_string().ends_with("policy error"), "{}", err);
+    }
+
+    #[test]
+    fn empty_verdict_command_is_an_error() {
+        assert!(VerdictCommand::new(&[], Path::new(".")).is_err());
+    }
+}
@@ -4,54 +4,878 @@
 //!
 //! Knowledge of the syn API is localized here.
 
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::Result;
 use quote::ToTokens;
+use serde::Serialize;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::Attribute;
 use syn::ItemFn;
 
-use crate::mutate::{Mutation, MutationOp};
+use crate::mutate::{Genre, Mutation};
+use crate::operators::{Closure, Function, Operators, PluginRequest, ResultAlias};
 use crate::source::SourceFile;
+use crate::textedit::{LineColumn, Span};
+
+/// A `mod foo;` declaration whose contents are in a separate file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModReference {
+    /// The name of the module, without any `r#` prefix, as used to find its file.
+    pub name: String,
+
+    /// The value of a `#[path = "..."]` attribute on the declaration, or of one of the
+    /// `#[cfg_attr(..., path = "...")]` attributes, if any.
+    pub path_attr: Option<String>,
+
+    /// Directory components contributed by any inline `mod { ... }` blocks enclosing this
+    /// declaration: either their names, or their own `#[path]` attributes.
+    pub inline_dirs: Vec<String>,
+
+    /// The path of the declared module from the crate root, such as `["inline", "renamed"]`.
+    pub namespace: Vec<String>,
+}
+
+/// Why a function, or other code that might have been mutated, was not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Test code: `#[test]` functions and `#[cfg(test)]` items.
+    Test,
+
+    /// Marked with `#[mutants::skip]`.
+    SkipAttribute,
+
+    /// Every operator that would apply is turned off by `#[mutants::skip_ops(...)]`.
+    SkipOps,
+
+    /// No enabled operator can mutate a function with this return type.
+    UnsupportedReturnType,
+
+    /// A `const fn` whose return type has no replacement that can be evaluated at compile
+    /// time.
+    ConstFn,
+
+    /// Code inside a macro invocation or definition, which isn't parsed.
+    MacroGenerated,
+
+    /// An `unsafe fn`, which is only mutated with `--mutate-unsafe`.
+    Unsafe,
+
+    /// A function containing an `unsafe` block, skipped with `--skip-unsafe-blocks`.
+    UnsafeBlock,
+
+    /// A trivial accessor, whose body only reads or assigns a field, skipped with
+    /// `--skip-trivial`.
+    Trivial,
+
+    /// A function returning `Self`, where `Self` doesn't derive `Default` in the same file, so
+    /// `Default::default()` would probably not build, or just call the function.
+    SelfWithoutDefault,
+
+    /// A function returning one of its type parameters, or those of its `impl` or trait, that
+    /// has no `Default` bound, so `Default::default()` wouldn't build.
+    GenericWithoutDefault,
+
+    /// A function returning the never type, `!`, which has no values to replace its body with.
+    ReturnsNever,
+
+    /// Marked with a `#[cfg(...)]` that's false in the test configuration, because of a cfg
+    /// predicate that's configured to be excluded.
+    ExcludedCfg,
+
+    /// The whole file is excluded, because it's a build script, matches an exclude pattern,
+    /// or can't be parsed.
+    ExcludedFile,
+}
+
+impl SkipReason {
+    /// Describe the reason for people reading `--list` output.
+    pub fn description(&self) -> &'static str {
+        match self {
+            SkipReason::Test => "test code",
+            SkipReason::SkipAttribute => "#[mutants::skip]",
+            SkipReason::SkipOps => "all operators skipped by #[mutants::skip_ops]",
+            SkipReason::UnsupportedReturnType => "no operator for the return type",
+            SkipReason::ConstFn => "const fn",
+            SkipReason::MacroGenerated => "macro",
+            SkipReason::Unsafe => "unsafe fn",
+            SkipReason::UnsafeBlock => "contains unsafe block",
+            SkipReason::Trivial => "trivial accessor",
+            SkipReason::SelfWithoutDefault => "returns Self, which doesn't derive Default",
+            SkipReason::GenericWithoutDefault => "returns a type parameter without a Default bound",
+            SkipReason::ReturnsNever => "returns !",
+            SkipReason::ExcludedCfg => "excluded cfg",
+            SkipReason::ExcludedFile => "excluded file",
+        }
+    }
+}
+
+/// A function, item, or file that was not mutated, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Skipped {
+    /// The path of the file, relative to the tree root, with forward slashes, or for code
+    /// that's included from elsewhere, a description of where it comes from.
+    pub file: String,
+
+    /// The name of the function or item, including enclosing modules and types, or None if
+    /// the whole file is skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The line where the function or item starts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+
+    pub reason: SkipReason,
+
+    /// More about why a file is excluded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// An `include!(...)` macro, which pulls in code from another file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeReference {
+    /// The line where the macro occurs.
+    pub line: usize,
+
+    /// The argument to the macro, as Rust syntax.
+    pub arg: String,
+
+    /// True if the macro is among the items of a module, so the included file also holds
+    /// items, and can be mutated like a module's file. Otherwise it's an expression, or
+    /// inside an `impl`, and can't be parsed on its own.
+    pub in_module: bool,
+
+    /// The path from the crate root of the module containing the macro.
+    pub namespace: Vec<String>,
+}
+
+/// Where the file named by an `include!` macro is found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludePath {
+    /// A path relative to the directory of the file containing the macro, from a string literal.
+    RelativeToFile(String),
+
+    /// A path relative to the package directory, from
+    /// `concat!(env!("CARGO_MANIFEST_DIR"), "/...")`.
+    RelativeToPackage(String),
+}
+
+impl IncludeReference {
+    /// True if the included file is generated by the build script into `OUT_DIR`.
+    pub fn is_out_dir(&self) -> bool {
+        self.arg.contains("OUT_DIR")
+    }
+
+    /// Return where the included file is, if it can be worked out from the source without
+    /// building the crate.
+    pub fn path(&self) -> Option<IncludePath> {
+        // The argument may have a trailing comma.
+        let args = syn::parse::Parser::parse_str(
+            syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+            &self.arg,
+        )
+        .ok()?;
+        if args.len() != 1 {
+            return None;
+        }
+        match args.into_iter().next()? {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(s),
+                ..
+            }) => Some(IncludePath::RelativeToFile(s.value())),
+            syn::Expr::Macro(syn::ExprMacro { mac, .. }) if mac.path.is_ident("concat") => {
+                let args = mac
+                    .parse_body_with(
+                        syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+                    )
+                    .ok()?;
+                let mut args = args.iter();
+                match args.next()? {
+                    syn::Expr::Macro(syn::ExprMacro { mac, .. })
+                        if mac.path.is_ident("env")
+                            && matches!(
+                                mac.parse_body::<syn::LitStr>(),
+                                Ok(var) if var.value() == "CARGO_MANIFEST_DIR"
+                            ) => {}
+                    _ => return None,
+                }
+                let mut path = String::new();
+                for arg in args {
+                    match arg {
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(s),
+                            ..
+                        }) => path.push_str(&s.value()),
+                        _ => return None,
+                    }
+                }
+                Some(IncludePath::RelativeToPackage(
+                    path.trim_start_matches('/').to_owned(),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Directives from `#[mutants::...]` attributes, which apply to functions within the item they're
+/// attached to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Directives {
+    /// Names of operators that shouldn't generate mutants, from `#[mutants::skip_ops(...)]`.
+    skip_ops: Vec<String>,
+
+    /// How long to let the tests run, from `#[mutants::timeout(secs)]`.
+    timeout: Option<Duration>,
+}
+
+impl Directives {
+    /// Return the directives for an item with the given attributes, nested inside an item
+    /// with these directives.
+    fn nested(&self, attrs: &[Attribute]) -> Directives {
+        let mut directives = self.clone();
+        for attr in attrs {
+            if let Some(args) = mutants_attr_args(attr, "skip_ops") {
+                directives
+                    .skip_ops
+                    .extend(args.iter().filter_map(|arg| match arg {
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                            path.get_ident().map(|ident| ident.to_string())
+                        }
+                        syn::NestedMeta::Lit(syn::Lit::Str(s)) => Some(s.value()),
+                        // `true` and `false` parse as literals rather than paths.
+                        syn::NestedMeta::Lit(syn::Lit::Bool(b)) => Some(b.value.to_string()),
+                        _ => None,
+                    }));
+            }
+            // A `cfg_attr` can hold both.
+            if let Some(args) = mutants_attr_args(attr, "timeout") {
+                if let [syn::NestedMeta::Lit(syn::Lit::Int(secs))] = args.as_slice() {
+                    if let Ok(secs) = secs.base10_parse::<u64>() {
+                        directives.timeout = Some(Duration::from_secs(secs));
+                    }
+                }
+            }
+        }
+        directives
+    }
+}
+
+/// The function whose body is being visited, within which binary operators can be mutated.
+#[derive(Debug, Clone)]
+struct FunctionContext {
+    /// The name of the function, including enclosing modules and types.
+    name: String,
+
+    /// The return type, as Rust syntax.
+    return_type: String,
+
+    /// The span of the function body.
+    body_span: Span,
+
+    /// The directives that apply to the function.
+    directives: Directives,
+}
+
+/// Options controlling which functions and items in a file are mutated.
+#[derive(Debug, Default, Clone)]
+pub struct VisitOptions {
+    /// Also mutate `unsafe fn`s.
+    pub mutate_unsafe: bool,
+
+    /// Cfg predicates, such as `fuzzing` or `feature = "bench"`, that are false in the test
+    /// configuration, so that items only compiled when they're true aren't mutated.
+    pub exclude_cfgs: Vec<String>,
+
+    /// Don't mutate trivial accessors, whose body only reads or assigns a field.
+    pub skip_trivial: bool,
+
+    /// Don't mutate functions containing `unsafe` blocks.
+    pub skip_unsafe_blocks: bool,
+}
 
 /// `syn` visitor that recursively traverses the syntax tree, accumulating places that could be mutated.
 pub struct DiscoveryVisitor<'sf> {
     /// All the mutations generated by visiting the file.
     pub mutations: Vec<Mutation>,
 
+    /// Functions and other items that weren't mutated.
+    pub skipped: Vec<Skipped>,
+
+    /// References to other files declared by `mod` statements in this file.
+    pub mod_references: Vec<ModReference>,
+
+    /// `include!` macros in this file.
+    pub include_references: Vec<IncludeReference>,
+
+    /// True if the file contains any `#[test]` functions or `#[cfg(test)]` items.
+    pub has_tests: bool,
+
+    /// True if the file has a `#![no_std]` attribute, possibly under `cfg_attr`.
+    pub no_std: bool,
+
+    /// The first error from running a plugin, or from an unknown operator name in
+    /// `#[mutants::skip_ops(...)]`, after which no more plugins are run.
+    pub error: Option<anyhow::Error>,
+
     /// The file being visited.
     source_file: &'sf SourceFile,
 
+    /// The operators that generate mutations.
+    operators: &'sf Operators,
+
+    /// Which functions and items are mutated.
+    options: &'sf VisitOptions,
+
+    /// The names of the types in this file that `#[derive(Default)]`.
+    derives_default: HashSet<String>,
+
+    /// The aliases for `Result` declared in this file, followed by those configured.
+    result_aliases: Vec<ResultAlias>,
+
+    /// The type of the `impl` block we're inside, which `Self` refers to, or None outside an
+    /// `impl`, or inside a trait.
+    self_type: Option<syn::Type>,
+
+    /// The generic parameters of the `impl` block or trait we're inside, if any.
+    outer_generics: Option<syn::Generics>,
+
     /// The stack of namespaces we're currently inside.
     namespace_stack: Vec<String>,
+
+    /// The directory components of the inline modules we're currently inside.
+    inline_dir_stack: Vec<String>,
+
+    /// The directives from attributes of the items we're currently inside, innermost last.
+    directives_stack: Vec<Directives>,
+
+    /// The function whose body we're inside, if it can be mutated.
+    function: Option<FunctionContext>,
+
+    /// The labels, if any, of the loops we're inside within the current function or closure,
+    /// innermost last, which are the loops a `break` or `continue` can apply to.
+    loop_labels: Vec<Option<String>>,
 }
 
 impl<'sf> DiscoveryVisitor<'sf> {
-    pub fn new(source_file: &'sf SourceFile) -> DiscoveryVisitor<'sf> {
+    pub fn new(
+        source_file: &'sf SourceFile,
+        operators: &'sf Operators,
+        options: &'sf VisitOptions,
+    ) -> DiscoveryVisitor<'sf> {
         DiscoveryVisitor {
             source_file,
+            operators,
+            options,
+            derives_default: HashSet::new(),
+            result_aliases: Vec::new(),
+            self_type: None,
+            outer_generics: None,
             mutations: Vec::new(),
-            namespace_stack: Vec::new(),
+            skipped: Vec::new(),
+            mod_references: Vec::new(),
+            include_references: Vec::new(),
+            has_tests: false,
+            no_std: false,
+            error: None,
+            namespace_stack: source_file.namespace.clone(),
+            inline_dir_stack: Vec::new(),
+            directives_stack: vec![Directives::default()],
+            function: None,
+            loop_labels: Vec::new(),
         }
     }
 
+    /// Collect the mutations of a function body, and return the context in which to mutate
+    /// binary operators within it, or None if it shouldn't be mutated at all.
+    ///
+    /// If the function has a `#[mutants::replace_with(...)]` attribute among `attrs`, its body
+    /// is replaced by the values given there rather than those from the operators.
     fn collect_fn_mutations(
         &mut self,
-        ident: &syn::Ident,
-        return_type: &syn::ReturnType,
-        span: &proc_macro2::Span,
-    ) {
-        self.in_namespace(&ident.to_string(), |v| {
+        sig: &syn::Signature,
+        attrs: &[Attribute],
+        block: &syn::Block,
+    ) -> Option<FunctionContext> {
+        self.in_namespace(&sig.ident.to_string(), |v| {
             let function_name = v.namespace_stack.join("::");
-            let return_type_str = format!("{}", return_type.to_token_stream());
-            for op in ops_for_return_type(return_type) {
+            let return_type_str = format!("{}", sig.output.to_token_stream());
+            // Mutate a function returning `Self` as one returning the type of the `impl`, so that
+            // constructors configured for the type apply.
+            let returns_self = matches!(
+                &sig.output,
+                syn::ReturnType::Type(_, typ) if matches!(
+                    &**typ,
+                    syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("Self")
+                )
+            );
+            let self_sig;
+            let sig = match &v.self_type {
+                Some(self_type) if returns_self => {
+                    self_sig = syn::Signature {
+                        output: syn::parse_quote! { -> #self_type },
+                        ..sig.clone()
+                    };
+                    &self_sig
+                }
+                _ => sig,
+            };
+            let function = Function {
+                sig,
+                no_std: v.source_file.no_std,
+                result_aliases: &v.result_aliases,
+            };
+            let span: Span = block.brace_token.span.into();
+            // Mutating unsafe code is more likely to cause undefined behavior, such as a crash
+            // that looks like the mutant was caught, than a clear test failure.
+            if sig.unsafety.is_some() && !v.options.mutate_unsafe {
+                v.skipped
+                    .push(v.skip(function_name, span.start.line, SkipReason::Unsafe));
+                return None;
+            }
+            if v.options.skip_trivial && block_is_trivial(block) {
+                v.skipped
+                    .push(v.skip(function_name, span.start.line, SkipReason::Trivial));
+                return None;
+            }
+            if v.options.skip_unsafe_blocks && block_has_unsafe(block) {
+                v.skipped
+                    .push(v.skip(function_name, span.start.line, SkipReason::UnsafeBlock));
+                return None;
+            }
+            let directives = v.directives_stack.last().expect("directives").clone();
+            let mut replacements = v
+                .operators
+                .fn_replacements_with(&function, &replace_with_values(attrs));
+            // `Default::default()` for a type that doesn't implement `Default` won't build, and
+            // for one that implements it by hand, often calls this function, so is equivalent,
+            // or, for `default` itself, never returns.
+            let self_derives_default = v.self_type.as_ref().is_some_and(|self_type| {
+                matches!(self_type, syn::Type::Path(syn::TypePath { path, .. })
+                if path.segments.last().is_some_and(|segment| {
+                    v.derives_default.contains(&segment.ident.to_string())
+                }))
+            });
+            if returns_self && !self_derives_default {
+                replacements.retain(|(op, _)| op != "default");
+            }
+            let returns_generic = match &sig.output {
+                syn::ReturnType::Type(_, typ) => {
+                    generic_without_default(typ, [Some(&sig.generics), v.outer_generics.as_ref()])
+                }
+                syn::ReturnType::Default => false,
+            };
+            if returns_generic {
+                replacements.retain(|(op, _)| op != "default");
+            }
+            if v.error.is_none() {
+                match v.plugin_replacements(sig, &function_name, &return_type_str, &span) {
+                    Ok(plugin_replacements) => replacements.extend(plugin_replacements),
+                    Err(err) => v.error = Some(err),
+                }
+            }
+            let reason = if replacements.is_empty() {
+                Some(if function.returns_never() {
+                    SkipReason::ReturnsNever
+                } else if sig.constness.is_some() {
+                    SkipReason::ConstFn
+                } else if returns_self {
+                    SkipReason::SelfWithoutDefault
+                } else if returns_generic {
+                    SkipReason::GenericWithoutDefault
+                } else {
+                    SkipReason::UnsupportedReturnType
+                })
+            } else {
+                replacements.retain(|(op, _)| !directives.skip_ops.contains(op));
+                replacements.is_empty().then_some(SkipReason::SkipOps)
+            };
+            if let Some(reason) = reason {
+                v.skipped
+                    .push(v.skip(function_name.clone(), span.start.line, reason));
+            }
+            for (op, replacement) in replacements {
                 v.mutations.push(Mutation::new(
                     v.source_file.clone(),
                     op,
+                    replacement,
                     function_name.clone(),
                     return_type_str.clone(),
-                    span.into(),
+                    span,
+                    directives.timeout,
                 ))
             }
+            Some(FunctionContext {
+                name: function_name,
+                return_type: return_type_str,
+                body_span: span,
+                directives,
+            })
+        })
+    }
+
+    /// Record a mutation by the operator `name`, of the given genre, that replaces the text in
+    /// `span` within the function being visited, unless the function's directives skip the
+    /// operator, or we're not inside a function that can be mutated.
+    fn push_body_mutation(&mut self, genre: Genre, name: &str, span: Span, replacement: String) {
+        let Some(function) = &self.function else {
+            return;
+        };
+        if function.directives.skip_ops.iter().any(|skip| skip == name) {
+            return;
+        }
+        self.mutations.push(Mutation::new_in_body(
+            self.source_file.clone(),
+            genre,
+            name.to_owned(),
+            replacement,
+            function.name.clone(),
+            function.return_type.clone(),
+            function.body_span,
+            span,
+            function.directives.timeout,
+        ));
+    }
+
+    /// Collect the mutations that replace a binary operator in the body of the current
+    /// function.
+    fn collect_binary_mutations(&mut self, op: &syn::BinOp) {
+        let span = token_span(op.span());
+        for (name, replacement) in self.operators.binary_replacements(op) {
+            self.push_body_mutation(Genre::BinaryOperator, name, span, replacement.to_owned());
+        }
+    }
+
+    /// Collect mutations of the condition of an `if` within the function being visited.
+    fn collect_condition_mutations(&mut self, condition: &syn::Expr) {
+        // `if let` patterns aren't boolean expressions, so can't be negated.
+        if self.function.is_none() || matches!(condition, syn::Expr::Let(_)) {
+            return;
+        }
+        let span = token_span(condition.span());
+        let original = self.source_file.span_text(&span);
+        for (name, replacement) in self.operators.condition_replacements(original) {
+            self.push_body_mutation(Genre::Condition, name, span, replacement);
+        }
+    }
+
+    /// Collect mutations that delete arms of a `match` within the function being visited.
+    fn collect_match_arm_mutations(&mut self, arms: &[syn::Arm]) {
+        for (name, index) in self.operators.match_arm_deletions(arms) {
+            let span = token_span(arms[index].span());
+            self.push_body_mutation(Genre::MatchArm, name, span, String::new());
+        }
+    }
+
+    /// Collect mutations that delete statements from a block within the function being visited.
+    fn collect_statement_mutations(&mut self, stmts: &[syn::Stmt]) {
+        for (name, index) in self.operators.statement_deletions(stmts) {
+            let span = token_span(stmts[index].span());
+            self.push_body_mutation(Genre::Statement, name, span, String::new());
+        }
+    }
+
+    /// Collect mutations that replace a literal within the function being visited.
+    fn collect_literal_mutations(&mut self, lit: &syn::Lit) {
+        let span = token_span(lit.span());
+        for (name, replacement) in self.operators.literal_replacements(lit) {
+            self.push_body_mutation(Genre::Literal, name, span, replacement.to_owned());
+        }
+    }
+
+    /// Collect mutations that delete the operator of a unary expression, such as `-x`, within
+    /// the function being visited.
+    fn collect_unary_mutations(&mut self, expr: &syn::ExprUnary) {
+        let span = token_span(expr.span());
+        let operand = self.source_file.span_text(&token_span(expr.expr.span()));
+        for name in self.operators.unary_deletions(&expr.op) {
+            self.push_body_mutation(Genre::UnaryOperator, name, span, operand.to_owned());
+        }
+    }
+
+    /// Collect mutations that change the limits of a range within the function being visited.
+    fn collect_range_mutations(&mut self, limits: &syn::RangeLimits) {
+        let span = token_span(limits.span());
+        for (name, replacement) in self.operators.range_replacements(limits) {
+            self.push_body_mutation(Genre::Range, name, span, replacement.to_owned());
+        }
+    }
+
+    /// Collect mutations that swap a `break` or `continue`, whose keyword is in `span`, within
+    /// the function being visited.
+    ///
+    /// `label` is the label of the loop it applies to, if any. Nothing is collected unless it's
+    /// inside a loop with that label: a `break` can also leave a labeled block, which can't be
+    /// continued.
+    fn collect_loop_control_mutations(
+        &mut self,
+        expr: &syn::Expr,
+        label: Option<&syn::Lifetime>,
+        span: proc_macro2::Span,
+    ) {
+        let in_loop = match label {
+            None => !self.loop_labels.is_empty(),
+            Some(label) => {
+                let label = label.ident.to_string();
+                self.loop_labels.contains(&Some(label))
+            }
+        };
+        if !in_loop {
+            return;
+        }
+        let span = token_span(span);
+        for (name, replacement) in self.operators.loop_control_replacements(expr) {
+            self.push_body_mutation(Genre::LoopControl, name, span, replacement.to_owned());
+        }
+    }
+
+    /// Collect mutations that change the head of a `while` or `loop`, in `span`, within the
+    /// function being visited.
+    fn collect_loop_mutations(&mut self, expr: &syn::Expr, span: proc_macro2::Span) {
+        let span = token_span(span);
+        for (name, replacement) in self.operators.loop_replacements(expr) {
+            self.push_body_mutation(Genre::Loop, name, span, replacement.to_owned());
+        }
+    }
+
+    /// Collect mutations that replace an `if let` or `while let` with its `else` branch, given
+    /// here, or an empty block, within the function being visited.
+    fn collect_if_let_mutations(&mut self, expr: &syn::Expr, else_branch: Option<&syn::Expr>) {
+        let span = token_span(expr.span());
+        let replacement = match else_branch {
+            Some(else_branch) => self
+                .source_file
+                .span_text(&token_span(else_branch.span()))
+                .to_owned(),
+            // An empty block, rather than `()`, can be a statement without a semicolon.
+            None => "{}".to_owned(),
+        };
+        for name in self.operators.let_skips(expr) {
+            self.push_body_mutation(Genre::IfLet, name, span, replacement.clone());
+        }
+    }
+
+    /// Collect mutations that swap parameters of the same type in the signature of the function
+    /// being visited.
+    ///
+    /// The span runs from the first parameter's name to the second's, and is replaced by the
+    /// same text with the names exchanged, so their types and anything between stay put.
+    fn collect_argument_mutations(&mut self, sig: &syn::Signature) {
+        let idents = sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(pat_ident) => Some(token_span(pat_ident.ident.span())),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect::<Vec<_>>();
+        for (name, (i, j)) in self.operators.argument_swaps(sig) {
+            let (Some(first), Some(second)) = (idents[i], idents[j]) else {
+                continue;
+            };
+            // Only the names are swapped: any `mut` and the types stay where they are.
+            let span = Span::new(first.start, second.end);
+            let first = self.source_file.span_text(&first);
+            let second = self.source_file.span_text(&second);
+            let original = self.source_file.span_text(&span);
+            let between = &original[first.len()..original.len() - second.len()];
+            let replacement = format!("{}{}{}", second, between, first);
+            self.push_body_mutation(Genre::Arguments, name, span, replacement);
+        }
+    }
+
+    /// Collect mutations that replace the body of a closure within the function being visited.
+    ///
+    /// `method` is the method the closure is passed to as an argument, if any.
+    fn collect_closure_mutations(&mut self, expr: &syn::ExprClosure, method: Option<&syn::Ident>) {
+        let closure = Closure { expr, method };
+        let span = token_span(expr.body.span());
+        for (name, replacement) in self.operators.closure_replacements(&closure) {
+            // A closure with a declared return type must have a block as its body.
+            let replacement = if matches!(*expr.body, syn::Expr::Block(_)) {
+                format!("{{ {} }}", replacement)
+            } else {
+                replacement.to_owned()
+            };
+            self.push_body_mutation(Genre::Closure, name, span, replacement);
+        }
+    }
+
+    /// Call a function while visiting the body of a function that can be mutated, or with
+    /// None, outside of any function.
+    fn in_function<F, T>(&mut self, function: Option<FunctionContext>, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let outer = std::mem::replace(&mut self.function, function);
+        let r = self.outside_loops(f);
+        self.function = outer;
+        r
+    }
+
+    /// Call a function while visiting the body of a loop with an optional label.
+    fn in_loop<F, T>(&mut self, label: Option<&syn::Label>, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.loop_labels
+            .push(label.map(|label| label.name.ident.to_string()));
+        let r = f(self);
+        self.loop_labels.pop();
+        r
+    }
+
+    /// Call a function while visiting code, such as a closure, that a `break` or `continue`
+    /// can't leave, even if it's inside a loop.
+    fn outside_loops<F, T>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let outer = std::mem::take(&mut self.loop_labels);
+        let r = f(self);
+        self.loop_labels = outer;
+        r
+    }
+
+    /// Ask each plugin for replacements for a function, returning pairs of the plugin name and
+    /// the replacement.
+    fn plugin_replacements(
+        &self,
+        sig: &syn::Signature,
+        function_name: &str,
+        return_type: &str,
+        span: &Span,
+    ) -> Result<Vec<(String, String)>> {
+        let plugins = self.operators.plugins();
+        if plugins.is_empty() {
+            return Ok(Vec::new());
+        }
+        let file = self.source_file.tree_relative_slashes();
+        let signature = sig.to_token_stream().to_string();
+        let body = self.source_file.span_text(span);
+        let request = PluginRequest {
+            file: &file,
+            function: function_name,
+            signature: &signature,
+            return_type,
+            body,
+            start: span.start,
+            end: span.end,
+        };
+        let mut r = Vec::new();
+        for plugin in plugins {
+            for replacement in plugin.replacements(&request)? {
+                r.push((plugin.name().to_owned(), replacement));
+            }
+        }
+        Ok(r)
+    }
+
+    /// Describe an item in this file that's skipped.
+    fn skip(&self, name: String, line: usize, reason: SkipReason) -> Skipped {
+        Skipped {
+            file: self.source_file.tree_relative_slashes(),
+            name: Some(name),
+            line: Some(line),
+            reason,
+            detail: None,
+        }
+    }
+
+    /// If the attrs exclude an item, record that it's skipped and return true.
+    ///
+    /// `name` is the name of the item, within the current namespace.
+    fn skip_excluded(&mut self, attrs: &[Attribute], name: &str, line: usize) -> bool {
+        let mut detail = None;
+        let reason = if attrs_are_test(attrs) {
+            SkipReason::Test
+        } else if attrs.iter().any(attr_is_mutants_skip) {
+            SkipReason::SkipAttribute
+        } else if let Some(attr) = excluded_cfg(attrs, &self.options.exclude_cfgs) {
+            let end = attr.bracket_token.span.end();
+            let span = Span::new(
+                attr.pound_token.span.start().into(),
+                LineColumn::new(end.line, end.column),
+            );
+            detail = Some(self.source_file.span_text(&span).to_owned());
+            SkipReason::ExcludedCfg
+        } else {
+            return false;
+        };
+        let name = self.in_namespace(name, |v| v.namespace_stack.join("::"));
+        self.skipped.push(Skipped {
+            detail,
+            ..self.skip(name, line, reason)
+        });
+        true
+    }
+
+    /// Record the file pulled in by a macro, if it's an `include!`.
+    fn collect_include(&mut self, mac: &syn::Macro, in_module: bool) {
+        if macro_is_include(mac) {
+            self.include_references.push(IncludeReference {
+                line: mac.bang_token.span.start().line,
+                arg: mac.tokens.to_string(),
+                in_module,
+                namespace: self.namespace_stack.clone(),
+            });
+        }
+    }
+
+    /// Record that the code in a macro in item position is skipped, unless it's an `include!`,
+    /// which is followed to another file.
+    fn skip_macro(&mut self, mac: &syn::Macro, ident: Option<&syn::Ident>) {
+        if macro_is_include(mac) {
+            return;
+        }
+        let mut name = self.namespace_stack.clone();
+        let path = mac.path.to_token_stream().to_string().replace(' ', "");
+        name.push(match ident {
+            Some(ident) => format!("{}! {}", path, ident),
+            None => format!("{}!", path),
         });
+        let line = mac.bang_token.span.start().line;
+        self.skipped
+            .push(self.skip(name.join("::"), line, SkipReason::MacroGenerated));
+    }
+
+    /// Call a function with the directives from `attrs` applying to everything visited inside it.
+    fn with_directives<F, T>(&mut self, attrs: &[Attribute], f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let outer = self.directives_stack.last().expect("directives");
+        let directives = outer.nested(attrs);
+        // A misspelled operator name would otherwise silently skip nothing.
+        if self.error.is_none() {
+            for name in &directives.skip_ops[outer.skip_ops.len()..] {
+                if let Err(err) = self.operators.check_name(name) {
+                    let line = attrs
+                        .iter()
+                        .find(|attr| mutants_attr_args(attr, "skip_ops").is_some())
+                        .map_or(0, |attr| attr.span().start().line);
+                    self.error = Some(err.context(format!(
+                        "#[mutants::skip_ops] at {}:{}",
+                        self.source_file.tree_relative_slashes(),
+                        line
+                    )));
+                    break;
+                }
+            }
+        }
+        self.directives_stack.push(directives);
+        let r = f(self);
+        self.directives_stack.pop();
+        r
     }
 
     /// Call a function with a namespace pushed onto the stack.
@@ -69,141 +893,2306 @@ impl<'sf> DiscoveryVisitor<'sf> {
 }
 
 impl<'ast, 'sf> Visit<'ast> for DiscoveryVisitor<'sf> {
+    fn visit_file(&mut self, i: &'ast syn::File) {
+        self.no_std = i.attrs.iter().any(attr_is_no_std);
+        // Types can be declared after the `impl` blocks that use them.
+        let mut derives = DerivesDefaultVisitor::default();
+        derives.visit_file(i);
+        self.derives_default = derives.names;
+        self.result_aliases = result_aliases(i, self.operators.result_aliases());
+        syn::visit::visit_file(self, i);
+    }
+
+    /// Visit any item. Items nested inside a function body, such as `const`s, aren't part of
+    /// the function, and functions set their own context.
+    fn visit_item(&mut self, i: &'ast syn::Item) {
+        self.in_function(None, |v| syn::visit::visit_item(v, i));
+    }
+
     fn visit_item_fn(&mut self, i: &'ast ItemFn) {
         // TODO: Filter out more inapplicable fns.
-        if attrs_excluded(&i.attrs) {
+        self.has_tests |= attrs_are_test(&i.attrs);
+        let name = i.sig.ident.to_string();
+        if self.skip_excluded(&i.attrs, &name, i.sig.ident.span().start().line) {
             return; // don't look inside it either
         }
-        self.collect_fn_mutations(&i.sig.ident, &i.sig.output, &i.block.brace_token.span);
-        self.in_namespace(&i.sig.ident.to_string(), |v| {
-            syn::visit::visit_item_fn(v, i);
+        self.with_directives(&i.attrs, |v| {
+            let function = v.collect_fn_mutations(&i.sig, &i.attrs, &i.block);
+            v.in_namespace(&i.sig.ident.to_string(), |v| {
+                v.in_function(function, |v| syn::visit::visit_item_fn(v, i));
+            });
         });
     }
 
     /// Visit `impl Foo { ...}` or `impl Debug for Foo { ... }`.
     fn visit_item_impl(&mut self, i: &'ast syn::ItemImpl) {
-        if attrs_excluded(&i.attrs) {
+        let name = impl_name(i);
+        if self.skip_excluded(&i.attrs, &name, i.impl_token.span.start().line) {
             return;
         }
-        // Make an approximately-right namespace.
-        let name = type_name_string(&i.self_ty);
-        self.in_namespace(&name, |v| syn::visit::visit_item_impl(v, i));
+        let outer_self_type = self.self_type.replace((*i.self_ty).clone());
+        let outer_generics = self.outer_generics.replace(i.generics.clone());
+        self.with_directives(&i.attrs, |v| {
+            v.in_namespace(&name, |v| syn::visit::visit_item_impl(v, i))
+        });
+        self.self_type = outer_self_type;
+        self.outer_generics = outer_generics;
     }
 
     /// Visit `fn foo()` within an `impl`.
     fn visit_impl_item_method(&mut self, i: &'ast syn::ImplItemMethod) {
-        if attrs_excluded(&i.attrs) {
+        let name = i.sig.ident.to_string();
+        if self.skip_excluded(&i.attrs, &name, i.sig.ident.span().start().line) {
             return;
         }
-        self.collect_fn_mutations(&i.sig.ident, &i.sig.output, &i.block.brace_token.span);
-        self.in_namespace(&i.sig.ident.to_string(), |v| {
-            syn::visit::visit_impl_item_method(v, i)
+        self.with_directives(&i.attrs, |v| {
+            let function = v.collect_fn_mutations(&i.sig, &i.attrs, &i.block);
+            v.in_namespace(&i.sig.ident.to_string(), |v| {
+                v.in_function(function, |v| syn::visit::visit_impl_item_method(v, i))
+            });
         });
     }
 
-    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
-        if !attrs_excluded(&node.attrs) {
-            self.in_namespace(&node.ident.to_string(), |v| {
-                syn::visit::visit_item_mod(v, node)
-            });
+    /// Visit `trait Foo { ... }`, whose methods may have default bodies.
+    fn visit_item_trait(&mut self, i: &'ast syn::ItemTrait) {
+        let name = i.ident.to_string();
+        if self.skip_excluded(&i.attrs, &name, i.ident.span().start().line) {
+            return;
         }
+        let outer_self_type = self.self_type.take();
+        let outer_generics = self.outer_generics.replace(i.generics.clone());
+        self.with_directives(&i.attrs, |v| {
+            v.in_namespace(&name, |v| syn::visit::visit_item_trait(v, i))
+        });
+        self.self_type = outer_self_type;
+        self.outer_generics = outer_generics;
     }
-}
 
-fn ops_for_return_type(return_type: &syn::ReturnType) -> Vec<MutationOp> {
-    let mut ops: Vec<MutationOp> = Vec::new();
-    match return_type {
-        syn::ReturnType::Default => ops.push(MutationOp::Unit),
-        syn::ReturnType::Type(_rarrow, box_typ) => match &**box_typ {
-            syn::Type::Path(syn::TypePath { path, .. }) => {
-                // dbg!(&path);
-                if path.is_ident("bool") {
-                    ops.push(MutationOp::True);
-                    ops.push(MutationOp::False);
-                } else if path.is_ident("String") {
-                    // TODO: Detect &str etc.
-                    ops.push(MutationOp::EmptyString);
-                    ops.push(MutationOp::Xyzzy);
-                } else if path_is_result(path) {
-                    // TODO: Try this for any path ending in "Result".
-                    // TODO: Recursively generate for types inside the Ok side of the Result.
-                    ops.push(MutationOp::OkDefault);
-                } else {
-                    ops.push(MutationOp::Default)
-                }
-            }
-            _ => ops.push(MutationOp::Default),
-        },
+    /// Visit `fn foo()` within a trait, which is mutated only if it has a default body.
+    fn visit_trait_item_method(&mut self, i: &'ast syn::TraitItemMethod) {
+        let Some(block) = &i.default else {
+            return;
+        };
+        let name = i.sig.ident.to_string();
+        if self.skip_excluded(&i.attrs, &name, i.sig.ident.span().start().line) {
+            return;
+        }
+        self.with_directives(&i.attrs, |v| {
+            let function = v.collect_fn_mutations(&i.sig, &i.attrs, block);
+            v.in_namespace(&i.sig.ident.to_string(), |v| {
+                v.in_function(function, |v| syn::visit::visit_trait_item_method(v, i))
+            });
+        });
     }
-    ops
-}
 
-fn type_name_string(ty: &syn::Type) -> String {
-    match ty {
-        syn::Type::Path(p) => {
-            if let Some(ident) = p.path.get_ident() {
-                format!("{}", ident)
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        self.has_tests |= attrs_are_test(&node.attrs);
+        let name = node.ident.to_string();
+        if self.skip_excluded(&node.attrs, &name, node.ident.span().start().line) {
+            return;
+        }
+        let path_attrs = path_attr_values(&node.attrs);
+        // Raw identifiers like `r#type` are found in files named without the `r#`.
+        let file_name = node.ident.unraw().to_string();
+        if node.content.is_none() {
+            // The cfg predicates of `cfg_attr` aren't evaluated, so the module is followed to
+            // every file it might be loaded from.
+            let path_attrs = if path_attrs.is_empty() {
+                vec![None]
             } else {
-                // TODO: Something better here?
-                "<??>".into()
+                path_attrs.into_iter().map(Some).collect()
+            };
+            for path_attr in path_attrs {
+                self.mod_references.push(ModReference {
+                    name: file_name.clone(),
+                    path_attr,
+                    inline_dirs: self.inline_dir_stack.clone(),
+                    namespace: self
+                        .namespace_stack
+                        .iter()
+                        .cloned()
+                        .chain([name.clone()])
+                        .collect(),
+                });
             }
+            return;
         }
-        _ => "<??>".into(),
+        self.inline_dir_stack
+            .push(path_attrs.into_iter().next().unwrap_or(file_name));
+        self.with_directives(&node.attrs, |v| {
+            v.in_namespace(&name, |v| syn::visit::visit_item_mod(v, node))
+        });
+        self.inline_dir_stack.pop();
     }
-}
 
-fn path_is_result(path: &syn::Path) -> bool {
-    path.segments
-        .last()
-        .map(|segment| segment.ident == "Result")
-        .unwrap_or_default()
-}
+    /// Visit the signature of a function, whose parameters can be swapped if the function can be
+    /// mutated.
+    fn visit_signature(&mut self, i: &'ast syn::Signature) {
+        self.collect_argument_mutations(i);
+        syn::visit::visit_signature(self, i);
+    }
 
-/// True if any of the attrs indicate that we should skip this node and everything inside it.
-fn attrs_excluded(attrs: &[Attribute]) -> bool {
-    attrs
-        .iter()
-        .any(|attr| attr_is_cfg_test(attr) || attr_is_test(attr) || attr_is_mutants_skip(attr))
-}
+    /// Visit an expression like `a + b`.
+    fn visit_expr_binary(&mut self, i: &'ast syn::ExprBinary) {
+        self.collect_binary_mutations(&i.op);
+        syn::visit::visit_expr_binary(self, i);
+    }
 
-/// True if the attribute is `#[cfg(test)]`.
-fn attr_is_cfg_test(attr: &Attribute) -> bool {
-    if !attr.path.is_ident("cfg") {
-        return false;
+    /// Visit an `if` expression, including the `if` of an `else if`.
+    fn visit_expr_if(&mut self, i: &'ast syn::ExprIf) {
+        self.collect_condition_mutations(&i.cond);
+        syn::visit::visit_expr_if(self, i);
     }
-    if let syn::Meta::List(meta_list) = attr.parse_meta().unwrap() {
-        // We should have already checked this above, but to make sure:
-        assert!(meta_list.path.is_ident("cfg"));
-        for nested_meta in meta_list.nested {
-            if let syn::NestedMeta::Meta(syn::Meta::Path(cfg_path)) = nested_meta {
-                if cfg_path.is_ident("test") {
-                    return true;
+
+    /// Visit a block, such as a function body or the body of a loop.
+    fn visit_block(&mut self, i: &'ast syn::Block) {
+        self.collect_statement_mutations(&i.stmts);
+        // Only `loop`s in statement position are changed: one that gives the value of the block
+        // often never finishes, or returns from the function, so there's no code after it to
+        // give the value instead.
+        for (index, stmt) in i.stmts.iter().enumerate() {
+            match stmt {
+                syn::Stmt::Semi(expr @ syn::Expr::Loop(expr_loop), _) => {
+                    self.collect_loop_mutations(expr, expr_loop.loop_token.span)
                 }
+                syn::Stmt::Expr(expr @ syn::Expr::Loop(expr_loop)) if index + 1 < i.stmts.len() => {
+                    self.collect_loop_mutations(expr, expr_loop.loop_token.span)
+                }
+                _ => (),
             }
         }
+        syn::visit::visit_block(self, i);
     }
-    false
-}
 
-/// True if the attribute is `#[test]`.
+    /// Visit a `match` expression.
+    fn visit_expr_match(&mut self, i: &'ast syn::ExprMatch) {
+        self.collect_match_arm_mutations(&i.arms);
+        syn::visit::visit_expr_match(self, i);
+    }
+
+    /// Visit a literal in an expression, such as `true`.
+    fn visit_expr_lit(&mut self, i: &'ast syn::ExprLit) {
+        self.collect_literal_mutations(&i.lit);
+        syn::visit::visit_expr_lit(self, i);
+    }
+
+    /// Visit a literal in a pattern, such as `true =>`, which isn't mutated because changing
+    /// it would usually make the match not exhaustive.
+    fn visit_pat_lit(&mut self, _i: &'ast syn::PatLit) {}
+
+    /// Visit a unary expression, such as `-x`.
+    fn visit_expr_unary(&mut self, i: &'ast syn::ExprUnary) {
+        self.collect_unary_mutations(i);
+        syn::visit::visit_expr_unary(self, i);
+    }
+
+    /// Visit a range expression, such as `a..b`.
+    fn visit_expr_range(&mut self, i: &'ast syn::ExprRange) {
+        // A range with no end, like `a..`, can't be made inclusive.
+        if i.to.is_some() {
+            self.collect_range_mutations(&i.limits);
+        }
+        syn::visit::visit_expr_range(self, i);
+    }
+
+    /// Visit a range pattern, such as `0..=9 =>`. The bounds are parts of the pattern, like
+    /// literal patterns, so aren't mutated themselves.
+    fn visit_pat_range(&mut self, i: &'ast syn::PatRange) {
+        self.collect_range_mutations(&i.limits);
+    }
+
+    /// Visit a method call, such as `v.iter().filter(|x| ...)`, noting which method any closures
+    /// are passed to.
+    fn visit_expr_method_call(&mut self, i: &'ast syn::ExprMethodCall) {
+        self.visit_expr(&i.receiver);
+        for arg in &i.args {
+            match arg {
+                syn::Expr::Closure(closure) => {
+                    self.collect_closure_mutations(closure, Some(&i.method));
+                    self.outside_loops(|v| syn::visit::visit_expr_closure(v, closure));
+                }
+                _ => self.visit_expr(arg),
+            }
+        }
+    }
+
+    /// Visit a closure that isn't passed directly to a method.
+    fn visit_expr_closure(&mut self, i: &'ast syn::ExprClosure) {
+        self.collect_closure_mutations(i, None);
+        self.outside_loops(|v| syn::visit::visit_expr_closure(v, i));
+    }
+
+    /// Visit an `async` block, which, like a closure, can't be left by a `break` or `continue`.
+    fn visit_expr_async(&mut self, i: &'ast syn::ExprAsync) {
+        self.outside_loops(|v| syn::visit::visit_expr_async(v, i));
+    }
+
+    /// Visit a `loop`.
+    fn visit_expr_loop(&mut self, i: &'ast syn::ExprLoop) {
+        self.in_loop(i.label.as_ref(), |v| v.visit_block(&i.body));
+    }
+
+    /// Visit a `while` loop, whose condition is outside the loop as far as `break` and
+    /// `continue` are concerned.
+    fn visit_expr_while(&mut self, i: &'ast syn::ExprWhile) {
+        self.visit_expr(&i.cond);
+        self.in_loop(i.label.as_ref(), |v| v.visit_block(&i.body));
+    }
+
+    /// Visit a `for` loop, whose pattern and iterator are outside the loop as far as `break`
+    /// and `continue` are concerned.
+    fn visit_expr_for_loop(&mut self, i: &'ast syn::ExprForLoop) {
+        self.visit_pat(&i.pat);
+        self.visit_expr(&i.expr);
+        self.in_loop(i.label.as_ref(), |v| v.visit_block(&i.body));
+    }
+
+    /// Visit any expression, looking for a `break` or `continue`, which might be swapped for
+    /// the other.
+    fn visit_expr(&mut self, i: &'ast syn::Expr) {
+        match i {
+            syn::Expr::Break(expr) => {
+                self.collect_loop_control_mutations(i, expr.label.as_ref(), expr.break_token.span)
+            }
+            syn::Expr::Continue(expr) => self.collect_loop_control_mutations(
+                i,
+                expr.label.as_ref(),
+                expr.continue_token.span,
+            ),
+            syn::Expr::If(expr) => self.collect_if_let_mutations(
+                i,
+                expr.else_branch.as_ref().map(|(_else, branch)| &**branch),
+            ),
+            syn::Expr::While(expr) => {
+                self.collect_loop_mutations(i, expr.cond.span());
+                self.collect_if_let_mutations(i, None);
+            }
+            _ => (),
+        }
+        syn::visit::visit_expr(self, i);
+    }
+
+    /// Visit a compound assignment like `a += b`.
+    fn visit_expr_assign_op(&mut self, i: &'ast syn::ExprAssignOp) {
+        self.collect_binary_mutations(&i.op);
+        syn::visit::visit_expr_assign_op(self, i);
+    }
+
+    /// Visit a macro invocation in expression position, which might be an `include!` of a file
+    /// holding an expression.
+    fn visit_expr_macro(&mut self, node: &'ast syn::ExprMacro) {
+        self.collect_include(&node.mac, false);
+        syn::visit::visit_expr_macro(self, node);
+    }
+
+    /// Visit a macro invocation or `macro_rules!` definition in item position, which might
+    /// generate functions that can't be seen without expanding it.
+    ///
+    /// The body of a `macro_rules!` is never visited: its tokens are fragments of code with
+    /// metavariables, which can't be edited as if they were code that's compiled.
+    fn visit_item_macro(&mut self, node: &'ast syn::ItemMacro) {
+        self.collect_include(&node.mac, true);
+        self.skip_macro(&node.mac, node.ident.as_ref());
+        if node.mac.path.is_ident("macro_rules") {
+            return;
+        }
+        syn::visit::visit_item_macro(self, node);
+    }
+
+    /// Visit a macro invocation inside an `impl` block.
+    fn visit_impl_item_macro(&mut self, node: &'ast syn::ImplItemMacro) {
+        self.collect_include(&node.mac, false);
+        self.skip_macro(&node.mac, None);
+        syn::visit::visit_impl_item_macro(self, node);
+    }
+}
+
+/// True if the macro is `include!`, possibly written with its path as `std::include!` or
+/// `core::include!`.
+fn macro_is_include(mac: &syn::Macro) -> bool {
+    let segments = &mac.path.segments;
+    segments.last().is_some_and(|last| last.ident == "include")
+        && match segments.len() {
+            1 => mac.path.leading_colon.is_none(),
+            2 => segments[0].ident == "std" || segments[0].ident == "core",
+            _ => false,
+        }
+}
+
+/// Return the span of a token, such as an operator, or an expression, ending at its last char.
+///
+/// `proc_macro2` spans end just after the token, so the spans of function bodies, made by
+/// converting them directly, include the char after the closing brace.
+fn token_span(span: proc_macro2::Span) -> Span {
+    let end = span.end();
+    Span::new(span.start().into(), LineColumn::new(end.line, end.column))
+}
+
+/// Return the namespace for the items in an `impl` block: the self type for inherent impls,
+/// like `Foo<T>`, or for trait impls, like `<impl Display for Foo<T>>`.
+fn impl_name(i: &syn::ItemImpl) -> String {
+    let self_ty = type_name_string(&i.self_ty);
+    match &i.trait_ {
+        Some((bang, path, _for)) => format!(
+            "<impl {}{} for {}>",
+            if bang.is_some() { "!" } else { "" },
+            path_string(path),
+            self_ty
+        ),
+        None => self_ty,
+    }
+}
+
+/// Render a type compactly, as it would usually be written, like `&mut [Vec<u8>]`.
+fn type_name_string(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path_string(path),
+        syn::Type::Reference(r) => format!(
+            "&{}{}{}",
+            r.lifetime
+                .as_ref()
+                .map_or(String::new(), |lifetime| format!("{} ", lifetime)),
+            if r.mutability.is_some() { "mut " } else { "" },
+            type_name_string(&r.elem)
+        ),
+        syn::Type::Ptr(p) => format!(
+            "*{} {}",
+            if p.mutability.is_some() {
+                "mut"
+            } else {
+                "const"
+            },
+            type_name_string(&p.elem)
+        ),
+        syn::Type::Slice(s) => format!("[{}]", type_name_string(&s.elem)),
+        syn::Type::Array(a) => format!(
+            "[{}; {}]",
+            type_name_string(&a.elem),
+            a.len.to_token_stream()
+        ),
+        syn::Type::Tuple(t) => match t.elems.len() {
+            1 => format!("({},)", type_name_string(&t.elems[0])),
+            _ => format!(
+                "({})",
+                t.elems
+                    .iter()
+                    .map(type_name_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+        syn::Type::Paren(p) => format!("({})", type_name_string(&p.elem)),
+        syn::Type::Group(g) => type_name_string(&g.elem),
+        syn::Type::TraitObject(t) => format!("dyn {}", bounds_string(&t.bounds)),
+        syn::Type::ImplTrait(t) => format!("impl {}", bounds_string(&t.bounds)),
+        syn::Type::Never(_) => "!".to_owned(),
+        // Rarer types, such as function pointers and qualified paths, are written as tokens.
+        _ => ty.to_token_stream().to_string(),
+    }
+}
+
+/// Render a path compactly, including any generic arguments, like `fmt::Display` or
+/// `HashMap<K, Vec<V>>`.
+fn path_string(path: &syn::Path) -> String {
+    let mut s = String::new();
+    if path.leading_colon.is_some() {
+        s.push_str("::");
+    }
+    for (i, segment) in path.segments.iter().enumerate() {
+        if i > 0 {
+            s.push_str("::");
+        }
+        s.push_str(&segment.ident.to_string());
+        match &segment.arguments {
+            syn::PathArguments::None => (),
+            syn::PathArguments::AngleBracketed(args) => {
+                let args = args
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => type_name_string(ty),
+                        syn::GenericArgument::Binding(binding) => {
+                            format!("{} = {}", binding.ident, type_name_string(&binding.ty))
+                        }
+                        other => other.to_token_stream().to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                s.push_str(&format!("<{}>", args.join(", ")));
+            }
+            syn::PathArguments::Parenthesized(args) => {
+                s.push_str(&format!(
+                    "({})",
+                    args.inputs
+                        .iter()
+                        .map(type_name_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                if let syn::ReturnType::Type(_, ty) = &args.output {
+                    s.push_str(&format!(" -> {}", type_name_string(ty)));
+                }
+            }
+        }
+    }
+    s
+}
+
+/// Render the bounds of a trait object or `impl Trait` type, like `Fn(u32) + Send + 'a`.
+fn bounds_string(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+) -> String {
+    bounds
+        .iter()
+        .map(|bound| match bound {
+            syn::TypeParamBound::Trait(t) => path_string(&t.path),
+            syn::TypeParamBound::Lifetime(lifetime) => lifetime.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Return the values of the `#[path = "..."]` attribute, and of any
+/// `#[cfg_attr(..., path = "...")]` attributes, in order.
+fn path_attr_values(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .flat_map(|attr| {
+            if attr.path.is_ident("path") {
+                attr.parse_meta().into_iter().collect()
+            } else {
+                cfg_attr_metas(attr)
+            }
+        })
+        .filter_map(|meta| match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(s),
+                ..
+            }) if path.is_ident("path") => Some(s.value()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// True if the attrs mark test code: `#[test]` or `#[cfg(test)]`.
+fn attrs_are_test(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr_is_cfg_test(attr) || attr_is_test(attr))
+}
+
+/// True if the attribute is `#[cfg(test)]`.
+fn attr_is_cfg_test(attr: &Attribute) -> bool {
+    if !attr.path.is_ident("cfg") {
+        return false;
+    }
+    if let syn::Meta::List(meta_list) = attr.parse_meta().unwrap() {
+        // We should have already checked this above, but to make sure:
+        assert!(meta_list.path.is_ident("cfg"));
+        for nested_meta in meta_list.nested {
+            if let syn::NestedMeta::Meta(syn::Meta::Path(cfg_path)) = nested_meta {
+                if cfg_path.is_ident("test") {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// If the attrs include a `#[cfg(...)]` that can't be true when the `excluded` cfg predicates
+/// are false, return it.
+fn excluded_cfg<'a>(attrs: &'a [Attribute], excluded: &[String]) -> Option<&'a Attribute> {
+    if excluded.is_empty() {
+        return None;
+    }
+    let excluded: Vec<String> = excluded
+        .iter()
+        .map(|cfg| {
+            let cfg = squash_whitespace(cfg);
+            match cfg
+                .strip_prefix("cfg(")
+                .and_then(|cfg| cfg.strip_suffix(')'))
+            {
+                Some(predicate) => predicate.to_owned(),
+                None => cfg,
+            }
+        })
+        .collect();
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .find(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(meta_list)) => meta_list
+                .nested
+                .iter()
+                .any(|predicate| cfg_is_false(predicate, &excluded)),
+            _ => false,
+        })
+}
+
+/// True if a cfg predicate is false when the `excluded` predicates, given without whitespace,
+/// are: because it's one of them, or it's an `all(...)` including one, or an `any(...)` of only
+/// them.
+fn cfg_is_false(predicate: &syn::NestedMeta, excluded: &[String]) -> bool {
+    let text = squash_whitespace(&predicate.to_token_stream().to_string());
+    if excluded.contains(&text) {
+        return true;
+    }
+    match predicate {
+        syn::NestedMeta::Meta(syn::Meta::List(list)) if list.path.is_ident("all") => list
+            .nested
+            .iter()
+            .any(|predicate| cfg_is_false(predicate, excluded)),
+        syn::NestedMeta::Meta(syn::Meta::List(list)) if list.path.is_ident("any") => {
+            !list.nested.is_empty()
+                && list
+                    .nested
+                    .iter()
+                    .all(|predicate| cfg_is_false(predicate, excluded))
+        }
+        _ => false,
+    }
+}
+
+fn squash_whitespace(s: &str) -> String {
+    s.split_whitespace().collect()
+}
+
+/// True if the attribute is `#![no_std]`, or `#![cfg_attr(..., no_std)]`.
+fn attr_is_no_std(attr: &Attribute) -> bool {
+    if attr.path.is_ident("no_std") {
+        return true;
+    }
+    if !attr.path.is_ident("cfg_attr") {
+        return false;
+    }
+    match attr.parse_meta() {
+        Ok(syn::Meta::List(meta_list)) => meta_list.nested.iter().skip(1).any(|nested_meta| {
+            matches!(nested_meta, syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("no_std"))
+        }),
+        _ => false,
+    }
+}
+
+/// True if the attribute is `#[test]`.
 fn attr_is_test(attr: &Attribute) -> bool {
     attr.path.is_ident("test")
 }
 
 /// True if the attribute is `#[mutants::skip]`.
 fn attr_is_mutants_skip(attr: &Attribute) -> bool {
-    attr.path
-        .segments
+    attr_is_mutants(attr, "skip")
+}
+
+/// True if the attribute is `#[mutants::name...]`, or `#[cfg_attr(..., mutants::name...)]`.
+fn attr_is_mutants(attr: &Attribute, name: &str) -> bool {
+    path_is_mutants(&attr.path, name)
+        || cfg_attr_metas(attr)
+            .iter()
+            .any(|meta| path_is_mutants(meta.path(), name))
+}
+
+/// True if the path is `mutants::name`.
+fn path_is_mutants(path: &syn::Path, name: &str) -> bool {
+    path.segments
         .iter()
         .map(|ps| &ps.ident)
-        .eq(["mutants", "skip"].iter())
+        .eq(["mutants", name].iter())
+}
+
+/// If the attribute is `#[cfg_attr(predicate, attrs...)]`, return the attributes it applies.
+///
+/// The predicate isn't checked: crates often apply `mutants` attributes only under some cfg, so
+/// as not to depend on the crate otherwise, but still mean them to be seen by cargo-mutants.
+fn cfg_attr_metas(attr: &Attribute) -> Vec<syn::Meta> {
+    if !attr.path.is_ident("cfg_attr") {
+        return Vec::new();
+    }
+    match attr.parse_meta() {
+        Ok(syn::Meta::List(meta_list)) => meta_list
+            .nested
+            .into_iter()
+            .skip(1)
+            .filter_map(|nested_meta| match nested_meta {
+                syn::NestedMeta::Meta(meta) => Some(meta),
+                syn::NestedMeta::Lit(_) => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Finds the names of the structs and enums in a file that `#[derive(Default)]`.
+#[derive(Default)]
+struct DerivesDefaultVisitor {
+    names: HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for DerivesDefaultVisitor {
+    fn visit_item_struct(&mut self, i: &'ast syn::ItemStruct) {
+        if attrs_derive_default(&i.attrs) {
+            self.names.insert(i.ident.to_string());
+        }
+    }
+
+    fn visit_item_enum(&mut self, i: &'ast syn::ItemEnum) {
+        if attrs_derive_default(&i.attrs) {
+            self.names.insert(i.ident.to_string());
+        }
+    }
+}
+
+/// Finds the type aliases declared anywhere in a file.
+#[derive(Default)]
+struct TypeAliasVisitor<'ast> {
+    items: Vec<&'ast syn::ItemType>,
+}
+
+impl<'ast> Visit<'ast> for TypeAliasVisitor<'ast> {
+    fn visit_item_type(&mut self, i: &'ast syn::ItemType) {
+        self.items.push(i);
+    }
+}
+
+/// Return the aliases for `Result` declared in a file, such as `type Res<T> = Result<T, E>`,
+/// followed by the `configured` aliases, which can themselves be aliased in the file.
+fn result_aliases(file: &syn::File, configured: &[ResultAlias]) -> Vec<ResultAlias> {
+    let mut types = TypeAliasVisitor::default();
+    types.visit_file(file);
+    let mut aliases: Vec<ResultAlias> = Vec::new();
+    // An alias can be declared in terms of another that's declared after it.
+    loop {
+        let known = [aliases.as_slice(), configured].concat();
+        let found = types
+            .items
+            .iter()
+            .filter(|item| !aliases.iter().any(|alias| item.ident == alias.name))
+            .filter_map(|item| ResultAlias::resolve(item, &known))
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            break;
+        }
+        aliases.extend(found);
+    }
+    aliases.extend(configured.iter().cloned());
+    aliases
+}
+
+/// True if the attrs include `#[derive(Default)]`, possibly among other derives.
+fn attrs_derive_default(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .any(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(meta_list)) => meta_list.nested.iter().any(|nested_meta| {
+                matches!(nested_meta, syn::NestedMeta::Meta(syn::Meta::Path(path))
+                    if path.segments.last().is_some_and(|segment| segment.ident == "Default"))
+            }),
+            _ => false,
+        })
+}
+
+/// True if a return type is a bare type parameter, like `T`, declared in any of `generics`,
+/// with no `Default` bound there, either inline or in a `where` clause.
+fn generic_without_default<'g>(
+    typ: &syn::Type,
+    generics: impl IntoIterator<Item = Option<&'g syn::Generics>>,
+) -> bool {
+    let syn::Type::Path(syn::TypePath { qself: None, path }) = typ else {
+        return false;
+    };
+    let Some(ident) = path.get_ident() else {
+        return false;
+    };
+    let is_default = |bound: &syn::TypeParamBound| {
+        matches!(bound, syn::TypeParamBound::Trait(trait_bound)
+            if trait_bound.path.segments.last().is_some_and(|segment| segment.ident == "Default"))
+    };
+    let mut declared = false;
+    for generics in generics.into_iter().flatten() {
+        for param in generics.type_params() {
+            if param.ident == *ident {
+                declared = true;
+                if param.bounds.iter().any(is_default) {
+                    return false;
+                }
+            }
+        }
+        let where_predicates = generics
+            .where_clause
+            .iter()
+            .flat_map(|where_clause| &where_clause.predicates);
+        for predicate in where_predicates {
+            if let syn::WherePredicate::Type(predicate) = predicate {
+                if predicate.bounded_ty == *typ && predicate.bounds.iter().any(is_default) {
+                    return false;
+                }
+            }
+        }
+    }
+    declared
+}
+
+/// True if a function body contains an `unsafe` block, including inside closures, but not
+/// inside nested items, which are mutated separately.
+fn block_has_unsafe(block: &syn::Block) -> bool {
+    struct UnsafeVisitor {
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for UnsafeVisitor {
+        fn visit_expr_unsafe(&mut self, _i: &'ast syn::ExprUnsafe) {
+            self.found = true;
+        }
+
+        fn visit_item(&mut self, _i: &'ast syn::Item) {}
+    }
+
+    let mut visitor = UnsafeVisitor { found: false };
+    visitor.visit_block(block);
+    visitor.found
+}
+
+/// True if a function body is a trivial accessor: a single field, possibly borrowed, like
+/// `self.name` or `&mut self.items`, or a single assignment of a variable or literal to a field,
+/// like `self.name = name;`.
+fn block_is_trivial(block: &syn::Block) -> bool {
+    let expr = match block.stmts.as_slice() {
+        [syn::Stmt::Expr(expr) | syn::Stmt::Semi(expr, _)] => expr,
+        _ => return false,
+    };
+    match expr {
+        syn::Expr::Assign(assign) => {
+            expr_is_field(&assign.left)
+                && matches!(&*assign.right, syn::Expr::Path(_) | syn::Expr::Lit(_))
+        }
+        syn::Expr::Reference(reference) => expr_is_field(&reference.expr),
+        expr => expr_is_field(expr),
+    }
+}
+
+/// True if the expression is a field of a variable, such as `self.name` or `self.0.len`.
+fn expr_is_field(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Field(field) => {
+            matches!(&*field.base, syn::Expr::Path(_)) || expr_is_field(&field.base)
+        }
+        _ => false,
+    }
+}
+
+/// Return the string arguments of any `#[mutants::replace_with("expr", ...)]` attributes.
+fn replace_with_values(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| mutants_attr_args(attr, "replace_with"))
+        .flatten()
+        .filter_map(|arg| match arg {
+            syn::NestedMeta::Lit(syn::Lit::Str(s)) => Some(s.value()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// If the attribute is `#[mutants::name(...)]`, or `#[cfg_attr(..., mutants::name(...))]`,
+/// return its arguments.
+fn mutants_attr_args(attr: &Attribute, name: &str) -> Option<Vec<syn::NestedMeta>> {
+    let meta = if path_is_mutants(&attr.path, name) {
+        attr.parse_meta().ok()?
+    } else {
+        cfg_attr_metas(attr)
+            .into_iter()
+            .find(|meta| path_is_mutants(meta.path(), name))?
+    };
+    match meta {
+        syn::Meta::List(meta_list) => Some(meta_list.nested.into_iter().collect()),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use crate::operators::Operators;
+    use crate::source::{SourceFile, VisitOptions};
+
+    #[test]
+    fn mutants_attrs_skip_ops_and_set_timeouts() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/lib.rs"),
+            r#"
+                #[mutants::skip_ops(unit)]
+                mod quiet {
+                    pub fn log(s: &str) {}
+
+                    #[mutants::timeout(120)]
+                    pub fn slow() -> bool { true }
+                }
+
+                impl Thing {
+                    #[mutants::skip_ops(true, "false")]
+                    fn check(&self) -> bool { true }
+                }
+
+                pub fn plain() {}
+            "#,
+        )
+        .unwrap();
+        let mutations = SourceFile::new(tmp.path(), Path::new("src/lib.rs"))
+            .unwrap()
+            .mutations(&Operators::all())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| (m.describe_change(), m.timeout()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "replace quiet::slow with true".to_owned(),
+                    Some(Duration::from_secs(120))
+                ),
+                (
+                    "replace quiet::slow with false".to_owned(),
+                    Some(Duration::from_secs(120))
+                ),
+                (
+                    "replace true with false in quiet::slow".to_owned(),
+                    Some(Duration::from_secs(120))
+                ),
+                ("replace true with false in Thing::check".to_owned(), None),
+                ("replace plain with ()".to_owned(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_skip_ops_names_are_errors() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            b"pub fn one() -> u32 { 1 }\n\n#[mutants::skip_ops(comparisons)]\npub fn less(a: u32, b: u32) -> bool { a < b }\n",
+        )
+        .unwrap();
+        let err = source_file.mutations(&Operators::all()).unwrap_err();
+        assert_eq!(err.to_string(), "#[mutants::skip_ops] at src/lib.rs:3");
+        assert!(err
+            .root_cause()
+            .to_string()
+            .starts_with("unknown mutation operator \"comparisons\": expected one of "));
+    }
+
+    #[test]
+    fn mutants_replace_with_gives_the_values_to_return() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/lib.rs"),
+            r#"
+                #[mutants::replace_with("Port(1)", "Port(u16::MAX)")]
+                pub fn port() -> Port { Port(8080) }
+
+                impl Thing {
+                    #[mutants::replace_with("Vec::new()")]
+                    #[mutants::skip_ops(unit)]
+                    async fn load(&self) -> Box<Vec<u8>> { todo!() }
+                }
+            "#,
+        )
+        .unwrap();
+        let describe = |operators: &Operators| {
+            SourceFile::new(tmp.path(), Path::new("src/lib.rs"))
+                .unwrap()
+                .mutations(operators)
+                .unwrap()
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            describe(&Operators::all()),
+            [
+                "replace port with Port(1)",
+                "replace port with Port(u16::MAX)",
+                "replace Thing::load with Box::new(Vec::new())",
+            ]
+        );
+        assert_eq!(
+            describe(
+                &Operators::all()
+                    .without(&["replace_with".to_owned()])
+                    .unwrap()
+            ),
+            [
+                "replace port with Default::default()",
+                "replace Thing::load with Box::new(Default::default())",
+            ]
+        );
+    }
+
+    #[test]
+    fn mutants_attrs_are_seen_inside_cfg_attr() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/lib.rs"),
+            r#"
+                #[cfg_attr(test, mutants::skip)]
+                pub fn flaky() -> u32 { 1 }
+
+                #[cfg_attr(feature = "mutants", mutants::skip)]
+                pub fn slow() -> u32 { 1 }
+
+                #[cfg_attr(test, inline, mutants::skip_ops(true), mutants::timeout(30))]
+                pub fn check() -> bool { true }
+
+                #[cfg_attr(test, inline)]
+                pub fn plain() {}
+            "#,
+        )
+        .unwrap();
+        let (mutations, skipped) = SourceFile::new(tmp.path(), Path::new("src/lib.rs"))
+            .unwrap()
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| (m.describe_change(), m.timeout()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "replace check with false".to_owned(),
+                    Some(Duration::from_secs(30))
+                ),
+                (
+                    "replace true with false in check".to_owned(),
+                    Some(Duration::from_secs(30))
+                ),
+                ("replace plain with ()".to_owned(), None),
+            ]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("flaky", super::SkipReason::SkipAttribute),
+                ("slow", super::SkipReason::SkipAttribute),
+            ]
+        );
+    }
+
+    #[test]
+    fn skipped_items_have_reasons() {
+        use super::SkipReason::*;
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(
+            tmp.path().join("src/lib.rs"),
+            r#"
+                pub fn plain() {}
+
+                #[mutants::skip]
+                pub fn fragile() -> u32 { 1 }
+
+                pub const fn limit() -> Vec<u8> { Vec::new() }
+
+                struct Thing;
+
+                #[mutants::skip]
+                impl Thing {
+                    fn go(&self) {}
+                }
+
+                #[mutants::skip_ops(true, false, flip_bool)]
+                pub fn flag() -> bool { true }
+
+                macro_rules! make {
+                    () => { fn made() {} };
+                }
+
+                make!();
+
+                #[cfg(test)]
+                mod test {
+                    #[test]
+                    fn t() {}
+                }
+            "#,
+        )
+        .unwrap();
+        let (mutations, skipped) = SourceFile::new(tmp.path(), Path::new("src/lib.rs"))
+            .unwrap()
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(mutations.len(), 1);
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.line.unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("fragile", 5, SkipAttribute),
+                ("limit", 7, ConstFn),
+                ("Thing", 12, SkipAttribute),
+                ("flag", 17, SkipOps),
+                ("macro_rules! make", 19, MacroGenerated),
+                ("make!", 23, MacroGenerated),
+                ("test", 26, Test),
+            ]
+        );
+        assert!(skipped.iter().all(|s| s.file == "src/lib.rs"));
+        assert_eq!(
+            serde_json::to_value(&skipped[1]).unwrap(),
+            serde_json::json!({"file": "src/lib.rs", "name": "limit", "line": 7, "reason": "const_fn"})
+        );
+
+        let (_mutations, skipped) = SourceFile::new(tmp.path(), Path::new("src/lib.rs"))
+            .unwrap()
+            .discover(
+                &Operators::all().without(&["unit".to_owned()]).unwrap(),
+                &VisitOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(skipped[0].name.as_deref(), Some("plain"));
+        assert_eq!(skipped[0].reason, UnsupportedReturnType);
+    }
+
+    #[test]
+    fn items_under_excluded_cfgs_are_skipped() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+                #[cfg(fuzzing)]
+                pub fn harness() {}
+
+                #[cfg(all(unix, feature = "bench"))]
+                mod bench {
+                    pub fn time() {}
+                }
+
+                #[cfg(any(windows, fuzzing))]
+                impl Handle {
+                    fn close(&self) {}
+                }
+
+                #[cfg(any(windows, unix))]
+                pub fn portable() {}
+
+                #[cfg(not(fuzzing))]
+                pub fn normal() {}
+            "#,
+        )
+        .unwrap();
+        let options = VisitOptions {
+            exclude_cfgs: vec![
+                "fuzzing".to_owned(),
+                "feature=\"bench\"".to_owned(),
+                "cfg(windows)".to_owned(),
+            ],
+            ..Default::default()
+        };
+        let (mutations, skipped) = source_file.discover(&Operators::all(), &options).unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            ["replace portable with ()", "replace normal with ()"]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (
+                    s.name.as_deref().unwrap(),
+                    s.reason,
+                    s.detail.as_deref().unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("harness", super::SkipReason::ExcludedCfg, "#[cfg(fuzzing)]"),
+                (
+                    "bench",
+                    super::SkipReason::ExcludedCfg,
+                    "#[cfg(all(unix, feature = \"bench\"))]"
+                ),
+                (
+                    "Handle",
+                    super::SkipReason::ExcludedCfg,
+                    "#[cfg(any(windows, fuzzing))]"
+                ),
+            ]
+        );
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(mutations.len(), 5);
+    }
+
+    #[test]
+    fn unsafe_fns_are_mutated_only_when_asked() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            b"pub unsafe fn raw(p: *const u8) -> u8 {\n    *p\n}\n\nimpl Buf {\n    pub unsafe fn clear(&mut self) {}\n}\n\npub fn safe() {}\n",
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.function_name())
+                .collect::<Vec<_>>(),
+            ["safe"]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("raw", super::SkipReason::Unsafe),
+                ("Buf::clear", super::SkipReason::Unsafe)
+            ]
+        );
+        let (mutations, skipped) = source_file
+            .discover(
+                &Operators::all(),
+                &VisitOptions {
+                    mutate_unsafe: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(mutations.len(), 5);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn functions_with_unsafe_blocks_are_skipped_when_asked() {
+        use itertools::Itertools;
+
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn read(p: *const u8) -> u8 {
+    unsafe { *p }
+}
+
+pub fn deferred(p: *const u8) -> bool {
+    let f = || unsafe { *p };
+    f() == 0
+}
+
+pub fn outer() -> bool {
+    fn inner(p: *const u8) -> u8 {
+        unsafe { *p }
+    }
+    true
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(mutations.len(), 12);
+        assert!(skipped.is_empty());
+        let (mutations, skipped) = source_file
+            .discover(
+                &Operators::all(),
+                &VisitOptions {
+                    mutate_unsafe: true,
+                    skip_unsafe_blocks: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.function_name())
+                .dedup()
+                .collect::<Vec<_>>(),
+            ["outer"]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("read", super::SkipReason::UnsafeBlock),
+                ("deferred", super::SkipReason::UnsafeBlock),
+                ("outer::inner", super::SkipReason::UnsafeBlock),
+            ]
+        );
+    }
+
+    #[test]
+    fn functions_returning_self_get_default_only_if_it_is_derived() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+impl Config {
+    pub fn new() -> Self {
+        Config { verbose: true }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    verbose: bool,
+}
+
+impl Handle {
+    pub fn open() -> Self {
+        Handle { fd: 0 }
+    }
+
+    pub fn reopen(&self) -> Handle {
+        Handle { fd: self.fd }
+    }
+}
+
+impl Default for Handle {
+    fn default() -> Self {
+        Handle::open()
+    }
+}
+
+pub trait Make {
+    fn make() -> Self {
+        unimplemented!()
+    }
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            [
+                "replace Config::new with Default::default()",
+                "replace true with false in Config::new",
+                "replace Handle::reopen with Default::default()",
+            ]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("Handle::open", super::SkipReason::SelfWithoutDefault),
+                (
+                    "<impl Default for Handle>::default",
+                    super::SkipReason::SelfWithoutDefault
+                ),
+                ("Make::make", super::SkipReason::SelfWithoutDefault),
+            ]
+        );
+
+        // Constructors configured for the type are used for `Self`.
+        let operators = Operators::all()
+            .with_constructors([("Handle".to_owned(), vec!["Handle { fd: 2 }".to_owned()])].into());
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            [
+                "replace Config::new with Default::default()",
+                "replace true with false in Config::new",
+                "replace Handle::open with Handle { fd: 2 }",
+                "replace Handle::reopen with Handle { fd: 2 }",
+                "replace <impl Default for Handle>::default with Handle { fd: 2 }",
+            ]
+        );
+    }
+
+    #[test]
+    fn functions_returning_type_parameters_get_default_only_if_bounded() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn id<T>(t: T) -> T {
+    t
+}
+
+pub fn fresh<T: Default>() -> T {
+    T::default()
+}
+
+pub fn made<T>() -> T
+where
+    T: std::default::Default,
+{
+    T::default()
+}
+
+pub fn wrapped<T>(t: T) -> Vec<T> {
+    vec![t]
+}
+
+pub struct Cell<T>(T);
+
+impl<T> Cell<T> {
+    pub fn take(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Default> Cell<T> {
+    pub fn reset(&mut self) -> T {
+        std::mem::take(&mut self.0)
+    }
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            [
+                "replace fresh with Default::default()",
+                "replace made with Default::default()",
+                "replace wrapped with Default::default()",
+                "replace Cell<T>::reset with Default::default()",
+            ]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("id", super::SkipReason::GenericWithoutDefault),
+                ("Cell<T>::take", super::SkipReason::GenericWithoutDefault),
+            ]
+        );
+    }
+
+    #[test]
+    fn functions_returning_never_are_skipped() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn fatal(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
+#[mutants::replace_with("loop {}")]
+pub fn hang() -> ! {
+    panic!()
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            [
+                "delete eprintln!(\"{}\", message); in fatal",
+                "replace hang with loop {}",
+            ]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [("fatal", super::SkipReason::ReturnsNever)]
+        );
+    }
+
+    #[test]
+    fn macro_rules_bodies_are_not_mutated() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+macro_rules! double {
+    ($x:expr) => {
+        $x * 2 + 1
+    };
+}
+
+pub fn run() {
+    macro_rules! local {
+        () => {
+            pub fn made() -> bool { !true }
+        };
+    }
+    local!();
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.describe_change())
+                .collect::<Vec<_>>(),
+            ["replace run with ()", "delete local!(); in run"]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.line.unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("macro_rules! double", 2, super::SkipReason::MacroGenerated),
+                (
+                    "run::macro_rules! local",
+                    9,
+                    super::SkipReason::MacroGenerated
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn trivial_accessors_are_skipped_when_asked() {
+        use itertools::Itertools;
+
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+impl Config {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limits.max
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limits.max = limit;
+    }
+
+    pub fn double_limit(&self) -> usize {
+        self.limits.max * 2
+    }
+
+    pub fn reset(&mut self) {
+        self.limits.max = self.default_max();
+    }
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(mutations.len(), 10);
+        let (mutations, skipped) = source_file
+            .discover(
+                &Operators::all(),
+                &VisitOptions {
+                    skip_trivial: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.function_name())
+                .dedup()
+                .collect::<Vec<_>>(),
+            ["Config::double_limit", "Config::reset"]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_deref().unwrap(), s.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("Config::name", super::SkipReason::Trivial),
+                ("Config::limit", super::SkipReason::Trivial),
+                ("Config::set_limit", super::SkipReason::Trivial),
+            ]
+        );
+    }
+
+    #[test]
+    fn binary_operators_are_mutated_only_in_function_bodies() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+const LIMIT: usize = 2 + 3;
+
+pub fn add(a: u32, b: u32) -> u32 {
+    let double = |x: u32| x * 2;
+    fn inner(x: u32) -> u32 { x - 1 }
+    inner(double(a + b) + b)
+}
+
+#[mutants::skip_ops(arithmetic)]
+pub fn quiet(a: &mut u32) {
+    *a %= 7;
+}
+
+#[mutants::skip]
+pub fn skipped(a: u32) -> u32 {
+    a / 2
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "swap arguments a and b in add in src/lib.rs:4",
+                "replace add with Default::default() in src/lib.rs:4",
+                "replace * with / in add in src/lib.rs:5",
+                "replace * with + in add in src/lib.rs:5",
+                "replace add::inner with Default::default() in src/lib.rs:6",
+                "replace - with + in add::inner in src/lib.rs:6",
+                "replace - with / in add::inner in src/lib.rs:6",
+                "replace + with - in add in src/lib.rs:7",
+                "replace + with * in add in src/lib.rs:7",
+                "replace + with - in add in src/lib.rs:7",
+                "replace + with * in add in src/lib.rs:7",
+                "replace quiet with () in src/lib.rs:11",
+                "delete *a %= 7; in quiet in src/lib.rs:12",
+            ]
+        );
+        // Mutants of the same operator in different places have different ids.
+        assert_ne!(mutations[7].id(), mutations[9].id());
+        assert_eq!(
+            mutations[7].mutated_code().lines().nth(6).unwrap(),
+            "    inner(double(a - /* ~ changed by cargo-mutants ~ */ b) + b)"
+        );
+    }
+
+    #[test]
+    fn if_conditions_are_negated() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn sign(a: i32, b: Option<i32>) {
+    if a
+        < 0
+    {
+        neg();
+    } else if let Some(b) = b {
+        other(b);
+    } else if a > 0 {
+        pos();
+    }
+}
+
+#[mutants::skip_ops(negate_condition)]
+pub fn check(a: bool) {
+    if a {
+        yes();
+    }
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&[
+                "comparison".to_owned(),
+                "delete_statement".to_owned(),
+                "skip_let_match".to_owned(),
+            ])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace sign with () in src/lib.rs:2",
+                "replace a < 0 with !(a < 0) in sign in src/lib.rs:3",
+                "replace a > 0 with !(a > 0) in sign in src/lib.rs:9",
+                "replace check with () in src/lib.rs:15",
+            ]
+        );
+        assert_eq!(mutations[1].genre(), crate::mutate::Genre::Condition);
+        assert_eq!(
+            mutations[1].mutated_code().lines().nth(2).unwrap(),
+            "    if !(a"
+        );
+        assert_eq!(
+            mutations[2].mutated_code().lines().nth(8).unwrap(),
+            "    } else if !(a > 0) /* ~ changed by cargo-mutants ~ */ {"
+        );
+    }
+
+    #[test]
+    fn match_arms_are_deleted_when_still_exhaustive() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn name(a: Option<u32>) {
+    match a {
+        Some(0) => zero(),
+        #[cfg(feature = "one")]
+        Some(1) => {
+            one();
+        }
+        _ => other(),
+    }
+    match a {
+        Some(_) => some(),
+        None => none(),
+    }
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&["unit".to_owned(), "delete_statement".to_owned()])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "delete match arm Some(0) in name in src/lib.rs:4",
+                "delete match arm #[cfg(feature = \"one\")] Some(1) in name in src/lib.rs:5",
+            ]
+        );
+        assert_eq!(mutations[0].genre(), crate::mutate::Genre::MatchArm);
+        assert_eq!(
+            mutations[0].mutated_code().lines().nth(3).unwrap(),
+            "        /* ~ changed by cargo-mutants ~ */"
+        );
+        let mutated = mutations[1].mutated_code();
+        assert!(
+            mutated.contains("        /* ~ changed by cargo-mutants ~ */\n        _ => other(),"),
+            "{}",
+            mutated
+        );
+        assert!(syn::parse_file(&mutated).is_ok());
+    }
+
+    #[test]
+    fn bool_literals_are_flipped_in_function_bodies() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+const VERBOSE: bool = true;
+
+pub fn options(quiet: bool) -> Options {
+    let fast = false;
+    match quiet {
+        true => Options { fast, quiet: true },
+        false => Options { fast: true, quiet },
+    }
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&["default".to_owned(), "delete_match_arm".to_owned()])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace false with true in options in src/lib.rs:5",
+                "replace true with false in options in src/lib.rs:7",
+                "replace true with false in options in src/lib.rs:8",
+            ]
+        );
+        assert_eq!(mutations[0].genre(), crate::mutate::Genre::Literal);
+        assert_eq!(
+            mutations[2].mutated_code().lines().nth(7).unwrap(),
+            "        false => Options { fast: false /* ~ changed by cargo-mutants ~ */, quiet },"
+        );
+    }
+
+    #[test]
+    fn unary_minus_is_deleted() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+const MIN: i32 = -100;
+
+pub fn refund(amount: i32) -> i32 {
+    let fee = -amount.abs() / 10;
+    -(amount + fee)
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&["default".to_owned(), "arithmetic".to_owned()])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace -amount.abs() with amount.abs() in refund in src/lib.rs:5",
+                "replace -(amount + fee) with (amount + fee) in refund in src/lib.rs:6",
+            ]
+        );
+        assert_eq!(mutations[0].genre(), crate::mutate::Genre::UnaryOperator);
+        assert_eq!(
+            mutations[1].mutated_code().lines().nth(5).unwrap(),
+            "    (amount + fee) /* ~ changed by cargo-mutants ~ */"
+        );
+    }
+
+    #[test]
+    fn unary_not_is_deleted() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn visible(hidden: bool, name: &str) -> bool {
+    !hidden && !name.starts_with('.')
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&["true".to_owned(), "false".to_owned(), "logical".to_owned()])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace !hidden with hidden in visible in src/lib.rs:3",
+                "replace !name.starts_with('.') with name.starts_with('.') in visible in src/lib.rs:3",
+            ]
+        );
+        assert_eq!(
+            mutations[0].mutated_code().lines().nth(2).unwrap(),
+            "    hidden /* ~ changed by cargo-mutants ~ */ && !name.starts_with('.')"
+        );
+    }
+
+    #[test]
+    fn bool_closures_are_replaced() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn tidy(names: &mut Vec<String>, limit: usize) -> Vec<usize> {
+    names.retain(|name| { !name.is_empty() });
+    let short = |name: &String| -> bool { name.len() < limit };
+    let always = |_: &String| true;
+    names.iter().map(|name| name.len()).filter(|&len| len > 1).collect()
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .filter(|m| m.genre() == crate::mutate::Genre::Closure)
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "replace { !name.is_empty() } with { true } in tidy in src/lib.rs:3",
+                "replace { !name.is_empty() } with { false } in tidy in src/lib.rs:3",
+                "replace { name.len() < limit } with { true } in tidy in src/lib.rs:4",
+                "replace { name.len() < limit } with { false } in tidy in src/lib.rs:4",
+                "replace true with false in tidy in src/lib.rs:5",
+                "replace len > 1 with true in tidy in src/lib.rs:6",
+                "replace len > 1 with false in tidy in src/lib.rs:6",
+            ]
+        );
+    }
+
+    #[test]
+    fn range_limits_are_swapped() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn digits(s: &[u8], n: usize) -> usize {
+    let mut count = 0;
+    for i in 0..n {
+        match s[i] {
+            b'0'..=b'9' => count += 1,
+            _ => (),
+        }
+    }
+    count + s[n..].len() + s[..=n].len()
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all()
+            .without(&[
+                "default".to_owned(),
+                "arithmetic".to_owned(),
+                "delete_match_arm".to_owned(),
+                "delete_statement".to_owned(),
+            ])
+            .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace .. with ..= in digits in src/lib.rs:4",
+                "replace ..= with .. in digits in src/lib.rs:6",
+                "replace ..= with .. in digits in src/lib.rs:10",
+            ]
+        );
+        assert_eq!(mutations[0].genre(), crate::mutate::Genre::Range);
+        assert_eq!(
+            mutations[1].mutated_code().lines().nth(5).unwrap(),
+            "            b'0'.. /* ~ changed by cargo-mutants ~ */b'9' => count += 1,"
+        );
+    }
+
+    #[test]
+    fn break_and_continue_are_swapped_in_loops() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn first_odd_square(v: &[u32]) -> Option<u32> {
+    let found = 'outer: loop {
+        for x in v {
+            if x % 2 == 0 {
+                continue;
+            }
+            let squares = v.iter().map(|y| loop { break y * y; });
+            while squares.len() > 0 {
+                break 'outer Some(*x);
+            }
+            'block: {
+                break 'block;
+            }
+            if *x > 100 {
+                continue 'outer;
+            }
+            break 'outer None;
+        }
+        break;
+    };
+    found
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        let loop_control = mutations
+            .iter()
+            .filter(|m| m.genre() == crate::mutate::Genre::LoopControl)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            loop_control
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "replace continue with break in first_odd_square in src/lib.rs:6",
+                "replace continue with break in first_odd_square in src/lib.rs:16",
+                "replace break with continue in first_odd_square in src/lib.rs:20",
+            ]
+        );
+        assert_eq!(
+            loop_control[0].mutated_code().lines().nth(5).unwrap(),
+            "                break /* ~ changed by cargo-mutants ~ */;"
+        );
+    }
+
+    #[test]
+    fn loops_are_skipped() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn drain(v: &mut Vec<u32>) -> u32 {
+    let mut sum = 0;
+    while v.len() > 1 {
+        sum += v.pop().unwrap();
+    }
+    while let Some(x) = v.pop() {
+        sum += x;
+    }
+    loop {
+        sum /= 2;
+        if sum < 10 {
+            break;
+        }
+    }
+    let n = loop {
+        break 3;
+    };
+    loop {
+        return sum + n;
+    }
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        let loops = mutations
+            .iter()
+            .filter(|m| m.genre() == crate::mutate::Genre::Loop)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            loops.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace v.len() > 1 with false in drain in src/lib.rs:4",
+                "replace loop with while false in drain in src/lib.rs:10",
+            ]
+        );
+        assert_eq!(
+            loops[1].mutated_code().lines().nth(9).unwrap(),
+            "    while false /* ~ changed by cargo-mutants ~ */ {"
+        );
+    }
+
+    #[test]
+    fn if_let_and_while_let_take_the_else_path() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn total(v: &mut Vec<Option<u32>>) -> u32 {
+    let mut sum = 0;
+    while let Some(x) = v.pop() {
+        if let Some(x) = x { sum += x } else if let [] = v[..] { sum = 1 }
+    }
+    if sum > 0 {
+        sum -= 1;
+    }
+    sum
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        let if_lets = mutations
+            .iter()
+            .filter(|m| m.genre() == crate::mutate::Genre::IfLet)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            if_lets.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "replace while let Some(x) = v.pop() { if let Some(x) = x { sum += x } else if let [] = v[..] { sum = 1 } } with {} in total in src/lib.rs:4",
+                "replace if let Some(x) = x { sum += x } else if let [] = v[..] { sum = 1 } with if let [] = v[..] { sum = 1 } in total in src/lib.rs:5",
+                "replace if let [] = v[..] { sum = 1 } with {} in total in src/lib.rs:5",
+            ]
+        );
+        assert_eq!(
+            if_lets[1].mutated_code().lines().nth(4).unwrap(),
+            "        if let [] = v[..] { sum = 1 } /* ~ changed by cargo-mutants ~ */"
+        );
+    }
+
+    #[test]
+    fn result_aliases_are_mutated_like_result() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+type Res<T> = Result<T, String>;
+type Fallible<T> = anyhow::Result<T>;
+type Flag<E> = Result<bool, E>;
+
+pub fn parse() -> Res<bool> {
+    Ok(true)
+}
+
+pub fn load() -> Fallible<()> {
+    Ok(())
+}
+
+pub fn check() -> Flag<String> {
+    Ok(true)
+}
+
+pub fn run() -> Outcome {
+    Ok(())
+}
+
+pub fn external() -> Checked<()> {
+    Ok(())
+}
+
+mod error {
+    pub type Outcome = super::Res<()>;
+}
+"#,
+        )
+        .unwrap();
+        let operators = Operators::all().with_result_aliases(&["Checked".to_owned()]);
+        let (mutations, _skipped) = source_file
+            .discover(&operators, &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .filter(|m| m.genre() == crate::mutate::Genre::FnValue)
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "replace parse with Err(Default::default()) in src/lib.rs:6",
+                "replace parse with Ok(true) in src/lib.rs:6",
+                "replace parse with Ok(false) in src/lib.rs:6",
+                "replace load with Ok(Default::default()) in src/lib.rs:10",
+                "replace load with Err(::anyhow::anyhow!(\"mutated!\")) in src/lib.rs:10",
+                "replace check with Default::default() in src/lib.rs:14",
+                "replace run with Ok(Default::default()) in src/lib.rs:18",
+                "replace run with Err(Default::default()) in src/lib.rs:18",
+                "replace external with Ok(Default::default()) in src/lib.rs:22",
+            ]
+        );
+    }
+
+    #[test]
+    fn parameters_of_the_same_type_are_swapped() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub fn clamp(mut x: u32, lo: u32, name: &str, hi: u32) {
+    println!("{name}: {}", x.max(lo).min(hi));
+    x += 1;
+}
+
+impl Span {
+    pub fn new(&self, (a, b): (u8, u8), start: &'static str, end: &'static str) {}
+}
+
+pub fn longest<'a, 'b>(a: &'a str, b: &'b str) {}
+
+extern "C" {
+    fn external(a: u32, b: u32);
+}
+
+pub fn width(
+    lo: f64,
+    hi: f64,
+) -> f64 {
+    hi - lo
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, _skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        let swaps = mutations
+            .iter()
+            .filter(|m| m.genre() == crate::mutate::Genre::Arguments)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            swaps.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            [
+                "swap arguments x and lo in clamp in src/lib.rs:2",
+                "swap arguments x and hi in clamp in src/lib.rs:2",
+                "swap arguments lo and hi in clamp in src/lib.rs:2",
+                "swap arguments start and end in Span::new in src/lib.rs:8",
+                "swap arguments lo and hi in width in src/lib.rs:18",
+            ]
+        );
+        assert_eq!(
+            swaps[0].mutated_code().lines().nth(1).unwrap(),
+            "pub fn clamp(mut lo: u32, x /* ~ changed by cargo-mutants ~ */: u32, name: &str, hi: u32) {"
+        );
+        assert!(swaps.iter().all(|m| m.op == "swap_arguments"));
+    }
+
+    #[test]
+    fn default_trait_methods_are_mutated() {
+        let source_file = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            br#"
+pub trait Shape {
+    fn sides(&self) -> u32;
+
+    fn corners(&self) -> u32 {
+        self.sides()
+    }
+
+    #[mutants::skip]
+    fn name(&self) -> String {
+        format!("{}-gon", self.sides())
+    }
+}
+"#,
+        )
+        .unwrap();
+        let (mutations, skipped) = source_file
+            .discover(&Operators::all(), &VisitOptions::default())
+            .unwrap();
+        assert_eq!(
+            mutations.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            ["replace Shape::corners with Default::default() in src/lib.rs:5"]
+        );
+        assert_eq!(
+            mutations[0].mutated_code().lines().nth(5).unwrap(),
+            "Default::default() /* ~ changed by cargo-mutants ~ */"
+        );
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].name.as_deref(), Some("Shape::name"));
+    }
+
+    #[test]
+    fn impl_names_show_generic_types_and_traits() {
+        let names = |code: &str| {
+            SourceFile::from_bytes(Path::new("src/lib.rs"), code.as_bytes())
+                .unwrap()
+                .mutations(&Operators::all())
+                .unwrap()
+                .iter()
+                .map(|m| m.function_name().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(
+                r#"
+                impl Foo { fn a(&self) {} }
+                impl<T> Foo<T> { fn b(&self) {} }
+                impl<T: Clone> fmt::Display for Foo<Vec<T>> {
+                    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+                }
+                impl<'a> From<&'a [u8]> for Bytes<'a> { fn from(b: &'a [u8]) -> Bytes<'a> { todo!() } }
+                impl Iterator for Box<dyn Fn(u32) -> u32 + Send> { fn next(&mut self) {} }
+                impl Trait for (u8, [u16; 4], &mut str) { fn t() {} }
+            "#
+            ),
+            [
+                "Foo::a",
+                "Foo<T>::b",
+                "<impl fmt::Display for Foo<Vec<T>>>::fmt",
+                "<impl From<&'a [u8]> for Bytes<'a>>::from",
+                "<impl Iterator for Box<dyn Fn(u32) -> u32 + Send>>::next",
+                "<impl Trait for (u8, [u16; 4], &mut str)>::t",
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_and_non_ascii_identifiers_are_named_as_written() {
+        let mutations = SourceFile::from_bytes(
+            Path::new("src/lib.rs"),
+            "pub mod r#type {\n    pub struct r#Struct;\n    impl r#Struct {\n        pub fn r#match(&self) -> u32 { 1 }\n    }\n}\n\npub fn größe(é: &str) -> bool { é.is_empty() }\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .mutations(&Operators::all())
+        .unwrap();
+        assert_eq!(
+            mutations
+                .iter()
+                .map(|m| m.function_name())
+                .collect::<Vec<_>>(),
+            ["r#type::r#Struct::r#match", "größe", "größe"]
+        );
+        assert_eq!(
+            mutations[1].mutated_code(),
+            "pub mod r#type {\n    pub struct r#Struct;\n    impl r#Struct {\n        pub fn r#match(&self) -> u32 { 1 }\n    }\n}\n\npub fn größe(é: &str) -> bool {\ntrue /* ~ changed by cargo-mutants ~ */\n}\n"
+        );
+        let json = serde_json::to_value(&mutations[1]).unwrap();
+        assert_eq!(json["function"], "größe");
+        assert_eq!(json["span"]["start"]["column"], 31);
+    }
+
+    #[test]
+    fn path_attr_values() {
+        let item: syn::ItemMod = syn::parse_quote! {
+            #[path = "../shared/util.rs"]
+            mod util;
+        };
+        assert_eq!(super::path_attr_values(&item.attrs), ["../shared/util.rs"]);
+        let item: syn::ItemMod = syn::parse_quote! { mod util; };
+        assert!(super::path_attr_values(&item.attrs).is_empty());
+        let item: syn::ItemMod = syn::parse_quote! {
+            #[cfg_attr(unix, path = "unix.rs")]
+            #[cfg_attr(windows, allow(dead_code), path = "windows.rs")]
+            #[cfg_attr(test, allow(unused))]
+            mod sys;
+        };
+        assert_eq!(
+            super::path_attr_values(&item.attrs),
+            ["unix.rs", "windows.rs"]
+        );
+    }
+
+    #[test]
+    fn include_path() {
+        use super::{IncludePath, IncludeReference};
+        let path = |arg: &str| {
+            IncludeReference {
+                line: 1,
+                arg: arg.to_owned(),
+                in_module: true,
+                namespace: Vec::new(),
+            }
+            .path()
+        };
+        assert_eq!(
+            path(r#""tables.rs""#),
+            Some(IncludePath::RelativeToFile("tables.rs".to_owned()))
+        );
+        assert_eq!(
+            path(r#"concat!(env!("CARGO_MANIFEST_DIR"), "/src/", "tables.rs")"#),
+            Some(IncludePath::RelativeToPackage("src/tables.rs".to_owned()))
+        );
+        assert_eq!(path(r#"concat!(env!("OUT_DIR"), "/tables.rs")"#), None);
+        assert_eq!(path(r#"concat!(env!("CARGO_MANIFEST_DIR"), NAME)"#), None);
+        assert_eq!(
+            path(r#""tables.rs","#),
+            Some(IncludePath::RelativeToFile("tables.rs".to_owned()))
+        );
+        assert_eq!(path(r#""a.rs", "b.rs""#), None);
+    }
+
     #[test]
-    fn path_is_result() {
-        let path: syn::Path = syn::parse_quote! { Result<(), ()> };
-        assert!(super::path_is_result(&path));
+    fn no_std_attrs() {
+        let file: syn::File = syn::parse_quote! { #![no_std] };
+        assert!(file.attrs.iter().any(super::attr_is_no_std));
+        let file: syn::File = syn::parse_quote! { #![cfg_attr(not(feature = "std"), no_std)] };
+        assert!(file.attrs.iter().any(super::attr_is_no_std));
+        let file: syn::File = syn::parse_quote! { #![cfg_attr(feature = "no_std", allow(unused))] };
+        assert!(!file.attrs.iter().any(super::attr_is_no_std));
     }
 }
@@ -0,0 +1,80 @@
+//! Async functions, and functions returning `impl Future`, whose mutants should all build and
+//! be caught.
+//!
+//! The tests run the futures with a minimal executor, so that the tree has no dependencies.
+
+// Returning `impl Future` explicitly is the point of some of these examples.
+#![allow(clippy::manual_async_fn)]
+
+use std::future::Future;
+
+pub async fn double(a: u32) -> u32 {
+    a * 2
+}
+
+pub async fn is_even(a: u32) -> bool {
+    a % 2 == 0
+}
+
+pub fn greet(name: &str) -> impl Future<Output = String> + '_ {
+    async move { format!("Hello, {}!", name) }
+}
+
+pub fn halve(a: u32) -> impl Future<Output = Option<u32>> {
+    async move {
+        if a % 2 == 0 {
+            Some(a / 2)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use super::*;
+
+    struct NoopWake;
+
+    impl Wake for NoopWake {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWake));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn double_doubles() {
+        assert_eq!(block_on(double(3)), 6);
+    }
+
+    #[test]
+    fn is_even_checks_the_remainder() {
+        assert!(block_on(is_even(4)));
+        assert!(!block_on(is_even(3)));
+    }
+
+    #[test]
+    fn greet_includes_the_name() {
+        assert_eq!(block_on(greet("Ferris")), "Hello, Ferris!");
+    }
+
+    #[test]
+    fn halve_only_even_numbers() {
+        assert_eq!(block_on(halve(6)), Some(3));
+        assert_eq!(block_on(halve(2)), Some(1));
+        assert_eq!(block_on(halve(5)), None);
+    }
+}
@@ -0,0 +1,14 @@
+//! Generate some code into `OUT_DIR`, which should not be mutated.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(
+        Path::new(&out_dir).join("generated.rs"),
+        "pub fn generated_answer() -> u32 { 42 }\n",
+    )
+    .unwrap();
+}
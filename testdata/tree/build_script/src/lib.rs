@@ -0,0 +1,12 @@
+//! A tree with a build script that generates code, neither of which should be mutated.
+
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+pub fn double_answer() -> u32 {
+    generated_answer() * 2
+}
+
+#[test]
+fn test_double_answer() {
+    assert_eq!(double_answer(), 84);
+}
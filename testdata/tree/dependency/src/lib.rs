@@ -0,0 +1,7 @@
+// Copyright 2022 Martin Pool
+
+//! A dependency of `relative_dependency`, found through a relative path.
+
+pub fn double(a: u32) -> u32 {
+    a * 2
+}
@@ -0,0 +1,17 @@
+// Copyright 2022 Martin Pool
+
+//! Code that's only built, and tested, with a non-default feature.
+
+#[cfg(feature = "double")]
+pub fn double(a: u32) -> u32 {
+    a * 2
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "double")]
+    #[test]
+    fn double() {
+        assert_eq!(super::double(21), 42);
+    }
+}
@@ -0,0 +1,25 @@
+//! An example of a function that hangs when mutated, so that cargo-mutants has to stop it
+//! after a timeout.
+
+/// If mutated to return false, the program will spin forever.
+fn should_stop() -> bool {
+    true
+}
+
+pub fn controlled_loop() -> usize {
+    let mut i = 0;
+    loop {
+        i += 1;
+        if should_stop() {
+            return i;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn controlled_loop_terminates() {
+        assert_eq!(super::controlled_loop(), 1);
+    }
+}
@@ -0,0 +1,11 @@
+//! A crate with a library, and a binary that's a thin wrapper around it with no tests of its
+//! own.
+
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[test]
+fn double_doubles() {
+    assert_eq!(double(3), 6);
+}
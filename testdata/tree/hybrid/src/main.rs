@@ -0,0 +1,3 @@
+fn main() {
+    println!("{}", cargo_mutants_testdata_hybrid::double(21));
+}
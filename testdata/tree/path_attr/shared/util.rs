@@ -0,0 +1,8 @@
+pub fn double(a: u32) -> u32 {
+    a * 2
+}
+
+#[test]
+fn test_double() {
+    assert_eq!(double(21), 42);
+}
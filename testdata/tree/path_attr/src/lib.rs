@@ -0,0 +1,24 @@
+//! An example tree with modules whose source files are found through `#[path]` attributes.
+
+#[path = "../shared/util.rs"]
+mod util;
+
+mod inline {
+    #[path = "renamed_file.rs"]
+    pub mod renamed;
+}
+
+mod plain;
+
+#[cfg_attr(unix, path = "sys/unix.rs")]
+#[cfg_attr(not(unix), path = "sys/other.rs")]
+pub mod sys;
+
+pub fn sum_of_doubles(a: u32, b: u32) -> u32 {
+    util::double(a) + inline::renamed::double(b)
+}
+
+#[test]
+fn test_sum_of_doubles() {
+    assert_eq!(sum_of_doubles(2, 3), 10);
+}
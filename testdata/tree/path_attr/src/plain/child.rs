@@ -0,0 +1,9 @@
+pub fn is_even(a: u32) -> bool {
+    a % 2 == 0
+}
+
+#[test]
+fn test_is_even() {
+    assert!(is_even(2));
+    assert!(!is_even(3));
+}
@@ -0,0 +1,3 @@
+pub fn path_separator() -> char {
+    '/'
+}
@@ -0,0 +1,15 @@
+// Copyright 2022 Martin Pool
+
+//! A proc macro with no tests of its own: it's only tested through the crate that uses it.
+
+use proc_macro::TokenStream;
+
+/// Expands to the answer, as an integer literal.
+#[proc_macro]
+pub fn answer(_input: TokenStream) -> TokenStream {
+    answer_value().to_string().parse().unwrap()
+}
+
+fn answer_value() -> u32 {
+    42
+}
@@ -0,0 +1,15 @@
+// Copyright 2022 Martin Pool
+
+//! Uses the proc macro from the parent package, and tests it.
+
+pub fn answer() -> u32 {
+    cargo_mutants_testdata_proc_macro::answer!()
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn answer_is_42() {
+        assert_eq!(super::answer(), 42);
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright 2022 Martin Pool
+
+//! A crate with a path dependency outside of its own directory, to check that cargo-mutants can
+//! still build it when it's copied to a scratch directory.
+
+use cargo_mutants_testdata_dependency::double;
+
+pub fn double_factorial(n: u32) -> u32 {
+    double((1..=n).product())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn double_factorial() {
+        assert_eq!(super::double_factorial(3), 12);
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::{BTreeSet, HashMap};
+
+/// Count how many times each word occurs.
+fn word_counts(text: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_default() += 1;
+    }
+    counts
+}
+
+/// Return the distinct lengths of the words.
+fn word_lengths(text: &str) -> BTreeSet<usize> {
+    text.split_whitespace().map(str::len).collect()
+}
+
+mod test {
+    #[test]
+    fn word_counts() {
+        use super::word_counts;
+        use std::collections::HashMap;
+
+        assert_eq!(
+            word_counts("the cat and the hat"),
+            HashMap::from([("the", 2), ("cat", 1), ("and", 1), ("hat", 1)])
+        );
+    }
+
+    #[test]
+    fn word_lengths() {
+        use super::word_lengths;
+        use std::collections::BTreeSet;
+
+        assert_eq!(word_lengths("the cat and a hat"), BTreeSet::from([1, 3]));
+    }
+}
@@ -8,9 +8,12 @@
 
 #![allow(unused, dead_code)]
 
+mod collections;
 mod inside_mod;
 mod item_mod;
 mod methods;
 mod nested_function;
+mod option;
 mod result;
 pub mod simple_fns;
+mod traits;
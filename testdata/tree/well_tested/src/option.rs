@@ -0,0 +1,14 @@
+/// Return the first even number, if there is one.
+fn first_even(numbers: &[u32]) -> Option<u32> {
+    numbers.iter().copied().find(|n| n % 2 == 0)
+}
+
+mod test {
+    #[test]
+    fn first_even() {
+        use super::first_even;
+
+        assert_eq!(first_even(&[1, 4, 6]), Some(4));
+        assert_eq!(first_even(&[1, 3]), None);
+    }
+}
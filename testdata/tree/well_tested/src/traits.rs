@@ -0,0 +1,25 @@
+pub trait Shape {
+    fn sides(&self) -> u32;
+
+    /// The number of corners, which for a polygon is the same as the number of sides.
+    fn corners(&self) -> u32 {
+        self.sides()
+    }
+}
+
+struct Triangle;
+
+impl Shape for Triangle {
+    fn sides(&self) -> u32 {
+        3
+    }
+}
+
+mod test {
+    #[test]
+    fn default_method_uses_the_implementation() {
+        use super::{Shape, Triangle};
+
+        assert_eq!(Triangle.corners(), 3);
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright 2022 Martin Pool
+
+use cargo_mutants_testdata_workspace_utils::triple;
+
+fn main() {
+    println!("{}", triple(14));
+}
+
+#[test]
+fn triple_14() {
+    assert_eq!(triple(14), 42);
+}
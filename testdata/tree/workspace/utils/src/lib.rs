@@ -0,0 +1,15 @@
+// Copyright 2022 Martin Pool
+
+//! A member of a virtual workspace.
+
+pub fn triple(a: u32) -> u32 {
+    a * 3
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn triple() {
+        assert_eq!(super::triple(3), 9);
+    }
+}
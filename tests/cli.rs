@@ -165,6 +165,242 @@ fn list_mutants_json_well_tested() {
         .assert_insta();
 }
 
+#[test]
+fn skipped_operators_generate_no_mutants() {
+    run_assert_cmd()
+        .args(["mutants", "--list", "--skip-operator", "xyzzy"])
+        .current_dir("testdata/tree/well_tested")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("with \"\".into()"))
+        .stdout(predicate::str::contains("xyzzy").not());
+}
+
+#[test]
+fn unknown_skipped_operator_is_an_error() {
+    run_assert_cmd()
+        .args(["mutants", "--list", "--skip-operator", "wibble"])
+        .current_dir("testdata/tree/well_tested")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown mutation operator \"wibble\"",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn plugin_replacements_are_listed() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempdir().unwrap();
+    let plugin_path = tmp.path().join("one");
+    fs::write(
+        &plugin_path,
+        "#!/bin/sh\ngrep -q '\"return_type\":\"-> u32\"' && echo '{\"replacements\": [\"1\"]}' || echo '{\"replacements\": []}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&plugin_path, fs::Permissions::from_mode(0o755)).unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--list", "--plugin"])
+        .arg(&plugin_path)
+        .current_dir("testdata/tree/factorial")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "src/bin/main.rs:7: replace factorial -> u32 with 1",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn mutants_that_dont_parse_are_bad_patches() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let plugin_path = tmp_src_dir.path().join("unbalanced");
+    fs::write(
+        &plugin_path,
+        "#!/bin/sh\ngrep -q '\"return_type\":\"-> u32\"' && echo '{\"replacements\": [\"(1\"]}' || echo '{\"replacements\": []}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&plugin_path, fs::Permissions::from_mode(0o755)).unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--plugin"])
+        .arg(&plugin_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .env("CLICOLOR_FORCE", "0")
+        .assert()
+        .stdout(predicate::str::contains(
+            "src/bin/main.rs:7: replace factorial -> u32 with (1 ... BAD PATCH",
+        ));
+    let log = fs::read_to_string(
+        tmp_src_dir
+            .path()
+            .join("mutants.out/log/replace_factorial_with_(1_in_src_bin_main.rs_7.log"),
+    )
+    .unwrap();
+    assert!(
+        log.contains("*** bad patch: mutated code doesn't parse: src/bin/main.rs:"),
+        "{}",
+        log
+    );
+    assert!(log.contains("*** mutated src/bin/main.rs:\n"), "{}", log);
+    assert!(!log.contains("cargo build"), "{}", log);
+}
+
+#[test]
+fn list_files_shows_excluded_build_script_and_generated_code() {
+    run_assert_cmd()
+        .args(["mutants", "--list-files", "-d", "testdata/tree/build_script"])
+        .env("CLICOLOR_FORCE", "0")
+        .assert()
+        .success()
+        .stderr("")
+        .stdout(
+            r#"src/lib.rs
+build.rs (excluded: build script)
+src/lib.rs:3: include!(concat ! (env ! ("OUT_DIR") , "/generated.rs")) (excluded: generated code in OUT_DIR)
+"#,
+        );
+}
+
+#[test]
+fn list_verbose_shows_skipped_code_and_why() {
+    run_assert_cmd()
+        .args(["mutants", "--list", "-v", "-d", "testdata/tree/build_script"])
+        .env("CLICOLOR_FORCE", "0")
+        .assert()
+        .success()
+        .stdout(
+            r#"src/lib.rs:5: replace double_answer -> u32 with Default::default()
+src/lib.rs:6: replace * with / in double_answer
+src/lib.rs:6: replace * with + in double_answer
+build.rs (skipped: excluded file: build script)
+src/lib.rs:3: include!(concat ! (env ! ("OUT_DIR") , "/generated.rs")) (skipped: excluded file: generated code in OUT_DIR)
+src/lib.rs:10: test_double_answer (skipped: test code)
+"#,
+        );
+}
+
+#[test]
+fn list_skipped_json_includes_skip_reasons() {
+    let list_json = |args: &[&str]| -> serde_json::Value {
+        let output = run_assert_cmd()
+            .arg("mutants")
+            .args(args)
+            .args(["--json", "-d", "testdata/tree/factorial"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice(&output).unwrap()
+    };
+    // The list of mutants has the same shape whether or not it's verbose.
+    assert_eq!(list_json(&["--list"]).as_array().unwrap().len(), 9);
+    assert_eq!(list_json(&["--list", "-v"]).as_array().unwrap().len(), 9);
+    assert_eq!(
+        list_json(&["--list-skipped"]),
+        serde_json::json!([{
+            "file": "src/bin/main.rs",
+            "name": "test_factorial",
+            "line": 16,
+            "reason": "test",
+        }])
+    );
+}
+
+#[test]
+fn list_files_in_virtual_workspace() {
+    run_assert_cmd()
+        .args(["mutants", "--list-files", "-d", "testdata/tree/workspace"])
+        .assert()
+        .success()
+        .stdout("main/src/main.rs\nutils/src/lib.rs\n");
+}
+
+#[test]
+fn virtual_workspace_members_are_mutated() {
+    let tmp_src_dir = copy_of_testdata("workspace");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "utils/src/lib.rs:5: replace triple -> u32 with Default::default() ... caught",
+        ))
+        .stdout(predicate::str::contains(
+            "main/src/main.rs:5: replace main with () ... NOT CAUGHT",
+        ));
+}
+
+#[test]
+fn mutants_in_binary_without_tests_are_marked_uncatchable() {
+    let tmp_src_dir = copy_of_testdata("hybrid");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "-d"])
+        .arg(tmp_src_dir.path())
+        .env("CLICOLOR_FORCE", "0")
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "binary hybrid has no tests of its own and there are no integration tests",
+        ))
+        .stdout(predicate::str::contains(
+            "src/lib.rs:4: replace double -> u32 with Default::default() ... caught\n",
+        ))
+        .stdout(predicate::str::contains(
+            "src/main.rs:1: replace main with () ... NOT CAUGHT (no tests can catch it)\n",
+        ));
+}
+
+#[test]
+fn proc_macro_mutants_are_caught_by_tests_of_dependent_crates() {
+    let tmp_src_dir = copy_of_testdata("proc_macro");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/lib.rs:9: replace answer -> TokenStream with Default::default() ... caught",
+        ))
+        .stdout(predicate::str::contains(
+            "src/lib.rs:13: replace answer_value -> u32 with Default::default() ... caught",
+        ));
+}
+
+#[test]
+fn feature_gated_mutant_is_missed_with_default_features() {
+    let tmp_src_dir = copy_of_testdata("feature_gated");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "src/lib.rs:6: replace double -> u32 with Default::default() ... NOT CAUGHT",
+        ));
+}
+
+#[test]
+fn feature_gated_mutant_is_caught_by_feature_matrix() {
+    let tmp_src_dir = copy_of_testdata("feature_gated");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "--feature-matrix", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/lib.rs:6: replace double -> u32 with Default::default() ... caught",
+        ));
+    let log = fs::read_to_string(tmp_src_dir.path().join("mutants.out/log/baseline.log")).unwrap();
+    assert!(log.contains("*** features: --all-features"), "{}", log);
+}
+
 #[test]
 fn copy_testdata_doesnt_include_build_artifacts() {
     // If there is a target or mutants.out in the source directory, we don't want it in the copy,
@@ -205,16 +441,86 @@ fn well_tested_tree_check_only() {
         }));
 }
 
+#[test]
+fn async_functions_are_mutated_and_caught() {
+    let tmp_src_dir = copy_of_testdata("async_fns");
+    run_assert_cmd()
+        .args(["mutants", "--list"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/lib.rs:15: replace is_even -> bool with false\n",
+        ))
+        .stdout(predicate::str::contains(
+            "src/lib.rs:23: replace halve -> impl Future < Output = Option < u32 > > with async { None }\n",
+        ));
+    run_assert_cmd()
+        .args(["mutants", "--no-times"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("18 mutants tested: 18 caught\n"));
+}
+
+#[test]
+fn offline_is_passed_to_every_cargo_invocation() {
+    let tmp_src_dir = copy_of_testdata("well_tested");
+    run_assert_cmd()
+        .args(["mutants", "--check", "--offline"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success();
+    let log_dir = tmp_src_dir.path().join("mutants.out/log");
+    for name in ["check_source_tree.log", "baseline.log"] {
+        let log = fs::read_to_string(log_dir.join(name)).unwrap();
+        assert!(log.contains(" check --offline"), "{}: {}", name, log);
+    }
+}
+
+#[test]
+fn clean_removes_output_dirs() {
+    let tmp_src_dir = copy_of_testdata("well_tested");
+    run_assert_cmd()
+        .args(["mutants", "--check"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success();
+    run_assert_cmd()
+        .args(["mutants", "--check"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success();
+    assert!(tmp_src_dir.path().join("mutants.out.old").is_dir());
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "clean"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed ").count(2));
+    assert!(!tmp_src_dir.path().join("mutants.out").exists());
+    assert!(!tmp_src_dir.path().join("mutants.out.old").exists());
+    run_assert_cmd()
+        .args(["mutants", "clean"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout("Nothing to clean\n");
+}
+
 #[test]
 fn uncaught_mutant_in_factorial() {
     let tmp_src_dir = copy_of_testdata("factorial");
 
-    let output_re = r"^build source tree \.\.\. ok in \d+\.\d\d\ds
+    let output_re = r#"^build source tree \.\.\. ok in \d+\.\d\d\ds
 copy source and build products to scratch directory \.\.\. \d+ MB in \d\.\d\d\ds
 baseline test with no mutations \.\.\. ok in \d+\.\d\d\ds
+9 mutants to test, estimated time \d+s
 src/bin/main\.rs:1: replace main with \(\) \.\.\. NOT CAUGHT in \d+\.\d\d\ds
-src/bin/main\.rs:7: replace factorial -> u32 with Default::default\(\) \.\.\. caught in \d+\.\d\d\ds
-$";
+src/bin/main\.rs:2: replace \.\.= with \.\. in main \.\.\. NOT CAUGHT in \d+\.\d\d\ds
+src/bin/main\.rs:3: delete println!\("\{\}! = \{\}", i, factorial\(i\)\); in main \.\.\. NOT CAUGHT in \d+\.\d\d\ds
+9 mutants tested: 6 caught, 3 missed
+$"#;
 
     run_assert_cmd()
         .arg("mutants")
@@ -242,6 +548,366 @@ $";
     let mutants_json =
         fs::read_to_string(tmp_src_dir.path().join("mutants.out/mutants.json")).unwrap();
     insta::assert_snapshot!(mutants_json);
+
+    // Mutants are listed in text files by their outcome.
+    let read_output =
+        |name: &str| fs::read_to_string(tmp_src_dir.path().join("mutants.out").join(name)).unwrap();
+    assert_eq!(
+        read_output("missed.txt"),
+        "replace main with () in src/bin/main.rs:1\n\
+         replace ..= with .. in main in src/bin/main.rs:2\n\
+         delete println!(\"{}! = {}\", i, factorial(i)); in main in src/bin/main.rs:3\n"
+    );
+    assert_eq!(
+        read_output("caught.txt"),
+        "replace factorial with Default::default() in src/bin/main.rs:7\n\
+         delete for i in 2..=n { a *= i; } in factorial in src/bin/main.rs:9\n\
+         replace ..= with .. in factorial in src/bin/main.rs:9\n\
+         delete a *= i; in factorial in src/bin/main.rs:10\n\
+         replace *= with /= in factorial in src/bin/main.rs:10\n\
+         replace *= with += in factorial in src/bin/main.rs:10\n"
+    );
+    assert_eq!(read_output("unviable.txt"), "");
+
+    // outcomes.json has the outcome of every scenario, including the baseline.
+    let outcomes: serde_json::Value = serde_json::from_str(&read_output("outcomes.json")).unwrap();
+    let outcomes = outcomes["outcomes"].as_array().unwrap();
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|outcome| outcome["status"].as_str().unwrap())
+            .collect_vec(),
+        [
+            "SourceBuildPassed",
+            "CleanTestPassed",
+            "MutantMissed",
+            "MutantMissed",
+            "MutantMissed",
+            "MutantCaught",
+            "MutantCaught",
+            "MutantCaught",
+            "MutantCaught",
+            "MutantCaught",
+            "MutantCaught"
+        ]
+    );
+    assert_eq!(outcomes[1]["scenario"], "Baseline");
+    assert_eq!(outcomes[1]["log_path"], "log/baseline.log");
+    assert_eq!(outcomes[5]["scenario"]["Mutant"]["function"], "factorial");
+    assert_eq!(
+        outcomes[9]["scenario"]["Mutant"]["genre"],
+        "binary_operator"
+    );
+}
+
+#[test]
+fn quiet_mode_prints_only_problems_and_a_summary() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--quiet", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stderr("")
+        .stdout(
+            "src/bin/main.rs:1: replace main with () ... NOT CAUGHT\n\
+             src/bin/main.rs:2: replace ..= with .. in main ... NOT CAUGHT\n\
+             src/bin/main.rs:3: delete println!(\"{}! = {}\", i, factorial(i)); in main ... NOT CAUGHT\n\
+             9 mutants tested: 6 caught, 3 missed\n",
+        );
+}
+
+#[test]
+fn group_by_file_prints_results_under_each_file() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--group-by-file", "-v", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stderr("")
+        .stdout(predicate::str::ends_with(
+            "src/bin/main.rs (9 mutants tested: 6 caught, 3 missed)\n\
+             \x20   line 1: replace main with () ... NOT CAUGHT\n\
+             \x20   line 2: replace ..= with .. in main ... NOT CAUGHT\n\
+             \x20   line 3: delete println!(\"{}! = {}\", i, factorial(i)); in main ... NOT CAUGHT\n\
+             \x20   line 7: replace factorial -> u32 with Default::default() ... caught\n\
+             \x20   line 9: delete for i in 2..=n { a *= i; } in factorial ... caught\n\
+             \x20   line 9: replace ..= with .. in factorial ... caught\n\
+             \x20   line 10: delete a *= i; in factorial ... caught\n\
+             \x20   line 10: replace *= with /= in factorial ... caught\n\
+             \x20   line 10: replace *= with += in factorial ... caught\n\
+             9 mutants tested: 6 caught, 3 missed\n",
+        ))
+        .stdout(predicate::str::contains(
+            "baseline test with no mutations ... ok\n",
+        ));
+}
+
+#[test]
+fn missed_log_lines_shows_the_end_of_the_log() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "--missed-log-lines", "2", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "src/bin/main.rs:1: replace main with () ... NOT CAUGHT\n    test test_factorial ... ok\n    test result: ok. 1 passed;",
+        ))
+        .stdout(predicate::str::contains("Default::default() ... caught\n"));
+}
+
+#[test]
+fn emit_plan_and_test_only_planned_mutants() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let plan_dir = tempfile::tempdir().unwrap();
+    let plan_path = plan_dir.path().join("plan.json");
+    run_assert_cmd()
+        .args(["mutants", "--emit-plan"])
+        .arg(&plan_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Wrote 9 mutants to "));
+    assert!(!tmp_src_dir.path().join("mutants.out").exists());
+
+    // Keep only the mutant of the whole body of factorial in the plan.
+    let mut plan: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&plan_path).unwrap()).unwrap();
+    plan["mutants"] = serde_json::json!([plan["mutants"][3]]);
+    fs::write(&plan_path, plan.to_string()).unwrap();
+
+    run_assert_cmd()
+        .args(["mutants", "--list", "--plan"])
+        .arg(&plan_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout("src/bin/main.rs:7: replace factorial -> u32 with Default::default()\n");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v", "--plan"])
+        .arg(&plan_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/bin/main.rs:7: replace factorial -> u32 with Default::default() ... caught",
+        ))
+        .stdout(predicate::str::contains("replace main").not());
+
+    // Edits that only move the planned function don't stop the plan applying.
+    let main_rs = tmp_src_dir.path().join("src/bin/main.rs");
+    let code = fs::read_to_string(&main_rs).unwrap();
+    fs::write(&main_rs, format!("// A new comment.\n\n{}", code)).unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--list", "--plan"])
+        .arg(&plan_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout("src/bin/main.rs:9: replace factorial -> u32 with Default::default()\n");
+
+    // If the source changes so that the planned mutant is no longer there, it's an error.
+    fs::write(
+        tmp_src_dir.path().join("src/bin/main.rs"),
+        "fn main() {}\n\nfn factorial(n: u32) -> u32 {\n    n\n}\n",
+    )
+    .unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--list", "--plan"])
+        .arg(&plan_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "(replace factorial with Default::default() in src/bin/main.rs:7) is not in the source tree",
+        ));
+}
+
+#[test]
+fn export_mutants_and_import_external_results() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let exchange_dir = tempfile::tempdir().unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--export-mutants"])
+        .arg(exchange_dir.path())
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Exported 9 mutants to "));
+    let plan: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(exchange_dir.path().join("plan.json")).unwrap())
+            .unwrap();
+    let main_id = plan["mutants"][0]["id"].as_str().unwrap();
+    let factorial_id = plan["mutants"][3]["id"].as_str().unwrap();
+    assert_eq!(plan["mutants"][3]["function"], "factorial");
+    let patch = fs::read_to_string(
+        exchange_dir
+            .path()
+            .join(format!("patches/{}.diff", factorial_id)),
+    )
+    .unwrap();
+    assert!(patch.starts_with("--- a/src/bin/main.rs\n+++ b/src/bin/main.rs\n"));
+    assert!(patch.contains("+Default::default()"));
+    // Nothing was tested.
+    assert!(!tmp_src_dir.path().join("mutants.out").exists());
+
+    fs::write(
+        exchange_dir.path().join("results.json"),
+        serde_json::json!({"results": {main_id: "missed", factorial_id: "caught"}}).to_string(),
+    )
+    .unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--import-results"])
+        .arg(exchange_dir.path())
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "src/bin/main.rs:1: replace main with () ... NOT CAUGHT",
+        ));
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/missed.txt")).unwrap(),
+        "replace main with () in src/bin/main.rs:1\n"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/caught.txt")).unwrap(),
+        "replace factorial with Default::default() in src/bin/main.rs:7\n"
+    );
+
+    // Results for mutants that aren't in the plan are an error.
+    fs::write(
+        exchange_dir.path().join("results.json"),
+        serde_json::json!({"results": {main_id: "missed", "7": "caught"}}).to_string(),
+    )
+    .unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--import-results"])
+        .arg(exchange_dir.path())
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "results.json has results for mutants that aren't in the plan: 7",
+        ));
+}
+
+#[test]
+fn ignored_mutants_are_not_tested() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    fs::write(
+        tmp_src_dir.path().join("mutants-ignore.toml"),
+        "[[ignore]]\nfunction = \"main\"\nreason = \"only prints\"\n",
+    )
+    .unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--no-times"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 mutants ignored by mutants-ignore.toml\n",
+        ))
+        .stdout(predicate::str::contains("replace main").not());
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/ignored.txt")).unwrap(),
+        "replace main with () in src/bin/main.rs:1\n\
+         replace ..= with .. in main in src/bin/main.rs:2\n\
+         delete println!(\"{}! = {}\", i, factorial(i)); in main in src/bin/main.rs:3\n"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/missed.txt")).unwrap(),
+        ""
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn verdict_command_can_ignore_missed_mutants() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    fs::create_dir(tmp_src_dir.path().join(".cargo")).unwrap();
+    fs::write(
+        tmp_src_dir.path().join(".cargo/mutants.toml"),
+        r#"verdict_command = ["sh", "-c", "grep -q '\"function\":\"main\"' && exit 10; exit 0"]"#,
+    )
+    .unwrap();
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-v"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/bin/main.rs:1: replace main with () ... ignored",
+        ));
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/ignored.txt")).unwrap(),
+        "replace main with () in src/bin/main.rs:1\n\
+         replace ..= with .. in main in src/bin/main.rs:2\n\
+         delete println!(\"{}! = {}\", i, factorial(i)); in main in src/bin/main.rs:3\n"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/caught.txt")).unwrap(),
+        "replace factorial with Default::default() in src/bin/main.rs:7\n\
+         delete for i in 2..=n { a *= i; } in factorial in src/bin/main.rs:9\n\
+         replace ..= with .. in factorial in src/bin/main.rs:9\n\
+         delete a *= i; in factorial in src/bin/main.rs:10\n\
+         replace *= with /= in factorial in src/bin/main.rs:10\n\
+         replace *= with += in factorial in src/bin/main.rs:10\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn max_mutants_per_function_keeps_every_operator() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempdir().unwrap();
+    let plugin_path = tmp.path().join("many");
+    fs::write(
+        &plugin_path,
+        "#!/bin/sh\ngrep -q '\"return_type\":\"-> u32\"' && echo '{\"replacements\": [\"1\", \"2\", \"3\", \"4\"]}' || echo '{\"replacements\": []}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&plugin_path, fs::Permissions::from_mode(0o755)).unwrap();
+    let list = |seed: &str| {
+        let output = run_assert_cmd()
+            .args([
+                "mutants",
+                "--list",
+                "--max-mutants-per-function",
+                "2",
+                "--seed",
+                seed,
+                "--plugin",
+            ])
+            .arg(&plugin_path)
+            .current_dir("testdata/tree/factorial")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let listed = list("0");
+    let lines: Vec<&str> = listed.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", listed);
+    assert_eq!(lines[0], "src/bin/main.rs:1: replace main with ()");
+    assert_eq!(lines[1], "src/bin/main.rs:2: replace ..= with .. in main");
+    assert_eq!(
+        lines[2],
+        "src/bin/main.rs:7: replace factorial -> u32 with Default::default()"
+    );
+    assert_eq!(list("0"), listed);
 }
 
 #[test]
@@ -273,6 +939,25 @@ r"src/bin/main\.rs:7: replace factorial -> u32 with Default::default\(\) \.\.\.
         ).unwrap());
 }
 
+#[test]
+fn relative_path_dependency_outside_tree_is_found_from_scratch_dir() {
+    let tmp = tempdir().unwrap();
+    for tree_name in ["relative_dependency", "dependency"] {
+        cp_r::CopyOptions::new()
+            .filter(|path, _stat| Ok(!path.starts_with("target")))
+            .copy_tree(
+                Path::new("testdata/tree").join(tree_name),
+                tmp.path().join(tree_name),
+            )
+            .unwrap();
+    }
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "-d"])
+        .arg(tmp.path().join("relative_dependency"))
+        .assert()
+        .success();
+}
+
 #[test]
 fn check_succeds_in_tree_that_builds_but_fails_tests() {
     // --check doesn't actually run the tests so won't discover that they fail.
@@ -308,6 +993,34 @@ fn check_tree_with_mutants_skip() {
         }));
 }
 
+#[test]
+fn hanging_mutant_is_stopped_by_timeout() {
+    let tmp_src_dir = copy_of_testdata("hang_when_mutated");
+    run_assert_cmd()
+        .args(["mutants", "--no-times", "--timeout", "5"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "src/lib.rs:5: replace should_stop -> bool with false ... TIMEOUT",
+        ));
+    assert_eq!(
+        fs::read_to_string(tmp_src_dir.path().join("mutants.out/timeout.txt")).unwrap(),
+        "replace should_stop with false in src/lib.rs:5\n\
+         replace true with false in should_stop in src/lib.rs:6\n\
+         replace should_stop() with !(should_stop()) in controlled_loop in src/lib.rs:13\n"
+    );
+}
+
+#[test]
+fn zero_timeout_is_a_usage_error() {
+    run_assert_cmd()
+        .args(["mutants", "--timeout", "0", "-d", "testdata/tree/factorial"])
+        .assert()
+        .code(1)
+        .stderr("--timeout must be a positive number of seconds\n");
+}
+
 #[test]
 fn already_failing_tests_are_detected_before_running_mutants() {
     let tmp_src_dir = copy_of_testdata("already_failing_tests");